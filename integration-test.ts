@@ -47,7 +47,7 @@ describe("Complete AMM Integration Tests", () => {
 
     // Setup: 创建AMM
     await program.methods
-      .createAmm(values.id, values.fee)
+      .createAmm(values.id, values.fee, new anchor.BN(values.feeDelay))
       .accounts({
         amm: values.ammPda,
         admin: values.admin.publicKey,
@@ -83,6 +83,8 @@ describe("Complete AMM Integration Tests", () => {
         poolAccountB: values.poolAccountB,
         payer: provider.wallet.publicKey,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -101,9 +103,14 @@ describe("Complete AMM Integration Tests", () => {
     await program.methods
       .depositLiquidity(
         new anchor.BN(initialAmountA),
-        new anchor.BN(initialAmountB)
+        new anchor.BN(initialAmountB),
+        new anchor.BN(0),
+        null,
+        null,
+        null
       )
       .accounts({
+        amm: values.ammPda,
         pool: values.poolPda,
         poolAuthority: values.poolAuthority,
         mintLiquidity: values.mintLiquidity,
@@ -129,6 +136,8 @@ describe("Complete AMM Integration Tests", () => {
         depositor: liquidityProvider1.publicKey,
         payer: provider.wallet.publicKey,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -166,9 +175,14 @@ describe("Complete AMM Integration Tests", () => {
     await program.methods
       .depositLiquidity(
         new anchor.BN(secondAmountA),
-        new anchor.BN(secondAmountB)
+        new anchor.BN(secondAmountB),
+        new anchor.BN(0),
+        null,
+        null,
+        null
       )
       .accounts({
+        amm: values.ammPda,
         pool: values.poolPda,
         poolAuthority: values.poolAuthority,
         mintLiquidity: values.mintLiquidity,
@@ -194,6 +208,8 @@ describe("Complete AMM Integration Tests", () => {
         depositor: liquidityProvider2.publicKey,
         payer: provider.wallet.publicKey,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -254,7 +270,8 @@ describe("Complete AMM Integration Tests", () => {
       .swapExactTokensForTokens(
         true, // A -> B
         new anchor.BN(swapAmountA),
-        new anchor.BN(250 * 10 ** 6) // 期望至少得到250B
+        new anchor.BN(250 * 10 ** 6), // 期望至少得到250B
+        null
       )
       .accounts({
         amm: values.ammPda,
@@ -272,7 +289,8 @@ describe("Complete AMM Integration Tests", () => {
         ),
         traderAccountB: trader1TokenB,
         payer: provider.wallet.publicKey,
-        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -304,7 +322,8 @@ describe("Complete AMM Integration Tests", () => {
       .swapExactTokensForTokens(
         false, // B -> A
         new anchor.BN(swapAmountB),
-        new anchor.BN(80 * 10 ** 6) // 期望至少得到80A
+        new anchor.BN(80 * 10 ** 6), // 期望至少得到80A
+        null
       )
       .accounts({
         amm: values.ammPda,
@@ -322,7 +341,8 @@ describe("Complete AMM Integration Tests", () => {
           true
         ),
         payer: provider.wallet.publicKey,
-        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -390,7 +410,12 @@ describe("Complete AMM Integration Tests", () => {
       .amount;
 
     await program.methods
-      .withdrawLiquidity(new anchor.BN(withdrawAmount.toString()))
+      .withdrawLiquidity(
+        new anchor.BN(withdrawAmount.toString()),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        null
+      )
       .accounts({
         pool: values.poolPda,
         poolAuthority: values.poolAuthority,
@@ -409,6 +434,8 @@ describe("Complete AMM Integration Tests", () => {
         depositor: liquidityProvider1.publicKey,
         payer: provider.wallet.publicKey,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -509,9 +536,14 @@ describe("Complete AMM Integration Tests", () => {
     await program.methods
       .depositLiquidity(
         new anchor.BN(2000 * 10 ** 6),
-        new anchor.BN(4000 * 10 ** 6)
+        new anchor.BN(4000 * 10 ** 6),
+        new anchor.BN(0),
+        null,
+        null,
+        null
       )
       .accounts({
+        amm: values.ammPda,
         pool: values.poolPda,
         poolAuthority: values.poolAuthority,
         mintLiquidity: values.mintLiquidity,
@@ -537,6 +569,8 @@ describe("Complete AMM Integration Tests", () => {
         depositor: liquidityProvider1.publicKey,
         payer: provider.wallet.publicKey,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -555,9 +589,14 @@ describe("Complete AMM Integration Tests", () => {
       program.methods
         .depositLiquidity(
           new anchor.BN(300 * 10 ** 6),
-          new anchor.BN(600 * 10 ** 6)
+          new anchor.BN(600 * 10 ** 6),
+          new anchor.BN(0),
+          null,
+          null,
+          null
         )
         .accounts({
+          amm: values.ammPda,
           pool: values.poolPda,
           poolAuthority: values.poolAuthority,
           mintLiquidity: values.mintLiquidity,
@@ -583,6 +622,8 @@ describe("Complete AMM Integration Tests", () => {
           depositor: liquidityProvider2.publicKey,
           payer: provider.wallet.publicKey,
           tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: anchor.web3.SystemProgram.programId,
         } as any)
@@ -596,7 +637,8 @@ describe("Complete AMM Integration Tests", () => {
         .swapExactTokensForTokens(
           true,
           new anchor.BN(100 * 10 ** 6),
-          new anchor.BN(1)
+          new anchor.BN(1),
+          null
         )
         .accounts({
           amm: values.ammPda,
@@ -618,7 +660,8 @@ describe("Complete AMM Integration Tests", () => {
             true
           ),
           payer: provider.wallet.publicKey,
-          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: anchor.web3.SystemProgram.programId,
         } as any)
@@ -632,7 +675,8 @@ describe("Complete AMM Integration Tests", () => {
         .swapExactTokensForTokens(
           false,
           new anchor.BN(150 * 10 ** 6),
-          new anchor.BN(1)
+          new anchor.BN(1),
+          null
         )
         .accounts({
           amm: values.ammPda,
@@ -654,7 +698,8 @@ describe("Complete AMM Integration Tests", () => {
             true
           ),
           payer: provider.wallet.publicKey,
-          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: anchor.web3.SystemProgram.programId,
         } as any)
@@ -702,9 +747,14 @@ describe("Complete AMM Integration Tests", () => {
     await program.methods
       .depositLiquidity(
         new anchor.BN(5000 * 10 ** 6),
-        new anchor.BN(10000 * 10 ** 6)
+        new anchor.BN(10000 * 10 ** 6),
+        new anchor.BN(0),
+        null,
+        null,
+        null
       )
       .accounts({
+        amm: values.ammPda,
         pool: values.poolPda,
         poolAuthority: values.poolAuthority,
         mintLiquidity: values.mintLiquidity,
@@ -730,6 +780,8 @@ describe("Complete AMM Integration Tests", () => {
         depositor: liquidityProvider1.publicKey,
         payer: provider.wallet.publicKey,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -754,7 +806,8 @@ describe("Complete AMM Integration Tests", () => {
         .swapExactTokensForTokens(
           swapA,
           new anchor.BN(swapAmount),
-          new anchor.BN(1)
+          new anchor.BN(1),
+          null
         )
         .accounts({
           amm: values.ammPda,
@@ -776,7 +829,8 @@ describe("Complete AMM Integration Tests", () => {
             true
           ),
           payer: provider.wallet.publicKey,
-          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: anchor.web3.SystemProgram.programId,
         } as any)
@@ -12,3 +12,74 @@ pub const AUTHORITY_SEED: &[u8] = b"authority";
 #[constant]
 pub const LIQUIDITY_SEED: &[u8] = b"liquidity";
 
+// The longest delay in seconds before a proposed fee takes effect (30 days)
+#[constant]
+pub const MAX_FEE_DELAY: i64 = 2_592_000;
+
+// The maximum number of pools in one swap_route
+#[constant]
+pub const MAX_ROUTE_LEGS: u8 = 4;
+
+// The number of tokens a basket pool can hold
+#[constant]
+pub const MIN_BASKET_TOKENS: u8 = 3;
+
+#[constant]
+pub const MAX_BASKET_TOKENS: u8 = 8;
+
+// The range of the amplification coefficient of StableSwap pools
+#[constant]
+pub const MIN_AMP: u64 = 1;
+
+#[constant]
+pub const MAX_AMP: u64 = 1_000_000;
+
+// The shortest amp ramp in seconds, and the largest factor one ramp can change amp by
+#[constant]
+pub const MIN_AMP_RAMP_DURATION: i64 = 86400;
+
+#[constant]
+pub const MAX_AMP_CHANGE: u64 = 10;
+
+// Token weights of weighted pools in basis points: each at least MIN_WEIGHT, adding up to TOTAL_WEIGHT
+#[constant]
+pub const MIN_WEIGHT: u16 = 100;
+
+#[constant]
+pub const TOTAL_WEIGHT: u16 = 10000;
+
+// Seeds of the tick arrays and positions of concentrated liquidity pools
+#[constant]
+pub const TICK_ARRAY_SEED: &[u8] = b"tick_array";
+
+#[constant]
+pub const POSITION_SEED: &[u8] = b"position";
+
+// The tick range of concentrated liquidity pools: prices from 1.0001^MIN_TICK to 1.0001^MAX_TICK
+#[constant]
+pub const MIN_TICK: i32 = -443636;
+
+#[constant]
+pub const MAX_TICK: i32 = 443636;
+
+// Square roots of the prices at MIN_TICK and MAX_TICK, as Q64.64 fixed-point numbers
+pub const MIN_SQRT_PRICE: u128 = 4295048017;
+
+pub const MAX_SQRT_PRICE: u128 = 79226673515401279992447579062;
+
+#[constant]
+pub const MAX_TICK_SPACING: u16 = 1000;
+
+// The number of ticks stored in one tick array
+pub const TICK_ARRAY_SIZE: usize = 32;
+
+// The maximum number of tick arrays one concentrated liquidity swap can cross
+#[constant]
+pub const MAX_SWAP_TICK_ARRAYS: u8 = 3;
+
+#[constant]
+pub const ORACLE_SEED: &[u8] = b"oracle";
+
+// The maximum number of observations an oracle can keep, bounded by the account size limit of init
+#[constant]
+pub const MAX_ORACLE_OBSERVATIONS: u16 = 200;
@@ -0,0 +1,608 @@
+use anchor_lang::prelude::*;
+use fixed::types::I64F64;
+
+use crate::{
+    constants::MINIMUM_LIQUIDITY,
+    errors::TutorialError,
+    math::{self, U256},
+};
+
+// 曲线的纯计算：不依赖Context和账户，链下报价直接调用这些函数，
+// 和链上的结果逐位一致
+// 支持三种曲线：恒定乘积x*y=k（默认）、StableSwap（适合USDC/USDT这类锚定同一价格的代币对）
+// 和加权乘积x^w_x * y^w_y = k（Balancer那样的80/20池子）
+// 所有数量都是池子实际收到/转出的数量（已经扣掉Token-2022的转账手续费），fee都是基点
+// 取整方向总是对池子有利：池子收到的向上取整，池子付出的向下取整
+
+/// The invariant used to price a pool, with its parameters
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Curve {
+    /// x * y = k
+    ConstantProduct,
+    /// Curve-style amplified invariant, see `math::stable_invariant`
+    StableSwap { amp: u64 },
+    /// Balancer-style weighted product, weights of the first (x) and second (y) reserve
+    /// passed to the curve functions, in basis points
+    Weighted { weight_x: u16, weight_y: u16 },
+}
+
+impl Curve {
+    /// The curve with its parameters ordered for a swap: `reserve_in` is token A when `swap_a`
+    pub fn oriented(self, swap_a: bool) -> Curve {
+        match self {
+            Curve::Weighted { weight_x, weight_y } if !swap_a => Curve::Weighted {
+                weight_x: weight_y,
+                weight_y: weight_x,
+            },
+            curve => curve,
+        }
+    }
+}
+
+/// Result of a swap against the curve
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SwapQuote {
+    /// Amount of the input token received by the pool
+    pub input: u64,
+    /// Amount of the output token sent by the pool
+    pub output: u64,
+    /// Trading fee taken from `input`, LP and protocol parts together
+    pub fee_amount: u64,
+    /// Part of `fee_amount` kept by the protocol
+    pub protocol_fee_amount: u64,
+}
+
+// 交易前后的不变量，swap之后不能变小
+pub fn invariant(curve: Curve, reserve_a: u64, reserve_b: u64) -> Result<u128> {
+    match curve {
+        Curve::ConstantProduct => Ok(math::invariant(reserve_a, reserve_b)),
+        Curve::StableSwap { amp } => math::stable_invariant(amp, &[reserve_a, reserve_b]),
+        Curve::Weighted { weight_x, weight_y } => {
+            math::weighted_log_invariant(reserve_a, reserve_b, weight_x, weight_y)
+        }
+    }
+}
+
+// 输入：input
+// 计算：output = ?
+// new_x = old_x + taxed_input
+// new_y = k / new_x（StableSwap：解出不变量D不变时的new_y）
+// 加权：new_y = old_y * (old_x / new_x)^(weight_x / weight_y)
+// output = old_y - new_y = taxed_input * old_y / (old_x + taxed_input)，向下取整
+pub fn swap_exact_input(
+    curve: Curve,
+    input: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    fee: u16,
+    protocol_fee: u16,
+) -> Result<SwapQuote> {
+    let fee_amount = math::mul_div(input, fee as u64, 10000)?;
+    let taxed_input = math::sub(input, fee_amount)?;
+    let output = match curve {
+        Curve::ConstantProduct => {
+            math::mul_div(taxed_input, reserve_out, math::add(reserve_in, taxed_input)?)?
+        }
+        Curve::StableSwap { amp } => {
+            // stable_reserve向上取整，output因此向下取整
+            let d = math::stable_invariant(amp, &[reserve_in, reserve_out])?;
+            let new_reserve_out = math::stable_reserve(amp, &[math::add(reserve_in, taxed_input)?, 0], 1, d)?;
+            reserve_out.saturating_sub(new_reserve_out)
+        }
+        Curve::Weighted { weight_x, weight_y } => {
+            // 幂向上取整，output = old_y * (1 - ratio)因此向下取整
+            let new_reserve_in = math::add(reserve_in, taxed_input)?;
+            let ratio = math::pow(reserve_in, new_reserve_in, weight_x as u64, weight_y as u64, true)?;
+            math::mul_fixed(reserve_out, I64F64::ONE.saturating_sub(ratio).max(I64F64::ZERO), false)?
+        }
+    };
+
+    Ok(SwapQuote {
+        input,
+        output,
+        fee_amount,
+        protocol_fee_amount: math::mul_div(fee_amount, protocol_fee as u64, 10000)?,
+    })
+}
+
+// 输入：output
+// 计算：input = ?（swap_exact_input的反推）
+// taxed_input = output * old_x / (old_y - output)（StableSwap：new_x - old_x）
+// 加权：taxed_input = old_x * ((old_y / (old_y - output))^(weight_y / weight_x) - 1)
+// input = taxed_input / (1 - fee)
+// 两步都向上取整，保证 swap_exact_input(input).output >= output
+// （StableSwap的new_x由stable_reserve向上取整，保证交易后不变量D不会变小）
+pub fn swap_exact_output(
+    curve: Curve,
+    output: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    fee: u16,
+    protocol_fee: u16,
+) -> Result<SwapQuote> {
+    // 池子不可能给出全部（或更多）的储备
+    if output >= reserve_out {
+        return err!(TutorialError::InsufficientLiquidity);
+    }
+
+    let taxed_input = match curve {
+        Curve::ConstantProduct => math::mul_div_ceil(output, reserve_in, reserve_out - output)?,
+        Curve::StableSwap { amp } => {
+            let d = math::stable_invariant(amp, &[reserve_in, reserve_out])?;
+            let new_reserve_in = math::stable_reserve(amp, &[0, reserve_out - output], 0, d)?;
+            new_reserve_in.saturating_sub(reserve_in)
+        }
+        Curve::Weighted { weight_x, weight_y } => {
+            let ratio = math::pow(reserve_out, reserve_out - output, weight_y as u64, weight_x as u64, true)?;
+            math::mul_fixed(reserve_in, ratio.saturating_sub(I64F64::ONE), true)?
+        }
+    };
+    // input - input * fee / 10000 >= taxed_input，手续费算法和swap_exact_input一致
+    let input = math::mul_div_ceil(taxed_input, 10000, 10000 - fee as u64)?;
+    let fee_amount = math::mul_div(input, fee as u64, 10000)?;
+
+    Ok(SwapQuote {
+        input,
+        output,
+        fee_amount,
+        protocol_fee_amount: math::mul_div(fee_amount, protocol_fee as u64, 10000)?,
+    })
+}
+
+// 按池子现有比例调整存款数量，同时确保不超过用户余额
+// 空池子（第一次存款）按原样存入，由存款人设置价格
+pub fn deposit_amounts(
+    amount_a: u64,
+    amount_b: u64,
+    reserve_a: u64,
+    reserve_b: u64,
+    balance_a: u64,
+    balance_b: u64,
+) -> Result<(u64, u64)> {
+    if reserve_a == 0 && reserve_b == 0 {
+        return Ok((amount_a, amount_b));
+    }
+
+    let ratio = math::checked_div(math::to_fixed(reserve_a)?, math::to_fixed(reserve_b)?)?;
+
+    // 选项1：以amount_a为基准，计算需要的amount_b
+    let option1_a = amount_a;
+    let option1_b = math::to_u64(math::checked_div(math::to_fixed(amount_a)?, ratio)?)?;
+
+    // 选项2：以amount_b为基准，计算需要的amount_a
+    let option2_b = amount_b;
+    let option2_a = math::to_u64(math::checked_mul(math::to_fixed(amount_b)?, ratio)?)?;
+
+    // 检查选项可行性
+    let option1_valid = option1_a <= balance_a && option1_b <= balance_b;
+    let option2_valid = option2_a <= balance_a && option2_b <= balance_b;
+
+    let amounts = if option1_valid && option2_valid {
+        // 两个都可行，选择存款量更大的
+        if option1_a as u128 + option1_b as u128 >= option2_a as u128 + option2_b as u128 {
+            (option1_a, option1_b)
+        } else {
+            (option2_a, option2_b)
+        }
+    } else if option1_valid {
+        (option1_a, option1_b)
+    } else if option2_valid {
+        (option2_a, option2_b)
+    } else {
+        // 都不可行，计算在余额限制下的最优解
+        let constrained_by_a = (balance_a, math::to_u64(math::checked_div(math::to_fixed(balance_a)?, ratio)?)?);
+        let constrained_by_b = (math::to_u64(math::checked_mul(math::to_fixed(balance_b)?, ratio)?)?, balance_b);
+
+        if constrained_by_a.1 <= balance_b {
+            constrained_by_a
+        } else if constrained_by_b.0 <= balance_a {
+            constrained_by_b
+        } else {
+            // 极端情况，取能取到的最小值
+            (balance_a.min(constrained_by_b.0), balance_b.min(constrained_by_a.1))
+        }
+    };
+
+    Ok(amounts)
+}
+
+// 存入amount_a、amount_b铸造的LP数量
+// 第一次存款（储备为空）：LP = 存入数量的流动性度量，锁定MINIMUM_LIQUIDITY，防止池子被清空后LP价格被操纵
+// 之后的存款：按流动性度量的增长比例铸造 total_liquidity * (L_after - L_before) / L_before
+// 手续费让储备（和L）增长之后，新存款人按增长后的L换算，不会稀释已有的LP
+// 流动性度量：恒定乘积是sqrt(x * y)，StableSwap是不变量D，加权曲线是加权几何平均x^weight_x * y^weight_y，
+// 都是一次齐次的，按比例存款时L的增长比例等于存入数量占储备的比例
+// 计算L时的取整误差可能对存款人有利，所以结果不超过按储备比例算出的份额
+// total_liquidity包含被锁定的MINIMUM_LIQUIDITY
+pub fn deposit_liquidity(
+    curve: Curve,
+    amount_a: u64,
+    amount_b: u64,
+    reserve_a: u64,
+    reserve_b: u64,
+    total_liquidity: u64,
+) -> Result<u64> {
+    if reserve_a == 0 && reserve_b == 0 {
+        let liquidity = liquidity_measure(curve, amount_a, amount_b)?;
+        if liquidity < MINIMUM_LIQUIDITY {
+            return err!(TutorialError::DepositTooSmall);
+        }
+        return Ok(liquidity - MINIMUM_LIQUIDITY);
+    }
+
+    let before = liquidity_measure(curve, reserve_a, reserve_b)?;
+    let after = liquidity_measure(curve, math::add(reserve_a, amount_a)?, math::add(reserve_b, amount_b)?)?;
+    let liquidity = math::mul_div(total_liquidity, after.saturating_sub(before), before)?;
+
+    let share = math::mul_div(amount_a, total_liquidity, reserve_a)?
+        .min(math::mul_div(amount_b, total_liquidity, reserve_b)?);
+    Ok(liquidity.min(share))
+}
+
+// 两种代币的流动性度量，向下取整
+fn liquidity_measure(curve: Curve, x: u64, y: u64) -> Result<u64> {
+    match curve {
+        Curve::ConstantProduct => Ok(math::sqrt_product(x, y)),
+        Curve::StableSwap { amp } => u64::try_from(math::stable_invariant(amp, &[x, y])?)
+            .map_err(|_| error!(TutorialError::MathOverflow)),
+        Curve::Weighted { weight_x, weight_y } => weighted_mean(x, y, weight_x, weight_y),
+    }
+}
+
+// 加权几何平均 x^weight_x * y^weight_y（权重之和为1），向下取整
+// 以较大的一边为底：x * (y / x)^weight_y，幂不超过1，不会溢出
+fn weighted_mean(x: u64, y: u64, weight_x: u16, weight_y: u16) -> Result<u64> {
+    if x == 0 || y == 0 {
+        return Ok(0);
+    }
+
+    let total = weight_x as u64 + weight_y as u64;
+    if x >= y {
+        math::mul_fixed(x, math::pow(y, x, weight_y as u64, total, false)?, false)
+    } else {
+        math::mul_fixed(y, math::pow(x, y, weight_x as u64, total, false)?, false)
+    }
+}
+
+// 销毁liquidity个LP能取回的代币：按LP占比分配储备，向下取整
+// total_liquidity包含被锁定的MINIMUM_LIQUIDITY
+pub fn withdraw_amounts(
+    liquidity: u64,
+    reserve_a: u64,
+    reserve_b: u64,
+    total_liquidity: u64,
+) -> Result<(u64, u64)> {
+    Ok((
+        math::mul_div(liquidity, reserve_a, total_liquidity)?,
+        math::mul_div(liquidity, reserve_b, total_liquidity)?,
+    ))
+}
+
+// 单边存款（zap）：只存入一种代币时，先把其中swap_amount换成另一种代币，剩下的和换到的按新比例存入
+// 换出之后池子的比例要和剩下的数量一致，设 g = 1 - fee，R = reserve_in，A = amount_in，s = swap_amount
+// 协议手续费 p * fee * s 不留在储备里，令 h = 1 - p * fee，swap之后的储备是 R + h * s：
+// (A - s) / (R + h * s) = g * s / R  （换到的数量 g * s * R_out / (R + g * s) 按新比例折算）
+// 整理得 g * h * s^2 + R * (1 + g) * s - A * R = 0，取正根：
+// s = (sqrt(R^2 * (1 + g)^2 + 4 * g * h * A * R) - R * (1 + g)) / (2 * g * h)
+// fee和protocol_fee是基点，令 G = 10000 - fee，H = 10^8 - protocol_fee * fee，分子分母同乘10^12：
+// s = (sqrt(b^2 + 4 * 10^12 * G * H * A * R) - b) / (2 * G * H)，b = 10^8 * R * (10000 + G)，向下取整
+// 只有恒定乘积有这样的闭式解，StableSwap和加权池子返回InvalidCurve
+pub fn single_sided_swap_amount(
+    curve: Curve,
+    amount_in: u64,
+    reserve_in: u64,
+    fee: u16,
+    protocol_fee: u16,
+) -> Result<u64> {
+    if curve != Curve::ConstantProduct {
+        return err!(TutorialError::InvalidCurve);
+    }
+    let g = 10000 - fee as u64;
+    if g == 0 {
+        return err!(TutorialError::DivisionByZero);
+    }
+    let h = 100_000_000 - protocol_fee as u64 * fee as u64;
+
+    // b^2和4 * 10^12 * G * H * A * R都不超过2^215，U256放得下
+    let b = math::u256_mul(U256::from(reserve_in), U256::from(100_000_000 * (10000 + g)))?;
+    let product = math::u256_mul(
+        math::u256_mul(U256::from(4_000_000_000_000u64), U256::from(g * h))?,
+        math::u256_mul(U256::from(amount_in), U256::from(reserve_in))?,
+    )?;
+    let discriminant = math::u256_add(math::u256_mul(b, b)?, product)?;
+    math::u256_to_u64(math::u256_div(
+        math::u256_sub(discriminant.integer_sqrt(), b)?,
+        U256::from(2 * g * h),
+    )?)
+}
+
+// 价格冲击：按交易前的现价能换到的数量和实际输出之差，以基点表示，不含手续费
+// spot_output = taxed_input * old_y / old_x
+// StableSwap的现价是不变量曲线的斜率：
+// spot_output = taxed_input * (4 * ann * x * y + D^3 / x) / (4 * ann * x * y + D^3 / y)
+// 加权曲线的现价：spot_output = taxed_input * (old_y / weight_y) / (old_x / weight_x)
+// price_impact = (spot_output - output) / spot_output
+pub fn price_impact(curve: Curve, quote: &SwapQuote, reserve_in: u64, reserve_out: u64) -> Result<u16> {
+    let taxed_input = math::sub(quote.input, quote.fee_amount)?;
+    let spot_output = match curve {
+        Curve::ConstantProduct => math::mul_div(taxed_input, reserve_out, reserve_in)?,
+        Curve::StableSwap { amp } => {
+            if reserve_in == 0 || reserve_out == 0 {
+                return err!(TutorialError::DivisionByZero);
+            }
+            let d = U256::from(math::stable_invariant(amp, &[reserve_in, reserve_out])?);
+            let d3 = d * d * d;
+            let axy = U256::from(amp) * 8 * U256::from(reserve_in) * U256::from(reserve_out);
+            let numerator = axy + d3 / U256::from(reserve_in);
+            let denominator = axy + d3 / U256::from(reserve_out);
+            let spot_output = U256::from(taxed_input)
+                .checked_mul(numerator)
+                .ok_or_else(|| error!(TutorialError::MathOverflow))?
+                / denominator;
+            u64::try_from(spot_output).map_err(|_| error!(TutorialError::MathOverflow))?
+        }
+        Curve::Weighted { weight_x, weight_y } => math::mul_div(
+            math::mul_div(taxed_input, reserve_out, reserve_in)?,
+            weight_x as u64,
+            weight_y as u64,
+        )?,
+    };
+    if spot_output == 0 {
+        return Ok(0);
+    }
+
+    // output <= spot_output，结果不超过10000
+    let price_impact = math::mul_div(spot_output.saturating_sub(quote.output), 10000, spot_output)?;
+    Ok(price_impact as u16)
+}
+
+// 篮子池子（三种及以上代币）：交换只改变第i、j种代币的储备
+// 恒定乘积：其他代币的储备不变，prod(x_k)不变等价于 x_i * x_j 不变，和两种代币的公式一样
+// StableSwap：用n种代币的D解出新的x_j
+// 协议手续费和两种代币的池子一样，是手续费的protocol_fee/10000，留在输入代币的vault里
+pub fn basket_swap_exact_input(
+    curve: Curve,
+    input: u64,
+    reserves: &[u64],
+    input_index: usize,
+    output_index: usize,
+    fee: u16,
+    protocol_fee: u16,
+) -> Result<SwapQuote> {
+    match curve {
+        Curve::ConstantProduct => {
+            swap_exact_input(curve, input, reserves[input_index], reserves[output_index], fee, protocol_fee)
+        }
+        Curve::StableSwap { amp } => {
+            let fee_amount = math::mul_div(input, fee as u64, 10000)?;
+            let taxed_input = math::sub(input, fee_amount)?;
+
+            let d = math::stable_invariant(amp, reserves)?;
+            let mut balances = reserves.to_vec();
+            balances[input_index] = math::add(reserves[input_index], taxed_input)?;
+            let new_reserve_out = math::stable_reserve(amp, &balances, output_index, d)?;
+
+            Ok(SwapQuote {
+                input,
+                output: reserves[output_index].saturating_sub(new_reserve_out),
+                fee_amount,
+                protocol_fee_amount: math::mul_div(fee_amount, protocol_fee as u64, 10000)?,
+            })
+        }
+        Curve::Weighted { .. } => err!(TutorialError::InvalidCurve),
+    }
+}
+
+// 篮子池子交换前后的不变量，只有第i、j种代币的储备会变
+pub fn basket_invariant(
+    curve: Curve,
+    reserves: &[u64],
+    input_index: usize,
+    output_index: usize,
+) -> Result<u128> {
+    match curve {
+        Curve::ConstantProduct => Ok(math::invariant(reserves[input_index], reserves[output_index])),
+        Curve::StableSwap { amp } => math::stable_invariant(amp, reserves),
+        Curve::Weighted { .. } => err!(TutorialError::InvalidCurve),
+    }
+}
+
+// 篮子池子第一次存款铸造的LP数量，锁定MINIMUM_LIQUIDITY
+// 恒定乘积用几何平均 (prod(amount_k))^(1/n)，StableSwap用不变量D，都是一次齐次的
+// 以最大的数量为底：max * prod((amount_k / max)^(1/n))，每个幂都不超过1，向下取整
+pub fn basket_initial_liquidity(curve: Curve, amounts: &[u64]) -> Result<u64> {
+    let liquidity = match curve {
+        Curve::ConstantProduct => {
+            let max = amounts.iter().copied().max().unwrap_or(0);
+            if amounts.contains(&0) {
+                0
+            } else {
+                let n = amounts.len() as u64;
+                let mut factor = I64F64::ONE;
+                for &amount in amounts {
+                    factor = math::checked_mul(factor, math::pow(amount, max, 1, n, false)?)?;
+                }
+                math::mul_fixed(max, factor, false)?
+            }
+        }
+        Curve::StableSwap { amp } => u64::try_from(math::stable_invariant(amp, amounts)?)
+            .map_err(|_| error!(TutorialError::MathOverflow))?,
+        Curve::Weighted { .. } => return err!(TutorialError::InvalidCurve),
+    };
+
+    if liquidity < MINIMUM_LIQUIDITY {
+        return err!(TutorialError::DepositTooSmall);
+    }
+    Ok(liquidity - MINIMUM_LIQUIDITY)
+}
+
+// 篮子池子之后的存款按LP总量的比例铸造：
+// liquidity = min(amount_k * total_liquidity / reserve_k)，向下取整
+// 每种代币实际需要存入 reserve_k * liquidity / total_liquidity，向上取整，不超过amount_k
+// total_liquidity包含被锁定的MINIMUM_LIQUIDITY
+pub fn basket_deposit(
+    amounts: &[u64],
+    reserves: &[u64],
+    total_liquidity: u64,
+) -> Result<(u64, Vec<u64>)> {
+    let mut liquidity = u64::MAX;
+    for (&amount, &reserve) in amounts.iter().zip(reserves) {
+        liquidity = liquidity.min(math::mul_div(amount, total_liquidity, reserve)?);
+    }
+
+    let required = reserves
+        .iter()
+        .map(|&reserve| math::mul_div_ceil(reserve, liquidity, total_liquidity))
+        .collect::<Result<Vec<u64>>>()?;
+    Ok((liquidity, required))
+}
+
+// 篮子池子销毁liquidity个LP能取回的每种代币，和withdraw_amounts一样按LP占比分配，向下取整
+pub fn basket_withdraw_amounts(
+    liquidity: u64,
+    reserves: &[u64],
+    total_liquidity: u64,
+) -> Result<Vec<u64>> {
+    reserves
+        .iter()
+        .map(|&reserve| math::mul_div(liquidity, reserve, total_liquidity))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CURVES: [Curve; 3] = [
+        Curve::ConstantProduct,
+        Curve::StableSwap { amp: 100 },
+        Curve::Weighted { weight_x: 8000, weight_y: 2000 },
+    ];
+
+    #[test]
+    fn swap_exact_input_rounds_output_down() {
+        let quote = swap_exact_input(Curve::ConstantProduct, 10_000, 1_000_000, 1_000_000, 30, 2000).unwrap();
+        assert_eq!(quote.fee_amount, 30);
+        assert_eq!(quote.protocol_fee_amount, 6);
+        // 9970 * 1_000_000 / 1_009_970 = 9871.58...
+        assert_eq!(quote.output, 9871);
+
+        // 手续费向下取整，不足1个单位时不收
+        let quote = swap_exact_input(Curve::ConstantProduct, 300, 1_000_000, 1_000_000, 30, 2000).unwrap();
+        assert_eq!(quote.fee_amount, 0);
+        assert_eq!(quote.protocol_fee_amount, 0);
+    }
+
+    #[test]
+    fn swap_exact_input_never_decreases_the_invariant() {
+        for curve in CURVES {
+            for input in [1, 999, 1_000_000, 123_456_789] {
+                let (reserve_in, reserve_out) = (1_000_000_000, 3_000_000_000);
+                let quote = swap_exact_input(curve, input, reserve_in, reserve_out, 30, 0).unwrap();
+                // 不收手续费的部分也不能让不变量变小
+                let taxed_input = input - quote.fee_amount;
+                let before = invariant(curve, reserve_in, reserve_out).unwrap();
+                let after = invariant(curve, reserve_in + taxed_input, reserve_out - quote.output).unwrap();
+                assert!(after >= before, "{curve:?} input {input}");
+            }
+        }
+    }
+
+    #[test]
+    fn swap_exact_output_rounds_input_up() {
+        // 1000 * 1_000_000 / 999_000 = 1001.001...，向上取整到1002，再除以(1 - 0.3%)向上取整
+        let quote = swap_exact_output(Curve::ConstantProduct, 1000, 1_000_000, 1_000_000, 30, 0).unwrap();
+        assert_eq!(quote.input, 1006);
+        assert_eq!(quote.fee_amount, 3);
+
+        // 付出quote.input一定能换到要求的数量
+        for curve in CURVES {
+            for output in [1, 999, 1_000_000, 500_000_000] {
+                let (reserve_in, reserve_out) = (2_000_000_000, 1_000_000_000);
+                let quote = swap_exact_output(curve, output, reserve_in, reserve_out, 30, 0).unwrap();
+                let check = swap_exact_input(curve, quote.input, reserve_in, reserve_out, 30, 0).unwrap();
+                assert!(check.output >= output, "{curve:?} output {output}");
+            }
+        }
+    }
+
+    #[test]
+    fn swap_exact_output_cannot_drain_the_pool() {
+        assert_eq!(
+            swap_exact_output(Curve::ConstantProduct, 1000, 1000, 1000, 30, 0),
+            Err(TutorialError::InsufficientLiquidity.into())
+        );
+    }
+
+    #[test]
+    fn first_deposit_locks_minimum_liquidity() {
+        assert_eq!(deposit_liquidity(Curve::ConstantProduct, 1000, 4000, 0, 0, 0).unwrap(), 1900);
+        assert_eq!(
+            deposit_liquidity(Curve::StableSwap { amp: 100 }, 1000, 1000, 0, 0, 0).unwrap(),
+            1900
+        );
+        assert_eq!(
+            deposit_liquidity(Curve::ConstantProduct, 10, 10, 0, 0, 0),
+            Err(TutorialError::DepositTooSmall.into())
+        );
+    }
+
+    #[test]
+    fn later_deposits_mint_at_most_the_reserve_share() {
+        // 按比例存入10%，铸造10%
+        assert_eq!(deposit_liquidity(Curve::ConstantProduct, 100, 400, 1000, 4000, 2000).unwrap(), 200);
+        // 多出来的代币B不会多铸造LP
+        assert_eq!(deposit_liquidity(Curve::ConstantProduct, 100, 1000, 1000, 4000, 2000).unwrap(), 200);
+
+        for curve in CURVES {
+            let liquidity = deposit_liquidity(curve, 333, 777, 1_000_000, 2_000_000, 1_000_000).unwrap();
+            assert!(liquidity <= 333 * 1_000_000 / 1_000_000);
+            assert!(liquidity <= 777 * 1_000_000 / 2_000_000);
+        }
+    }
+
+    #[test]
+    fn single_sided_swap_leaves_only_rounding_dust() {
+        let (amount_in, reserve_in, reserve_out) = (10_000_000, 1_000_000_000, 2_000_000_000);
+        for protocol_fee in [0, 2000, 10000] {
+            let swap_amount =
+                single_sided_swap_amount(Curve::ConstantProduct, amount_in, reserve_in, 30, protocol_fee).unwrap();
+            let quote =
+                swap_exact_input(Curve::ConstantProduct, swap_amount, reserve_in, reserve_out, 30, protocol_fee).unwrap();
+
+            // swap之后的储备不含协议手续费，剩下的输入和换到的代币按这个比例存入
+            let reserve_in = reserve_in + swap_amount - quote.protocol_fee_amount;
+            let reserve_out = reserve_out - quote.output;
+            let remaining = amount_in - swap_amount;
+            let (deposit_in, deposit_out) =
+                deposit_amounts(remaining, quote.output, reserve_in, reserve_out, remaining, quote.output).unwrap();
+            assert!(remaining - deposit_in <= 2, "protocol fee {protocol_fee}");
+            assert!(quote.output - deposit_out <= 2, "protocol fee {protocol_fee}");
+        }
+    }
+
+    #[test]
+    fn single_sided_swap_needs_a_constant_product_pool() {
+        assert_eq!(
+            single_sided_swap_amount(Curve::StableSwap { amp: 100 }, 1000, 1000, 30, 0),
+            Err(TutorialError::InvalidCurve.into())
+        );
+    }
+
+    #[test]
+    fn withdraw_amounts_round_down() {
+        assert_eq!(withdraw_amounts(1, 10, 3, 3).unwrap(), (3, 1));
+        assert_eq!(withdraw_amounts(3, 10, 3, 3).unwrap(), (10, 3));
+
+        // 分几次取完不会比一次取完拿到更多
+        let (reserve_a, reserve_b, total) = (1_000_003, 2_999_999, 1_000_000);
+        let (mut taken_a, mut taken_b) = (0, 0);
+        for _ in 0..7 {
+            let (amount_a, amount_b) = withdraw_amounts(total / 7, reserve_a, reserve_b, total).unwrap();
+            taken_a += amount_a;
+            taken_b += amount_b;
+        }
+        let (once_a, once_b) = withdraw_amounts(total / 7 * 7, reserve_a, reserve_b, total).unwrap();
+        assert!(taken_a <= once_a && taken_b <= once_b);
+    }
+}
@@ -16,4 +16,112 @@ pub enum TutorialError {
 
     #[msg("Invariant does not hold")]
     InvariantViolated,  // 变量被违反（AMM的核心数学规则被破坏）
-}
\ No newline at end of file
+
+    #[msg("Invalid fee delay")]
+    InvalidFeeDelay,  // 手续费生效延迟无效（不能为负数，也不能超过MAX_FEE_DELAY）
+
+    #[msg("Signer is not the AMM admin")]
+    Unauthorized,  // 签名者不是AMM的admin
+
+    #[msg("Signer is not the pending admin")]
+    InvalidPendingAdmin,  // 签名者不是被提名的新admin（或者没有待接受的admin转移）
+
+    #[msg("Invalid protocol fee value")]
+    InvalidProtocolFee,  // 协议手续费分成无效（超过100%）
+
+    #[msg("The pool is paused")]
+    PoolPaused,  // AMM或者池子被暂停，不能swap和deposit（withdraw不受影响）
+
+    #[msg("Input is above the maximum expected")]
+    InputTooLarge,  // 交换需要的输入超过最大可接受值（滑点保护）
+
+    #[msg("Not enough liquidity in the pool")]
+    InsufficientLiquidity,  // 池子的储备不足以给出要求的输出
+
+    #[msg("Invalid swap route")]
+    InvalidRoute,  // 路由里的池子账户无效（数量不对、不属于同一个AMM、vault不匹配、池子重复等）
+
+    #[msg("Transaction deadline exceeded")]
+    DeadlineExceeded,  // 交易超过了deadline才被执行
+
+    #[msg("Minted liquidity is below the minimum expected")]
+    LiquidityTooSmall,  // 铸造的LP代币少于最小期望值（滑点保护）
+
+    #[msg("Deposit is above the maximum expected")]
+    DepositTooLarge,  // 按池子比例调整后的存入量超过最大可接受值（滑点保护）
+
+    #[msg("Withdrawn amount is below the minimum expected")]
+    WithdrawOutputTooSmall,  // 提取的代币A或B少于最小期望值（滑点保护）
+
+    #[msg("Pool mints must be different")]
+    IdenticalMints,  // 池子的两个mint相同
+
+    #[msg("Pool mints must be ordered with mint_a < mint_b")]
+    InvalidMintOrder,  // 池子的mint顺序不是规范顺序（同一对代币只能有一个池子）
+
+    #[msg("Math operation overflowed")]
+    MathOverflow,  // 数学运算溢出（储备或数量过大）
+
+    #[msg("Division by zero")]
+    DivisionByZero,  // 除数为0（例如池子储备为空）
+
+    #[msg("Invalid amplification coefficient")]
+    InvalidAmp,  // StableSwap的放大系数超出[MIN_AMP, MAX_AMP]
+
+    #[msg("Operation not supported by the pool curve")]
+    InvalidCurve,  // 池子的曲线类型不支持这个操作（例如给恒定乘积池子调整amp）
+
+    #[msg("Invalid amplification ramp")]
+    InvalidAmpRamp,  // amp调整太快、幅度太大，或者上一次调整还没结束
+
+    #[msg("Invalid token weight")]
+    InvalidWeight,  // 加权池子的权重超出[MIN_WEIGHT, TOTAL_WEIGHT - MIN_WEIGHT]
+
+    #[msg("Invalid number of basket tokens")]
+    InvalidTokenCount,  // 篮子池子的代币数量超出[MIN_BASKET_TOKENS, MAX_BASKET_TOKENS]
+
+    #[msg("Invalid basket token index")]
+    InvalidTokenIndex,  // 篮子池子交换的输入/输出代币下标越界或者相同
+
+    #[msg("Invalid basket pool accounts")]
+    InvalidBasketAccounts,  // 篮子池子的remaining_accounts数量不对，或者mint、vault、代币账户对不上
+
+    #[msg("Invalid tick spacing")]
+    InvalidTickSpacing,  // 集中流动性池子的tick间距超出[1, MAX_TICK_SPACING]
+
+    #[msg("Invalid tick range")]
+    InvalidTickRange,  // tick超出[MIN_TICK, MAX_TICK]、不是tick间距的倍数，或者仓位的下界不小于上界
+
+    #[msg("Invalid sqrt price")]
+    InvalidSqrtPrice,  // sqrt_price超出[MIN_SQRT_PRICE, MAX_SQRT_PRICE]
+
+    #[msg("Invalid tick array")]
+    InvalidTickArray,  // tick array不属于这个池子、起始tick不对，或者交换时没有传入需要的tick array
+
+    #[msg("Position still holds liquidity or fees")]
+    PositionNotEmpty,  // 关闭仓位之前要先取出所有流动性和手续费
+
+    #[msg("Invalid oracle capacity")]
+    InvalidOracleCapacity,  // 观测点数量必须在1到MAX_ORACLE_OBSERVATIONS之间
+
+    #[msg("Invalid TWAP window")]
+    InvalidTwapWindow,  // TWAP的时间窗口必须大于0
+
+    #[msg("Oracle has no observation old enough for the window")]
+    OracleWindowTooLong,  // 最早的观测点比窗口的起点还晚，需要更大的容量或更短的窗口
+
+    #[msg("The pool has an oracle that must be passed")]
+    OracleRequired,  // 池子创建oracle之后，改变价格的指令都要传入oracle，不能跳过观测点
+
+    #[msg("A flash loan of the pool is in progress")]
+    FlashLoanActive,  // flash_borrow和flash_repay之间不能在同一个池子里交换、存取款或再借
+
+    #[msg("No flash loan of the pool to repay")]
+    FlashLoanNotActive,  // flash_repay之前没有flash_borrow
+
+    #[msg("Flash borrow without a matching flash repay")]
+    MissingFlashRepay,  // 同一笔交易里flash_borrow之后必须有同一个池子的flash_repay
+
+    #[msg("Invalid flash loan amount")]
+    InvalidFlashLoan,  // 两种代币都没有借，或者借的数量超过了LP的储备
+}
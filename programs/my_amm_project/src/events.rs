@@ -0,0 +1,322 @@
+use anchor_lang::prelude::*;
+
+use crate::state::CurveType;
+
+#[event]
+pub struct AmmCreated {
+    /// The AMM account
+    pub amm: Pubkey,
+    /// Account that has admin authority over the AMM
+    pub admin: Pubkey,
+    /// The LP fee taken on each trade, in basis points
+    pub fee: u16,
+    /// Delay in seconds between proposing a fee and it taking effect
+    pub fee_delay: i64,
+}
+
+#[event]
+pub struct FeeChanged {
+    pub amm: Pubkey,
+    /// Fee in effect after this change, in basis points
+    pub fee: u16,
+    /// Newly proposed fee, in basis points
+    pub pending_fee: u16,
+    /// Unix timestamp at which `pending_fee` takes effect
+    pub pending_fee_activation: i64,
+}
+
+#[event]
+pub struct AdminTransferProposed {
+    pub amm: Pubkey,
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+}
+
+#[event]
+pub struct AdminTransferCancelled {
+    pub amm: Pubkey,
+    pub admin: Pubkey,
+}
+
+#[event]
+pub struct AdminChanged {
+    pub amm: Pubkey,
+    pub old_admin: Pubkey,
+    pub new_admin: Pubkey,
+}
+
+#[event]
+pub struct ProtocolFeeChanged {
+    pub amm: Pubkey,
+    /// Share of the trading fee kept by the protocol, in basis points of the fee
+    pub protocol_fee: u16,
+    pub treasury: Pubkey,
+}
+
+#[event]
+pub struct ProtocolFeesCollected {
+    pub pool: Pubkey,
+    pub treasury: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+}
+
+#[event]
+pub struct AmmPauseChanged {
+    pub amm: Pubkey,
+    pub paused: bool,
+}
+
+#[event]
+pub struct PoolPauseChanged {
+    pub pool: Pubkey,
+    pub paused: bool,
+}
+
+#[event]
+pub struct PoolCreated {
+    pub amm: Pubkey,
+    pub pool: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    /// The LP token mint of the pool
+    pub mint_liquidity: Pubkey,
+    pub curve_type: CurveType,
+    /// Amplification coefficient of a StableSwap pool, 0 for other curves
+    pub amp: u64,
+    /// Weight of token A in a weighted pool, in basis points, 0 for other curves
+    pub weight_a: u16,
+    /// Weight of token B in a weighted pool, in basis points, 0 for other curves
+    pub weight_b: u16,
+}
+
+#[event]
+pub struct LiquidityAdded {
+    pub pool: Pubkey,
+    pub depositor: Pubkey,
+    /// Amount of token A sent by the depositor
+    pub amount_a: u64,
+    /// Amount of token B sent by the depositor
+    pub amount_b: u64,
+    /// LP tokens minted to the depositor
+    pub liquidity: u64,
+    /// LP reserve of token A after the deposit
+    pub reserve_a: u64,
+    /// LP reserve of token B after the deposit
+    pub reserve_b: u64,
+}
+
+#[event]
+pub struct LiquidityRemoved {
+    pub pool: Pubkey,
+    pub depositor: Pubkey,
+    /// Amount of token A sent by the pool
+    pub amount_a: u64,
+    /// Amount of token B sent by the pool
+    pub amount_b: u64,
+    /// LP tokens burned
+    pub liquidity: u64,
+    /// LP reserve of token A after the withdrawal
+    pub reserve_a: u64,
+    /// LP reserve of token B after the withdrawal
+    pub reserve_b: u64,
+}
+
+#[event]
+pub struct Swapped {
+    pub pool: Pubkey,
+    pub trader: Pubkey,
+    /// True when token A was sold for token B
+    pub swap_a: bool,
+    /// Amount of the input token sent by the trader (or the previous pool of a route)
+    pub input_amount: u64,
+    /// Amount of the output token sent by the pool
+    pub output_amount: u64,
+    /// Trading fee taken from the input, LP and protocol parts together
+    pub fee_amount: u64,
+    /// Part of `fee_amount` kept by the protocol
+    pub protocol_fee_amount: u64,
+    /// LP reserve of token A after the trade
+    pub reserve_a: u64,
+    /// LP reserve of token B after the trade
+    pub reserve_b: u64,
+}
+
+#[event]
+pub struct AmpRampStarted {
+    pub pool: Pubkey,
+    /// Amplification coefficient at the start of the ramp
+    pub amp: u64,
+    /// Amplification coefficient reached at the end of the ramp
+    pub target_amp: u64,
+    pub ramp_start: i64,
+    pub ramp_end: i64,
+}
+
+#[event]
+pub struct AmpRampStopped {
+    pub pool: Pubkey,
+    /// Amplification coefficient the pool is frozen at
+    pub amp: u64,
+}
+
+#[event]
+pub struct BasketPoolCreated {
+    pub amm: Pubkey,
+    pub pool: Pubkey,
+    pub mints: Vec<Pubkey>,
+    /// The LP token mint of the pool
+    pub mint_liquidity: Pubkey,
+    pub curve_type: CurveType,
+    /// Amplification coefficient of a StableSwap pool, 0 for constant product
+    pub amp: u64,
+}
+
+#[event]
+pub struct BasketLiquidityAdded {
+    pub pool: Pubkey,
+    pub depositor: Pubkey,
+    /// Amount of each token sent by the depositor, in mint order
+    pub amounts: Vec<u64>,
+    /// LP tokens minted to the depositor
+    pub liquidity: u64,
+}
+
+#[event]
+pub struct BasketLiquidityRemoved {
+    pub pool: Pubkey,
+    pub depositor: Pubkey,
+    /// Amount of each token sent by the pool, in mint order
+    pub amounts: Vec<u64>,
+    /// LP tokens burned
+    pub liquidity: u64,
+}
+
+#[event]
+pub struct BasketProtocolFeesCollected {
+    pub pool: Pubkey,
+    pub treasury: Pubkey,
+    /// Protocol fees of each token sent to the treasury, in mint order
+    pub amounts: Vec<u64>,
+}
+
+#[event]
+pub struct BasketSwapped {
+    pub pool: Pubkey,
+    pub trader: Pubkey,
+    /// Index of the input token in the pool mints
+    pub input_index: u8,
+    /// Index of the output token in the pool mints
+    pub output_index: u8,
+    /// Amount of the input token sent by the trader
+    pub input_amount: u64,
+    /// Amount of the output token sent by the pool
+    pub output_amount: u64,
+    /// Trading fee taken from the input
+    pub fee_amount: u64,
+    /// Part of `fee_amount` kept by the protocol
+    pub protocol_fee_amount: u64,
+}
+
+#[event]
+pub struct ClmmPoolCreated {
+    pub amm: Pubkey,
+    pub pool: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub tick_spacing: u16,
+    /// Initial square root of the price, as a Q64.64 fixed-point number
+    pub sqrt_price: u128,
+    /// Tick of the initial price
+    pub tick: i32,
+}
+
+#[event]
+pub struct PositionOpened {
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+}
+
+#[event]
+pub struct PositionLiquidityAdded {
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    /// Liquidity added to the position
+    pub liquidity: u128,
+    /// Amount of token A sent by the owner
+    pub amount_a: u64,
+    /// Amount of token B sent by the owner
+    pub amount_b: u64,
+}
+
+#[event]
+pub struct PositionLiquidityRemoved {
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    /// Liquidity removed from the position
+    pub liquidity: u128,
+    /// Amount of token A sent by the pool
+    pub amount_a: u64,
+    /// Amount of token B sent by the pool
+    pub amount_b: u64,
+}
+
+#[event]
+pub struct PositionFeesCollected {
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+}
+
+#[event]
+pub struct ClmmSwapped {
+    pub pool: Pubkey,
+    pub trader: Pubkey,
+    /// True when the trader sold token A for token B
+    pub swap_a: bool,
+    /// Amount of the input token sent by the trader
+    pub input_amount: u64,
+    /// Amount of the output token sent by the pool
+    pub output_amount: u64,
+    /// Trading fee taken from the input
+    pub fee_amount: u64,
+    /// Part of `fee_amount` kept by the protocol
+    pub protocol_fee_amount: u64,
+    /// Square root of the price after the swap, as a Q64.64 fixed-point number
+    pub sqrt_price: u128,
+    /// Tick of the price after the swap
+    pub tick: i32,
+    /// Liquidity in range after the swap
+    pub liquidity: u128,
+}
+
+#[event]
+pub struct FlashLoanBorrowed {
+    pub pool: Pubkey,
+    pub borrower: Pubkey,
+    /// Token A lent by the pool
+    pub amount_a: u64,
+    /// Token B lent by the pool
+    pub amount_b: u64,
+    /// Fee in token A owed on top of `amount_a`, earned by the LPs
+    pub fee_a: u64,
+    /// Fee in token B owed on top of `amount_b`, earned by the LPs
+    pub fee_b: u64,
+}
+
+#[event]
+pub struct FlashLoanRepaid {
+    pub pool: Pubkey,
+    pub borrower: Pubkey,
+    /// Token A received back by the pool, loan plus fee
+    pub amount_a: u64,
+    /// Token B received back by the pool, loan plus fee
+    pub amount_b: u64,
+}
@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::*, events::AdminChanged, state::Amm};
+
+pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+    let amm = &mut ctx.accounts.amm;
+    let old_admin = amm.admin;
+    amm.admin = ctx.accounts.pending_admin.key();
+    amm.pending_admin = Pubkey::default();
+
+    emit!(AdminChanged {
+        amm: amm.key(),
+        old_admin,
+        new_admin: amm.admin,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [
+            amm.id.as_ref()
+        ],
+        bump,
+        // pending_admin为默认值时表示没有待接受的转移，Pubkey::default()无法签名，所以这里不会误通过
+        has_one = pending_admin @ TutorialError::InvalidPendingAdmin,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    pub pending_admin: Signer<'info>,
+}
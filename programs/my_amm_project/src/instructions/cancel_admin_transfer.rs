@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::*, events::AdminTransferCancelled, state::Amm};
+
+pub fn cancel_admin_transfer(ctx: Context<CancelAdminTransfer>) -> Result<()> {
+    ctx.accounts.amm.pending_admin = Pubkey::default();
+
+    emit!(AdminTransferCancelled {
+        amm: ctx.accounts.amm.key(),
+        admin: ctx.accounts.admin.key(),
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelAdminTransfer<'info> {
+    #[account(
+        mut,
+        seeds = [
+            amm.id.as_ref()
+        ],
+        bump,
+        has_one = admin @ TutorialError::Unauthorized,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    pub admin: Signer<'info>,
+}
@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, TransferChecked};
+
+use crate::{
+    constants::AUTHORITY_SEED,
+    errors::*,
+    events::BasketProtocolFeesCollected,
+    state::{Amm, BasketPool},
+    utils::load_basket_tokens,
+};
+
+// 把篮子池子里累积的协议手续费转给treasury
+// 每种代币的[mint, vault, treasury_account, token_program]通过remaining_accounts传入，
+// treasury_account是treasury已有的代币账户
+pub fn collect_basket_protocol_fees<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CollectBasketProtocolFees<'info>>,
+) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+    let tokens = load_basket_tokens(
+        pool,
+        &ctx.accounts.pool_authority.key(),
+        &ctx.accounts.treasury.key(),
+        ctx.remaining_accounts,
+    )?;
+    let amounts = pool.protocol_fees.clone();
+
+    let authority_bump = ctx.bumps.pool_authority;
+    let authority_seeds = &[
+        &pool.amm.to_bytes(),
+        &pool.id.to_bytes(),
+        AUTHORITY_SEED,
+        &[authority_bump],
+    ];
+    let signer_seeds = &[&authority_seeds[..]];
+
+    // 将每种代币的协议手续费转给treasury
+    for (token, &amount) in tokens.iter().zip(&amounts) {
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                token.token_program.clone(),
+                TransferChecked {
+                    from: token.vault.clone(),
+                    mint: token.mint.clone(),
+                    to: token.user_account.clone(),
+                    authority: ctx.accounts.pool_authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+            token.decimals,
+        )?;
+    }
+
+    let pool = &mut ctx.accounts.pool;
+    pool.protocol_fees = vec![0; amounts.len()];
+
+    emit!(BasketProtocolFeesCollected {
+        pool: pool.key(),
+        treasury: ctx.accounts.treasury.key(),
+        amounts,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CollectBasketProtocolFees<'info> {
+    #[account(
+        seeds = [
+            amm.id.as_ref()
+        ],
+        bump,
+        has_one = admin @ TutorialError::Unauthorized,
+        has_one = treasury,
+    )]
+    pub amm: Box<Account<'info, Amm>>,
+
+    #[account(
+        mut,
+        seeds = [
+            pool.amm.as_ref(),
+            pool.id.as_ref(),
+        ],
+        bump,
+        has_one = amm,
+    )]
+    pub pool: Box<Account<'info, BasketPool>>,
+
+    /// CHECK: Read only authority
+    #[account(
+        seeds = [
+            pool.amm.as_ref(),
+            pool.id.as_ref(),
+            AUTHORITY_SEED,
+        ],
+        bump,
+    )]
+    pub pool_authority: AccountInfo<'info>,
+
+    /// CHECK: Read only, checked against amm.treasury
+    pub treasury: AccountInfo<'info>,
+
+    pub admin: Signer<'info>,
+}
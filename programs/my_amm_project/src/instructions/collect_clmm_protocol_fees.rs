@@ -0,0 +1,165 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{
+    constants::AUTHORITY_SEED,
+    errors::*,
+    events::ProtocolFeesCollected,
+    state::{Amm, ClmmPool},
+};
+
+// 把集中流动性池子里累积的协议手续费转给treasury，和collect_protocol_fees一样
+pub fn collect_clmm_protocol_fees(ctx: Context<CollectClmmProtocolFees>) -> Result<()> {
+    let amount_a = ctx.accounts.pool.protocol_fees_a;
+    let amount_b = ctx.accounts.pool.protocol_fees_b;
+
+    let authority_bump = ctx.bumps.pool_authority;
+    let authority_seeds = &[&ctx.accounts.pool.key().to_bytes(), AUTHORITY_SEED, &[authority_bump]];
+    let signer_seeds = &[&authority_seeds[..]];
+
+    // 转移Token A 给treasury
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program_a.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.pool_account_a.to_account_info(),
+                mint: ctx.accounts.mint_a.to_account_info(),
+                to: ctx.accounts.treasury_account_a.to_account_info(),
+                authority: ctx.accounts.pool_authority.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount_a,
+        ctx.accounts.mint_a.decimals,
+    )?;
+
+    // 转移Token B 给treasury
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program_b.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.pool_account_b.to_account_info(),
+                mint: ctx.accounts.mint_b.to_account_info(),
+                to: ctx.accounts.treasury_account_b.to_account_info(),
+                authority: ctx.accounts.pool_authority.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount_b,
+        ctx.accounts.mint_b.decimals,
+    )?;
+
+    let pool = &mut ctx.accounts.pool;
+    pool.protocol_fees_a = 0;
+    pool.protocol_fees_b = 0;
+
+    emit!(ProtocolFeesCollected {
+        pool: pool.key(),
+        treasury: ctx.accounts.treasury.key(),
+        amount_a,
+        amount_b,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CollectClmmProtocolFees<'info> {
+    #[account(
+        seeds = [
+            amm.id.as_ref()
+        ],
+        bump,
+        has_one = admin @ TutorialError::Unauthorized,
+        has_one = treasury,
+    )]
+    pub amm: Box<Account<'info, Amm>>,
+
+    #[account(
+        mut,
+        seeds = [
+            pool.amm.as_ref(),
+            pool.mint_a.as_ref(),
+            pool.mint_b.as_ref(),
+            pool.tick_spacing.to_le_bytes().as_ref(),
+        ],
+        bump,
+        has_one = amm,
+        has_one = mint_a,
+        has_one = mint_b,
+    )]
+    pub pool: Box<Account<'info, ClmmPool>>,
+
+    /// CHECK: Read only authority
+    #[account(
+        seeds = [
+            pool.key().as_ref(),
+            AUTHORITY_SEED,
+        ],
+        bump,
+    )]
+    pub pool_authority: AccountInfo<'info>,
+
+    #[account(
+        mint::token_program = token_program_a,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = pool_authority,
+        associated_token::token_program = token_program_a,
+    )]
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = pool_authority,
+        associated_token::token_program = token_program_b,
+    )]
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Read only, checked against amm.treasury
+    pub treasury: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_a,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program_a,
+    )]
+    pub treasury_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_b,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program_b,
+    )]
+    pub treasury_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub admin: Signer<'info>,
+
+    /// The account paying for all rents
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Solana ecosystem accounts
+    // 池子两边的代币可以各自属于Token或Token-2022程序
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
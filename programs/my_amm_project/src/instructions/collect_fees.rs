@@ -0,0 +1,186 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{
+    constants::AUTHORITY_SEED,
+    events::PositionFeesCollected,
+    errors::TutorialError,
+    state::{ClmmPool, Position, TickArray},
+    utils::update_position,
+};
+
+// 取出集中流动性仓位赚到的手续费
+// 先按区间内的手续费增长结算到现在，所以需要上下界所在的tick array
+pub fn collect_fees(ctx: Context<CollectFees>) -> Result<()> {
+    let accounts = ctx.accounts;
+    let same_tick_array = accounts.tick_array_lower.key() == accounts.tick_array_upper.key();
+    update_position(
+        &mut accounts.pool,
+        &mut accounts.position,
+        &mut accounts.tick_array_lower,
+        &mut accounts.tick_array_upper,
+        same_tick_array,
+        0,
+    )?;
+
+    let position = &mut accounts.position;
+    let (amount_a, amount_b) = (position.fees_owed_a, position.fees_owed_b);
+    position.fees_owed_a = 0;
+    position.fees_owed_b = 0;
+
+    // 生成PDA签名
+    let pool_key = accounts.pool.key();
+    let authority_bump = ctx.bumps.pool_authority;
+    let authority_seeds = &[&pool_key.to_bytes(), AUTHORITY_SEED, &[authority_bump]];
+    let signer_seeds = &[&authority_seeds[..]];
+
+    if amount_a > 0 {
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                accounts.token_program_a.to_account_info(),
+                TransferChecked {
+                    from: accounts.pool_account_a.to_account_info(),
+                    mint: accounts.mint_a.to_account_info(),
+                    to: accounts.owner_account_a.to_account_info(),
+                    authority: accounts.pool_authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount_a,
+            accounts.mint_a.decimals,
+        )?;
+    }
+    if amount_b > 0 {
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                accounts.token_program_b.to_account_info(),
+                TransferChecked {
+                    from: accounts.pool_account_b.to_account_info(),
+                    mint: accounts.mint_b.to_account_info(),
+                    to: accounts.owner_account_b.to_account_info(),
+                    authority: accounts.pool_authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount_b,
+            accounts.mint_b.decimals,
+        )?;
+    }
+
+    emit!(PositionFeesCollected {
+        pool: pool_key,
+        position: accounts.position.key(),
+        owner: accounts.owner.key(),
+        amount_a,
+        amount_b,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CollectFees<'info> {
+    #[account(
+        mut,
+        seeds = [
+            pool.amm.as_ref(),
+            pool.mint_a.as_ref(),
+            pool.mint_b.as_ref(),
+            pool.tick_spacing.to_le_bytes().as_ref(),
+        ],
+        bump,
+        has_one = mint_a,
+        has_one = mint_b,
+    )]
+    pub pool: Box<Account<'info, ClmmPool>>,
+
+    /// CHECK: Read only authority
+    #[account(
+        seeds = [
+            pool.key().as_ref(),
+            AUTHORITY_SEED,
+        ],
+        bump,
+    )]
+    pub pool_authority: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        has_one = pool,
+        has_one = owner,
+    )]
+    pub position: Box<Account<'info, Position>>,
+
+    /// Tick array holding the lower tick of the position
+    #[account(
+        mut,
+        has_one = pool @ TutorialError::InvalidTickArray,
+    )]
+    pub tick_array_lower: Box<Account<'info, TickArray>>,
+
+    /// Tick array holding the upper tick of the position, can be the same account as `tick_array_lower`
+    #[account(
+        mut,
+        has_one = pool @ TutorialError::InvalidTickArray,
+    )]
+    pub tick_array_upper: Box<Account<'info, TickArray>>,
+
+    #[account(
+        mint::token_program = token_program_a,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = pool_authority,
+        associated_token::token_program = token_program_a,
+    )]
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = pool_authority,
+        associated_token::token_program = token_program_b,
+    )]
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_a,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_a,
+    )]
+    pub owner_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_b,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_b,
+    )]
+    pub owner_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub owner: Signer<'info>,
+
+    /// The account paying for all rents
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    // 池子两边的代币可以各自属于Token或Token-2022程序
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,184 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{
+    constants::AUTHORITY_SEED,
+    errors::*,
+    events::ProtocolFeesCollected,
+    state::{Amm, Pool},
+};
+
+// 把池子里累积的协议手续费转给treasury
+pub fn collect_protocol_fees(ctx: Context<CollectProtocolFees>) -> Result<()> {
+    let amount_a = ctx.accounts.pool.protocol_fees_a;
+    let amount_b = ctx.accounts.pool.protocol_fees_b;
+
+    let authority_bump = ctx.bumps.pool_authority;
+    let authority_seeds = &[
+        &ctx.accounts.pool.amm.to_bytes(),
+        &ctx.accounts.mint_a.key().to_bytes(),
+        &ctx.accounts.mint_b.key().to_bytes(),
+        AUTHORITY_SEED,
+        &[authority_bump],
+    ];
+    let signer_seeds = &[&authority_seeds[..]];
+
+    // 转移Token A 给treasury
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program_a.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.pool_account_a.to_account_info(),
+                mint: ctx.accounts.mint_a.to_account_info(),
+                to: ctx.accounts.treasury_account_a.to_account_info(),
+                authority: ctx.accounts.pool_authority.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount_a,
+        ctx.accounts.mint_a.decimals,
+    )?;
+
+    // 转移Token B 给treasury
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program_b.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.pool_account_b.to_account_info(),
+                mint: ctx.accounts.mint_b.to_account_info(),
+                to: ctx.accounts.treasury_account_b.to_account_info(),
+                authority: ctx.accounts.pool_authority.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount_b,
+        ctx.accounts.mint_b.decimals,
+    )?;
+
+    let pool = &mut ctx.accounts.pool;
+    pool.protocol_fees_a = 0;
+    pool.protocol_fees_b = 0;
+
+    // 协议手续费本来就不计入储备，这里按vault的余额重新保存一次储备
+    // 先按之前保存的储备累计价格
+    ctx.accounts.pool_account_a.reload()?;
+    ctx.accounts.pool_account_b.reload()?;
+    let (reserve_a, reserve_b) = ctx
+        .accounts
+        .pool
+        .reserves(ctx.accounts.pool_account_a.amount, ctx.accounts.pool_account_b.amount)?;
+    let pool = &mut ctx.accounts.pool;
+    pool.update_price_cumulative(Clock::get()?.unix_timestamp);
+    pool.sync_reserves(reserve_a, reserve_b);
+
+    emit!(ProtocolFeesCollected {
+        pool: pool.key(),
+        treasury: ctx.accounts.treasury.key(),
+        amount_a,
+        amount_b,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CollectProtocolFees<'info> {
+    #[account(
+        seeds = [
+            amm.id.as_ref()
+        ],
+        bump,
+        has_one = admin @ TutorialError::Unauthorized,
+        has_one = treasury,
+    )]
+    pub amm: Box<Account<'info, Amm>>,
+
+    #[account(
+        mut,
+        seeds = [
+            pool.amm.as_ref(),
+            pool.mint_a.key().as_ref(),
+            pool.mint_b.key().as_ref(),
+        ],
+        bump,
+        has_one = amm,
+        has_one = mint_a,
+        has_one = mint_b,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        seeds = [
+            pool.amm.as_ref(),
+            mint_a.key().as_ref(),
+            mint_b.key().as_ref(),
+            AUTHORITY_SEED,
+        ],
+        bump,
+    )]
+    /// CHECK: Read only authority
+    pub pool_authority: AccountInfo<'info>,
+
+    #[account(
+        mint::token_program = token_program_a,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = pool_authority,
+        associated_token::token_program = token_program_a,
+    )]
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = pool_authority,
+        associated_token::token_program = token_program_b,
+    )]
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Read only, checked against amm.treasury
+    pub treasury: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_a,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program_a,
+    )]
+    pub treasury_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_b,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program_b,
+    )]
+    pub treasury_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub admin: Signer<'info>,
+
+    /// The account paying for all rents
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Solana ecosystem accounts
+    // 池子两边的代币可以各自属于Token或Token-2022程序
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
@@ -1,22 +1,33 @@
 use anchor_lang::prelude::*;
 
-use crate::{errors::*, state::Amm};
+use crate::{constants::MAX_FEE_DELAY, errors::*, events::AmmCreated, state::Amm};
 
 pub fn create_amm(
     ctx: Context<CreateAmm>,
     id: Pubkey,
-    fee: u16
+    fee: u16,
+    fee_delay: i64,
 ) -> Result<()> {
     let amm = &mut ctx.accounts.amm;
     amm.admin = ctx.accounts.admin.key();
     amm.id = id;
     amm.fee = fee;
+    amm.fee_delay = fee_delay;
+    // 协议分成默认为0，treasury默认为admin，之后可以通过set_protocol_fee修改
+    amm.treasury = ctx.accounts.admin.key();
+
+    emit!(AmmCreated {
+        amm: amm.key(),
+        admin: amm.admin,
+        fee,
+        fee_delay,
+    });
 
     Ok(())
 }
 
 #[derive(Accounts)]
-#[instruction(id: Pubkey, fee: u16)]
+#[instruction(id: Pubkey, fee: u16, fee_delay: i64)]
 pub struct CreateAmm<'info>{
     #[account(
         init,
@@ -24,6 +35,7 @@ pub struct CreateAmm<'info>{
         space = Amm::LEN,
         seeds = [id.as_ref()],
         constraint = fee < 10000 @ TutorialError::InvalidFee,
+        constraint = (0..=MAX_FEE_DELAY).contains(&fee_delay) @ TutorialError::InvalidFeeDelay,
         bump,
     )]
     pub amm: Account<'info, Amm>,
@@ -0,0 +1,165 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{self, get_associated_token_address_with_program_id, AssociatedToken, Create},
+    token::Token,
+    token_interface::Mint,
+};
+
+use crate::{
+    constants::{AUTHORITY_SEED, LIQUIDITY_SEED, MAX_AMP, MAX_BASKET_TOKENS, MIN_AMP, MIN_BASKET_TOKENS},
+    errors::TutorialError,
+    events::BasketPoolCreated,
+    state::{Amm, BasketPool, CurveType},
+};
+
+// 创建池子时每种代币在remaining_accounts里占用的账户数量：[mint, vault, token_program]
+const BASKET_CREATE_ACCOUNTS: usize = 3;
+
+// 篮子池子：同一个池子里有三种及以上的代币（例如USDC/USDT/DAI），共用一个LP代币
+// 代币数量不固定，PDA用id代替mint，每种代币的mint、vault、token程序通过remaining_accounts传入
+// 只支持恒定乘积和StableSwap，amp在恒定乘积池子里忽略
+pub fn create_basket_pool<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CreateBasketPool<'info>>,
+    id: Pubkey,
+    token_count: u8,
+    curve_type: CurveType,
+    amp: u64,
+) -> Result<()> {
+    if !(MIN_BASKET_TOKENS..=MAX_BASKET_TOKENS).contains(&token_count) {
+        return err!(TutorialError::InvalidTokenCount);
+    }
+    let remaining_accounts = ctx.remaining_accounts;
+    if remaining_accounts.len() != token_count as usize * BASKET_CREATE_ACCOUNTS {
+        return err!(TutorialError::InvalidBasketAccounts);
+    }
+
+    let amp = match curve_type {
+        CurveType::ConstantProduct => 0,
+        CurveType::StableSwap => {
+            if !(MIN_AMP..=MAX_AMP).contains(&amp) {
+                return err!(TutorialError::InvalidAmp);
+            }
+            amp
+        }
+        CurveType::Weighted => return err!(TutorialError::InvalidCurve),
+    };
+
+    let pool_authority = ctx.accounts.pool_authority.key();
+    let mut mints: Vec<Pubkey> = Vec::with_capacity(token_count as usize);
+    for accounts in remaining_accounts.chunks(BASKET_CREATE_ACCOUNTS) {
+        let (mint, vault, token_program) = (&accounts[0], &accounts[1], &accounts[2]);
+
+        // 规范顺序：mint严格递增，和两种代币的池子一样，同时排除了重复的mint
+        if let Some(last) = mints.last() {
+            if mint.key() == *last {
+                return err!(TutorialError::IdenticalMints);
+            }
+            if mint.key() < *last {
+                return err!(TutorialError::InvalidMintOrder);
+            }
+        }
+
+        // token程序必须是mint的owner（Token或Token-2022）
+        InterfaceAccount::<Mint>::try_from(mint)?;
+        if token_program.key() != *mint.owner {
+            return err!(TutorialError::InvalidBasketAccounts);
+        }
+
+        // vault是pool_authority的ATA，在这里创建
+        if vault.key()
+            != get_associated_token_address_with_program_id(&pool_authority, &mint.key(), token_program.key)
+        {
+            return err!(TutorialError::InvalidBasketAccounts);
+        }
+        associated_token::create(CpiContext::new(
+            ctx.accounts.associated_token_program.to_account_info(),
+            Create {
+                payer: ctx.accounts.payer.to_account_info(),
+                associated_token: vault.clone(),
+                authority: ctx.accounts.pool_authority.to_account_info(),
+                mint: mint.clone(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_program: token_program.clone(),
+            },
+        ))?;
+
+        mints.push(mint.key());
+    }
+
+    let pool = &mut ctx.accounts.pool;
+    pool.amm = ctx.accounts.amm.key();
+    pool.id = id;
+    pool.curve_type = curve_type;
+    pool.amp = amp;
+    pool.target_amp = amp;
+    pool.protocol_fees = vec![0; mints.len()];
+    pool.mints = mints;
+
+    emit!(BasketPoolCreated {
+        amm: pool.amm,
+        pool: pool.key(),
+        mints: pool.mints.clone(),
+        mint_liquidity: ctx.accounts.mint_liquidity.key(),
+        curve_type,
+        amp,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(id: Pubkey, token_count: u8)]
+pub struct CreateBasketPool<'info> {
+    #[account(
+        seeds = [
+            amm.id.as_ref()
+        ],
+        bump,
+        constraint = !amm.paused @ TutorialError::PoolPaused,
+    )]
+    pub amm: Box<Account<'info, Amm>>,
+
+    #[account(
+        init,
+        space = BasketPool::space(token_count as usize),
+        payer = payer,
+        seeds = [
+            amm.key().as_ref(),
+            id.as_ref(),
+        ],
+        bump,
+    )]
+    pub pool: Box<Account<'info, BasketPool>>,
+
+    /// CHECK: Read only authority
+    #[account(
+        seeds = [
+            amm.key().as_ref(),
+            id.as_ref(),
+            AUTHORITY_SEED,
+        ],
+        bump,
+    )]
+    pub pool_authority: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [
+            amm.key().as_ref(),
+            id.as_ref(),
+            LIQUIDITY_SEED,
+        ],
+        bump,
+        mint::decimals = 6,
+        mint::authority = pool_authority,
+        mint::token_program = token_program, // LP代币统一用经典的Token程序
+    )]
+    pub mint_liquidity: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,126 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::{
+    constants::{AUTHORITY_SEED, MAX_TICK_SPACING},
+    errors::TutorialError,
+    events::ClmmPoolCreated,
+    state::{Amm, ClmmPool},
+    tick_math,
+};
+
+// 集中流动性池子：LP在自己选的价格区间[tick_lower, tick_upper)里提供流动性，
+// 价格在区间内时才参与交易、赚手续费，同样的资金在窄区间里能提供多得多的深度
+// 和现有的Pool分开：没有LP代币，每个仓位是一个Position账户（见open_position）
+// sqrt_price是初始价格（代币B / 代币A）的平方根，Q64.64
+pub fn create_clmm_pool(
+    ctx: Context<CreateClmmPool>,
+    tick_spacing: u16,
+    sqrt_price: u128,
+) -> Result<()> {
+    if !(1..=MAX_TICK_SPACING).contains(&tick_spacing) {
+        return err!(TutorialError::InvalidTickSpacing);
+    }
+    let tick = tick_math::tick_at_sqrt_price(sqrt_price)?;
+
+    let pool = &mut ctx.accounts.pool;
+    pool.amm = ctx.accounts.amm.key();
+    pool.mint_a = ctx.accounts.mint_a.key();
+    pool.mint_b = ctx.accounts.mint_b.key();
+    pool.tick_spacing = tick_spacing;
+    pool.sqrt_price = sqrt_price;
+    pool.tick = tick;
+    pool.last_price_update = Clock::get()?.unix_timestamp;
+
+    emit!(ClmmPoolCreated {
+        amm: pool.amm,
+        pool: pool.key(),
+        mint_a: pool.mint_a,
+        mint_b: pool.mint_b,
+        tick_spacing,
+        sqrt_price,
+        tick,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(tick_spacing: u16)]
+pub struct CreateClmmPool<'info> {
+    #[account(
+        seeds = [
+            amm.id.as_ref()
+        ],
+        bump,
+        constraint = !amm.paused @ TutorialError::PoolPaused,
+    )]
+    pub amm: Box<Account<'info, Amm>>,
+
+    // 同一对代币可以有多个tick间距不同的池子
+    #[account(
+        init,
+        space = ClmmPool::LEN,
+        payer = payer,
+        seeds = [
+            amm.key().as_ref(),
+            mint_a.key().as_ref(),
+            mint_b.key().as_ref(),
+            tick_spacing.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub pool: Box<Account<'info, ClmmPool>>,
+
+    /// CHECK: Read only authority
+    #[account(
+        seeds = [
+            pool.key().as_ref(),
+            AUTHORITY_SEED,
+        ],
+        bump,
+    )]
+    pub pool_authority: AccountInfo<'info>,
+
+    // 规范顺序：mint_a < mint_b，和Pool一样
+    #[account(
+        constraint = mint_a.key() != mint_b.key() @ TutorialError::IdenticalMints,
+        constraint = mint_a.key() < mint_b.key() @ TutorialError::InvalidMintOrder,
+        mint::token_program = token_program_a,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = mint_a,
+        associated_token::authority = pool_authority,
+        associated_token::token_program = token_program_a,
+    )]
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = mint_b,
+        associated_token::authority = pool_authority,
+        associated_token::token_program = token_program_b,
+    )]
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    // 池子两边的代币可以各自属于Token或Token-2022程序
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
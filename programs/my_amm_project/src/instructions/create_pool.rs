@@ -1,22 +1,80 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{Mint, Token, TokenAccount},
+    token::Token,
+    token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
 
 use crate::{
-    constants::{AUTHORITY_SEED, LIQUIDITY_SEED},
-    state::{Amm, Pool},
+    constants::{AUTHORITY_SEED, LIQUIDITY_SEED, MAX_AMP, MIN_AMP, MIN_WEIGHT, TOTAL_WEIGHT},
+    errors::TutorialError,
+    events::PoolCreated,
+    state::{Amm, CurveType, Pool},
 };
 
+// 默认的恒定乘积池子
 pub fn create_pool(
     ctx: Context<CreatePool>
+) -> Result<()> {
+    init_pool(ctx, CurveType::ConstantProduct, 0, (0, 0))
+}
+
+// StableSwap池子，适合USDC/USDT这类锚定同一价格的代币对
+// amp越大，价格在1:1附近越平坦；amp趋近0时退化成恒定乘积
+pub fn create_stable_pool(
+    ctx: Context<CreatePool>,
+    amp: u64,
+) -> Result<()> {
+    if !(MIN_AMP..=MAX_AMP).contains(&amp) {
+        return err!(TutorialError::InvalidAmp);
+    }
+
+    init_pool(ctx, CurveType::StableSwap, amp, (0, 0))
+}
+
+// 加权池子，例如weight_a = 8000表示80/20：池子的价值80%是代币A
+// 交换公式是加权乘积 reserve_a^weight_a * reserve_b^weight_b = k，存取款仍然按储备比例
+pub fn create_weighted_pool(
+    ctx: Context<CreatePool>,
+    weight_a: u16,
+) -> Result<()> {
+    if !(MIN_WEIGHT..=TOTAL_WEIGHT - MIN_WEIGHT).contains(&weight_a) {
+        return err!(TutorialError::InvalidWeight);
+    }
+
+    init_pool(ctx, CurveType::Weighted, 0, (weight_a, TOTAL_WEIGHT - weight_a))
+}
+
+fn init_pool(
+    ctx: Context<CreatePool>,
+    curve_type: CurveType,
+    amp: u64,
+    (weight_a, weight_b): (u16, u16),
 ) -> Result<()> {
     let pool = &mut ctx.accounts.pool;
     pool.amm = ctx.accounts.amm.key();
     pool.mint_a = ctx.accounts.mint_a.key();
     pool.mint_b = ctx.accounts.mint_b.key();
+    pool.curve_type = curve_type;
+    pool.amp = amp;
+    pool.target_amp = amp;
+    pool.weight_a = weight_a;
+    pool.weight_b = weight_b;
+    // 价格累加器从创建时开始计时
+    pool.last_price_update = Clock::get()?.unix_timestamp;
+
+    emit!(PoolCreated {
+        amm: pool.amm,
+        pool: pool.key(),
+        mint_a: pool.mint_a,
+        mint_b: pool.mint_b,
+        mint_liquidity: ctx.accounts.mint_liquidity.key(),
+        curve_type,
+        amp,
+        weight_a,
+        weight_b,
+    });
     Ok(())
 }
 
@@ -29,6 +87,7 @@ pub struct CreatePool<'info>{
             amm.id.as_ref()
         ],
         bump,
+        constraint = !amm.paused @ TutorialError::PoolPaused,
     )]
     // 不用Box：数据存储在栈上
     // pub amm: Account<'info, Amm>,
@@ -74,12 +133,23 @@ pub struct CreatePool<'info>{
         bump,
         mint::decimals = 6,
         mint::authority = pool_authority,
+        mint::token_program = token_program, // LP代币统一用经典的Token程序
+    )]
+    pub mint_liquidity: Box<InterfaceAccount<'info, Mint>>,
+
+    // 规范顺序：mint_a < mint_b（按pubkey字节比较）
+    // 否则同一对代币可以建出A/B和B/A两个池子，流动性被分散
+    #[account(
+        constraint = mint_a.key() != mint_b.key() @ TutorialError::IdenticalMints,
+        constraint = mint_a.key() < mint_b.key() @ TutorialError::InvalidMintOrder,
+        mint::token_program = token_program_a,
     )]
-    pub mint_liquidity: Box<Account<'info, Mint>>,
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
 
-    pub mint_a: Box<Account<'info, Mint>>,
-    
-    pub mint_b: Box<Account<'info, Mint>>,
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
 
     // - 你有一个钱包地址：wallet_address
     // - 你想持有USDC代币，但钱包本身不能直接存储代币
@@ -95,21 +165,26 @@ pub struct CreatePool<'info>{
         payer = payer,
         associated_token::mint = mint_a, // 这个账户存储mint_a代币
         associated_token::authority = pool_authority, // pool_authority拥有这个账户
+        associated_token::token_program = token_program_a,
     )]
-    pub pool_account_a: Box<Account<'info, TokenAccount>>,
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         init,
         payer = payer,
-        associated_token::mint = mint_b, // 这个账户存储mint_a代币
+        associated_token::mint = mint_b, // 这个账户存储mint_b代币
         associated_token::authority = pool_authority, // pool_authority拥有这个账户
+        associated_token::token_program = token_program_b,
     )]
-    pub pool_account_b: Box<Account<'info, TokenAccount>>,
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(mut)]
     pub payer: Signer<'info>,
 
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Program<'info, Token>,
+    // 池子两边的代币可以各自属于Token或Token-2022程序
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
\ No newline at end of file
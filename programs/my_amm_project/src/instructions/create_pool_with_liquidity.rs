@@ -0,0 +1,294 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::Token,
+    token_interface::{self, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked},
+};
+use crate::{
+    constants::{AUTHORITY_SEED, LIQUIDITY_SEED, MAX_AMP, MIN_AMP, MIN_WEIGHT, TOTAL_WEIGHT},
+    errors::TutorialError,
+    curve,
+    events::{LiquidityAdded, PoolCreated},
+    math,
+    state::{Amm, CurveType, Pool},
+    utils::get_transfer_fee,
+};
+
+// 在同一条指令里创建池子并存入初始流动性
+// 分开调用create_pool和deposit_liquidity时，两笔交易之间别人可以抢先存入一笔比例很坏的初始流动性
+// （见docs/AMM池子初始化的Frontrun攻击详解.md）
+// 合并成一条指令之后，池子一创建就已经有了创建者指定的价格；如果池子已经被别人抢先创建，init会失败，交易直接回滚
+pub fn create_pool_with_liquidity(
+    ctx: Context<CreatePoolWithLiquidity>,
+    amount_a: u64,
+    amount_b: u64,
+) -> Result<()> {
+    init_pool_with_liquidity(ctx, CurveType::ConstantProduct, 0, (0, 0), amount_a, amount_b)
+}
+
+// create_stable_pool的一步版本，amp的范围检查相同
+pub fn create_stable_pool_with_liquidity(
+    ctx: Context<CreatePoolWithLiquidity>,
+    amp: u64,
+    amount_a: u64,
+    amount_b: u64,
+) -> Result<()> {
+    if !(MIN_AMP..=MAX_AMP).contains(&amp) {
+        return err!(TutorialError::InvalidAmp);
+    }
+
+    init_pool_with_liquidity(ctx, CurveType::StableSwap, amp, (0, 0), amount_a, amount_b)
+}
+
+// create_weighted_pool的一步版本，weight_a的范围检查相同
+pub fn create_weighted_pool_with_liquidity(
+    ctx: Context<CreatePoolWithLiquidity>,
+    weight_a: u16,
+    amount_a: u64,
+    amount_b: u64,
+) -> Result<()> {
+    if !(MIN_WEIGHT..=TOTAL_WEIGHT - MIN_WEIGHT).contains(&weight_a) {
+        return err!(TutorialError::InvalidWeight);
+    }
+
+    init_pool_with_liquidity(
+        ctx,
+        CurveType::Weighted,
+        0,
+        (weight_a, TOTAL_WEIGHT - weight_a),
+        amount_a,
+        amount_b,
+    )
+}
+
+fn init_pool_with_liquidity(
+    ctx: Context<CreatePoolWithLiquidity>,
+    curve_type: CurveType,
+    amp: u64,
+    (weight_a, weight_b): (u16, u16),
+    amount_a: u64,
+    amount_b: u64,
+) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    pool.amm = ctx.accounts.amm.key();
+    pool.mint_a = ctx.accounts.mint_a.key();
+    pool.mint_b = ctx.accounts.mint_b.key();
+    pool.curve_type = curve_type;
+    pool.amp = amp;
+    pool.target_amp = amp;
+    pool.weight_a = weight_a;
+    pool.weight_b = weight_b;
+    // 价格累加器从创建时开始计时
+    pool.last_price_update = Clock::get()?.unix_timestamp;
+
+    // 池子实际收到的数量要扣掉Token-2022的转账手续费，LP按实际收到的数量铸造
+    let net_amount_a = math::sub(amount_a, get_transfer_fee(&ctx.accounts.mint_a.to_account_info(), amount_a)?)?;
+    let net_amount_b = math::sub(amount_b, get_transfer_fee(&ctx.accounts.mint_b.to_account_info(), amount_b)?)?;
+
+    // 初始存款按原样存入，不按余额截断：截断会改变创建者指定的价格
+    // 锁定一些最小流动性
+    let liquidity = curve::deposit_liquidity(pool.curve(Clock::get()?.unix_timestamp), net_amount_a, net_amount_b, 0, 0, 0)?;
+    // 新池子的储备就是这次实际存入的数量，之后的价格按它累计
+    pool.sync_reserves(net_amount_a, net_amount_b);
+
+    // 将代币转移到池子
+    // token a
+    token_interface::transfer_checked(
+        CpiContext::new(
+            ctx.accounts.token_program_a.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.depositor_account_a.to_account_info(),
+                mint: ctx.accounts.mint_a.to_account_info(),
+                to: ctx.accounts.pool_account_a.to_account_info(),
+                authority: ctx.accounts.depositor.to_account_info(),
+            },
+        ),
+        amount_a,
+        ctx.accounts.mint_a.decimals,
+    )?;
+
+    // token b
+    token_interface::transfer_checked(
+        CpiContext::new(
+            ctx.accounts.token_program_b.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.depositor_account_b.to_account_info(),
+                mint: ctx.accounts.mint_b.to_account_info(),
+                to: ctx.accounts.pool_account_b.to_account_info(),
+                authority: ctx.accounts.depositor.to_account_info(),
+            },
+        ),
+        amount_b,
+        ctx.accounts.mint_b.decimals,
+    )?;
+
+    // 给创建者铸造LP代币
+    let authority_bump = ctx.bumps.pool_authority;
+    let authority_seeds = &[
+        &ctx.accounts.amm.key().to_bytes(),
+        &ctx.accounts.mint_a.key().to_bytes(),
+        &ctx.accounts.mint_b.key().to_bytes(),
+        AUTHORITY_SEED,
+        &[authority_bump],
+    ];
+    let signer_seeds = &[&authority_seeds[..]];
+
+    token_interface::mint_to(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.mint_liquidity.to_account_info(),
+                to: ctx.accounts.depositor_account_liquidity.to_account_info(),
+                authority: ctx.accounts.pool_authority.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        liquidity,
+    )?;
+
+    emit!(PoolCreated {
+        amm: ctx.accounts.amm.key(),
+        pool: ctx.accounts.pool.key(),
+        mint_a: ctx.accounts.mint_a.key(),
+        mint_b: ctx.accounts.mint_b.key(),
+        mint_liquidity: ctx.accounts.mint_liquidity.key(),
+        curve_type: ctx.accounts.pool.curve_type,
+        amp: ctx.accounts.pool.amp,
+        weight_a: ctx.accounts.pool.weight_a,
+        weight_b: ctx.accounts.pool.weight_b,
+    });
+    emit!(LiquidityAdded {
+        pool: ctx.accounts.pool.key(),
+        depositor: ctx.accounts.depositor.key(),
+        amount_a,
+        amount_b,
+        liquidity,
+        reserve_a: net_amount_a,
+        reserve_b: net_amount_b,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CreatePoolWithLiquidity<'info>{
+
+    #[account(
+        seeds = [
+            amm.id.as_ref()
+        ],
+        bump,
+        constraint = !amm.paused @ TutorialError::PoolPaused,
+    )]
+    pub amm: Box<Account<'info, Amm>>,
+
+    #[account(
+        init,
+        space = Pool::LEN,
+        payer = payer,
+        seeds = [
+            amm.key().as_ref(),
+            mint_a.key().as_ref(),
+            mint_b.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    /// CHECK: Read only authority
+    #[account(
+        seeds = [
+            amm.key().as_ref(),
+            mint_a.key().as_ref(),
+            mint_b.key().as_ref(),
+            AUTHORITY_SEED,
+        ],
+        bump,
+    )]
+    pub pool_authority: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [
+            amm.key().as_ref(),
+            mint_a.key().as_ref(),
+            mint_b.key().as_ref(),
+            LIQUIDITY_SEED,
+        ],
+        bump,
+        mint::decimals = 6,
+        mint::authority = pool_authority,
+        mint::token_program = token_program, // LP代币统一用经典的Token程序
+    )]
+    pub mint_liquidity: Box<InterfaceAccount<'info, Mint>>,
+
+    // 规范顺序：mint_a < mint_b（按pubkey字节比较）
+    // 否则同一对代币可以建出A/B和B/A两个池子，流动性被分散
+    #[account(
+        constraint = mint_a.key() != mint_b.key() @ TutorialError::IdenticalMints,
+        constraint = mint_a.key() < mint_b.key() @ TutorialError::InvalidMintOrder,
+        mint::token_program = token_program_a,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = mint_a, // 这个账户存储mint_a代币
+        associated_token::authority = pool_authority, // pool_authority拥有这个账户
+        associated_token::token_program = token_program_a,
+    )]
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = mint_b, // 这个账户存储mint_b代币
+        associated_token::authority = pool_authority, // pool_authority拥有这个账户
+        associated_token::token_program = token_program_b,
+    )]
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = mint_liquidity,
+        associated_token::authority = depositor,
+    )]
+    pub depositor_account_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = depositor,
+        associated_token::token_program = token_program_a,
+    )]
+    pub depositor_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = depositor,
+        associated_token::token_program = token_program_b,
+    )]
+    pub depositor_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub depositor: Signer<'info>,
+
+    /// The account paying for all rents
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Program<'info, Token>,
+    // 池子两边的代币可以各自属于Token或Token-2022程序
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
\ No newline at end of file
@@ -0,0 +1,213 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{
+    constants::AUTHORITY_SEED,
+    errors::TutorialError,
+    events::PositionLiquidityRemoved,
+    math,
+    state::{ClmmPool, Deadline, Position, TickArray},
+    tick_math,
+    utils::{check_deadline, get_transfer_fee, update_position},
+};
+
+// 从集中流动性仓位取出liquidity的流动性，按当前价格换算成代币A、B，向下取整
+// 赚到的手续费结算进仓位，用collect_fees取出
+pub fn decrease_liquidity(
+    ctx: Context<DecreaseLiquidity>,
+    liquidity: u128,
+    min_amount_a: u64,
+    min_amount_b: u64,
+    deadline: Option<Deadline>,
+) -> Result<()> {
+    check_deadline(deadline)?;
+
+    let accounts = ctx.accounts;
+    if liquidity == 0 || liquidity > accounts.position.liquidity {
+        return err!(TutorialError::InsufficientLiquidity);
+    }
+    let liquidity_delta = i128::try_from(liquidity).map_err(|_| error!(TutorialError::MathOverflow))?;
+
+    let (amount_a, amount_b) = tick_math::amounts_for_liquidity(
+        accounts.pool.sqrt_price,
+        tick_math::sqrt_price_at_tick(accounts.position.tick_lower)?,
+        tick_math::sqrt_price_at_tick(accounts.position.tick_upper)?,
+        liquidity,
+        false,
+    )?;
+
+    // 滑点保护：比较的是用户实际收到的数量（扣掉Token-2022的转账手续费）
+    let received_a = math::sub(amount_a, get_transfer_fee(&accounts.mint_a.to_account_info(), amount_a)?)?;
+    let received_b = math::sub(amount_b, get_transfer_fee(&accounts.mint_b.to_account_info(), amount_b)?)?;
+    if received_a < min_amount_a || received_b < min_amount_b {
+        return err!(TutorialError::WithdrawOutputTooSmall);
+    }
+
+    let same_tick_array = accounts.tick_array_lower.key() == accounts.tick_array_upper.key();
+    update_position(
+        &mut accounts.pool,
+        &mut accounts.position,
+        &mut accounts.tick_array_lower,
+        &mut accounts.tick_array_upper,
+        same_tick_array,
+        -liquidity_delta,
+    )?;
+
+    // 生成PDA签名
+    let pool_key = accounts.pool.key();
+    let authority_bump = ctx.bumps.pool_authority;
+    let authority_seeds = &[&pool_key.to_bytes(), AUTHORITY_SEED, &[authority_bump]];
+    let signer_seeds = &[&authority_seeds[..]];
+
+    if amount_a > 0 {
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                accounts.token_program_a.to_account_info(),
+                TransferChecked {
+                    from: accounts.pool_account_a.to_account_info(),
+                    mint: accounts.mint_a.to_account_info(),
+                    to: accounts.owner_account_a.to_account_info(),
+                    authority: accounts.pool_authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount_a,
+            accounts.mint_a.decimals,
+        )?;
+    }
+    if amount_b > 0 {
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                accounts.token_program_b.to_account_info(),
+                TransferChecked {
+                    from: accounts.pool_account_b.to_account_info(),
+                    mint: accounts.mint_b.to_account_info(),
+                    to: accounts.owner_account_b.to_account_info(),
+                    authority: accounts.pool_authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount_b,
+            accounts.mint_b.decimals,
+        )?;
+    }
+
+    emit!(PositionLiquidityRemoved {
+        pool: pool_key,
+        position: accounts.position.key(),
+        owner: accounts.owner.key(),
+        liquidity,
+        amount_a,
+        amount_b,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct DecreaseLiquidity<'info> {
+    // 取款不检查暂停，和withdraw_liquidity一样
+    #[account(
+        mut,
+        seeds = [
+            pool.amm.as_ref(),
+            pool.mint_a.as_ref(),
+            pool.mint_b.as_ref(),
+            pool.tick_spacing.to_le_bytes().as_ref(),
+        ],
+        bump,
+        has_one = mint_a,
+        has_one = mint_b,
+    )]
+    pub pool: Box<Account<'info, ClmmPool>>,
+
+    /// CHECK: Read only authority
+    #[account(
+        seeds = [
+            pool.key().as_ref(),
+            AUTHORITY_SEED,
+        ],
+        bump,
+    )]
+    pub pool_authority: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        has_one = pool,
+        has_one = owner,
+    )]
+    pub position: Box<Account<'info, Position>>,
+
+    /// Tick array holding the lower tick of the position
+    #[account(
+        mut,
+        has_one = pool @ TutorialError::InvalidTickArray,
+    )]
+    pub tick_array_lower: Box<Account<'info, TickArray>>,
+
+    /// Tick array holding the upper tick of the position, can be the same account as `tick_array_lower`
+    #[account(
+        mut,
+        has_one = pool @ TutorialError::InvalidTickArray,
+    )]
+    pub tick_array_upper: Box<Account<'info, TickArray>>,
+
+    #[account(
+        mint::token_program = token_program_a,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = pool_authority,
+        associated_token::token_program = token_program_a,
+    )]
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = pool_authority,
+        associated_token::token_program = token_program_b,
+    )]
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_a,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_a,
+    )]
+    pub owner_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_b,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_b,
+    )]
+    pub owner_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub owner: Signer<'info>,
+
+    /// The account paying for all rents
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    // 池子两边的代币可以各自属于Token或Token-2022程序
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
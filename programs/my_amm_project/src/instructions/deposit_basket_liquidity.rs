@@ -0,0 +1,199 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::Token,
+    token_interface::{self, Mint, MintTo, TokenAccount, TransferChecked},
+};
+
+use crate::{
+    constants::{AUTHORITY_SEED, LIQUIDITY_SEED, MINIMUM_LIQUIDITY},
+    errors::TutorialError,
+    curve,
+    events::BasketLiquidityAdded,
+    math,
+    state::{Amm, BasketPool, Deadline},
+    utils::{check_deadline, get_transfer_fee, get_transfer_inverse_fee, load_basket_tokens},
+};
+
+// 篮子池子存款：按池子里每种代币的现有比例存入，铸造一个LP代币
+// amounts按pool.mints的顺序给出每种代币最多存入的数量
+// 每种代币的[mint, vault, depositor_account, token_program]通过remaining_accounts传入
+pub fn deposit_basket_liquidity<'info>(
+    ctx: Context<'_, '_, 'info, 'info, DepositBasketLiquidity<'info>>,
+    amounts: Vec<u64>,
+    min_liquidity_out: u64,
+    deadline: Option<Deadline>,
+) -> Result<()> {
+    check_deadline(deadline)?;
+
+    let pool = &ctx.accounts.pool;
+    let tokens = load_basket_tokens(
+        pool,
+        &ctx.accounts.pool_authority.key(),
+        &ctx.accounts.depositor.key(),
+        ctx.remaining_accounts,
+    )?;
+    if amounts.len() != tokens.len() {
+        return err!(TutorialError::InvalidTokenCount);
+    }
+
+    // 防止存款人存入不属于自己的资产
+    let amounts: Vec<u64> = amounts
+        .iter()
+        .zip(&tokens)
+        .map(|(&amount, token)| amount.min(token.balance))
+        .collect();
+
+    // 池子实际收到的数量要扣掉Token-2022的转账手续费
+    let net_amounts = amounts
+        .iter()
+        .zip(&tokens)
+        .map(|(&amount, token)| math::sub(amount, get_transfer_fee(token.mint, amount)?))
+        .collect::<Result<Vec<u64>>>()?;
+
+    // 第一次存款（还没有LP）设置池子的比例，锁定MINIMUM_LIQUIDITY
+    // 之前直接转进vault的代币也算进种子，否则只捐了一种代币就会让之后的按比例存款除以0
+    // 之后的存款按比例调整，多出来的部分留在用户那里
+    let reserves: Vec<u64> = tokens.iter().map(|token| token.reserve).collect();
+    let supply = ctx.accounts.mint_liquidity.supply;
+    let (liquidity, amounts) = if supply == 0 {
+        let seed = reserves
+            .iter()
+            .zip(&net_amounts)
+            .map(|(&reserve, &amount)| math::add(reserve, amount))
+            .collect::<Result<Vec<u64>>>()?;
+        let curve = pool.curve(Clock::get()?.unix_timestamp)?;
+        (curve::basket_initial_liquidity(curve, &seed)?, amounts)
+    } else {
+        let total_liquidity = math::add(supply, MINIMUM_LIQUIDITY)?;
+        let (liquidity, required) = curve::basket_deposit(&net_amounts, &reserves, total_liquidity)?;
+
+        // 要让池子正好收到required，转账时加上Token-2022的转账手续费
+        let amounts = required
+            .iter()
+            .zip(&tokens)
+            .map(|(&required, token)| math::add(required, get_transfer_inverse_fee(token.mint, required)?))
+            .collect::<Result<Vec<u64>>>()?;
+        (liquidity, amounts)
+    };
+
+    if liquidity == 0 {
+        return err!(TutorialError::DepositTooSmall);
+    }
+    if liquidity < min_liquidity_out {
+        return err!(TutorialError::LiquidityTooSmall);
+    }
+
+    // 将每种代币转移到池子
+    for (token, &amount) in tokens.iter().zip(&amounts) {
+        token_interface::transfer_checked(
+            CpiContext::new(
+                token.token_program.clone(),
+                TransferChecked {
+                    from: token.user_account.clone(),
+                    mint: token.mint.clone(),
+                    to: token.vault.clone(),
+                    authority: ctx.accounts.depositor.to_account_info(),
+                },
+            ),
+            amount,
+            token.decimals,
+        )?;
+    }
+
+    // 给用户铸造LP代币
+    let authority_bump = ctx.bumps.pool_authority;
+    let authority_seeds = &[
+        &pool.amm.to_bytes(),
+        &pool.id.to_bytes(),
+        AUTHORITY_SEED,
+        &[authority_bump],
+    ];
+    let signer_seeds = &[&authority_seeds[..]];
+
+    token_interface::mint_to(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.mint_liquidity.to_account_info(),
+                to: ctx.accounts.depositor_account_liquidity.to_account_info(),
+                authority: ctx.accounts.pool_authority.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        liquidity,
+    )?;
+
+    emit!(BasketLiquidityAdded {
+        pool: pool.key(),
+        depositor: ctx.accounts.depositor.key(),
+        amounts,
+        liquidity,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct DepositBasketLiquidity<'info> {
+    // 只用来检查AMM是否被暂停
+    #[account(
+        seeds = [
+            amm.id.as_ref()
+        ],
+        bump,
+        constraint = !amm.paused @ TutorialError::PoolPaused,
+    )]
+    pub amm: Box<Account<'info, Amm>>,
+
+    #[account(
+        seeds = [
+            pool.amm.as_ref(),
+            pool.id.as_ref(),
+        ],
+        bump,
+        has_one = amm,
+        constraint = !pool.paused @ TutorialError::PoolPaused,
+    )]
+    pub pool: Box<Account<'info, BasketPool>>,
+
+    /// CHECK: Read only authority
+    #[account(
+        seeds = [
+            pool.amm.as_ref(),
+            pool.id.as_ref(),
+            AUTHORITY_SEED,
+        ],
+        bump,
+    )]
+    pub pool_authority: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            pool.amm.as_ref(),
+            pool.id.as_ref(),
+            LIQUIDITY_SEED,
+        ],
+        bump,
+    )]
+    pub mint_liquidity: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_liquidity,
+        associated_token::authority = depositor,
+    )]
+    pub depositor_account_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub depositor: Signer<'info>,
+
+    /// The account paying for all rents
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
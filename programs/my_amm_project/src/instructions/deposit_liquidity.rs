@@ -1,22 +1,23 @@
 use anchor_lang::prelude::*;
 
 // self的含义：导入模块本身，这样我们可以：
-//   - 调用token::transfer() ← 使用模块函数
-//   - 同时使用Transfer ← 使用模块中的结构体
+//   - 调用token_interface::transfer_checked() ← 使用模块函数
+//   - 同时使用TransferChecked ← 使用模块中的结构体
+// token_interface同时兼容Token和Token-2022两个程序
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{self, Mint, Token, TokenAccount, MintTo, Transfer},
+    token::Token,
+    token_interface::{self, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked},
 };
-// 固定点数学库，用于精确计算：
-// - I64F64 = 64位整数 + 64位小数
-// - 避免浮点数精度丢失问题
-// - 区块链上不能用浮点数，必须用整数或固定点
-use fixed::types::I64F64;
 
 use crate::{
-    constants::{AUTHORITY_SEED, LIQUIDITY_SEED, MINIMUM_LIQUIDITY},
+    constants::{AUTHORITY_SEED, LIQUIDITY_SEED, MINIMUM_LIQUIDITY, ORACLE_SEED},
     errors::TutorialError,
-    state::Pool,
+    curve,
+    events::LiquidityAdded,
+    math,
+    state::{Amm, Deadline, Oracle, Pool},
+    utils::{check_deadline, get_transfer_fee},
 };
 
 
@@ -25,114 +26,108 @@ pub fn deposit_liquidity(
     ctx: Context<DepositLiquidity>,
     amount_a: u64,
     amount_b: u64,
+    min_liquidity_out: u64,
+    max_amount_a: Option<u64>,
+    max_amount_b: Option<u64>,
+    deadline: Option<Deadline>,
 ) -> Result<()> {
+    check_deadline(deadline)?;
+
     // 防止存款人存入不属于自己的资产
-    let mut amount_a = if amount_a > ctx.accounts.depositor_account_a.amount {
+    let amount_a = if amount_a > ctx.accounts.depositor_account_a.amount {
         ctx.accounts.depositor_account_a.amount
     } else {
         amount_a
     };
 
-    let mut amount_b = if amount_b > ctx.accounts.depositor_account_b.amount {
+    let amount_b = if amount_b > ctx.accounts.depositor_account_b.amount {
         ctx.accounts.depositor_account_b.amount
     } else {
         amount_b
     };
 
     // 确保按照与现有流动性相同的比例提供
-    let pool_a = &ctx.accounts.pool_account_a;
-    let pool_b = &ctx.accounts.pool_account_b;
-    
-    // 这样定义"池子创建"（让第一次deposit设置比例）会导致frontrun攻击风险
-    let pool_creation = pool_a.amount == 0 && pool_b.amount == 0;
-    
-    (amount_a, amount_b) = if pool_creation {
-        // 如果没有流动性，就按原样添加
-        (amount_a, amount_b)
-    } else {
-        // 池子不为空，必须按现有比例调整，同时确保不超过用户余额
-        
-        let ratio = I64F64::from_num(pool_a.amount).checked_div(I64F64::from_num(pool_b.amount)).unwrap();
-        
-        // 选项1：以amount_a为基准，计算需要的amount_b
-        let option1_a = amount_a;
-        let option1_b = I64F64::from_num(amount_a).checked_div(ratio).unwrap().to_num::<u64>();
-        
-        // 选项2：以amount_b为基准，计算需要的amount_a
-        let option2_b = amount_b;
-        let option2_a = I64F64::from_num(amount_b).checked_mul(ratio).unwrap().to_num::<u64>();
-        
-        // 获取用户实际余额
-        let user_balance_a = ctx.accounts.depositor_account_a.amount;
-        let user_balance_b = ctx.accounts.depositor_account_b.amount;
-        
-        // 检查选项可行性
-        let option1_valid = option1_a <= user_balance_a && option1_b <= user_balance_b;
-        let option2_valid = option2_a <= user_balance_a && option2_b <= user_balance_b;
-        
-        if option1_valid && option2_valid {
-            // 两个都可行，选择存款量更大的
-            if option1_a + option1_b >= option2_a + option2_b {
-                (option1_a, option1_b)
-            } else {
-                (option2_a, option2_b)
-            }
-        } else if option1_valid {
-            (option1_a, option1_b)
-        } else if option2_valid {
-            (option2_a, option2_b)
-        } else {
-            // 都不可行，计算在余额限制下的最优解
-            let constrained_by_a = (user_balance_a, I64F64::from_num(user_balance_a).checked_div(ratio).unwrap().to_num::<u64>());
-            let constrained_by_b = (I64F64::from_num(user_balance_b).checked_mul(ratio).unwrap().to_num::<u64>(), user_balance_b);
-            
-            if constrained_by_a.1 <= user_balance_b {
-                constrained_by_a
-            } else if constrained_by_b.0 <= user_balance_a {
-                constrained_by_b
-            } else {
-                // 极端情况，取能取到的最小值
-                (user_balance_a.min(constrained_by_b.0), user_balance_b.min(constrained_by_a.1))
-            }
-        }
-    };
+    // 储备要扣除vault里属于协议的手续费
+    let (reserve_a, reserve_b) = ctx
+        .accounts
+        .pool
+        .reserves(ctx.accounts.pool_account_a.amount, ctx.accounts.pool_account_b.amount)?;
+    let now = Clock::get()?.unix_timestamp;
+
+    // 按上一条指令结束时保存的储备累计价格，池子有oracle时必须传入，记录一个观测点
+    ctx.accounts.pool.update_price_cumulative(now);
+    if let Some(oracle) = ctx.accounts.oracle.as_mut() {
+        oracle.write(ctx.accounts.pool.observation());
+    }
 
-    // 计算存入的流动性数量
-    let mut liquidity = I64F64::from_num(amount_a).checked_mul(I64F64::from_num(amount_b)).unwrap().sqrt().to_num::<u64>();
+    // 池子不为空时必须按现有比例调整，同时确保不超过用户余额（见curve::deposit_amounts）
+    let (amount_a, amount_b) = curve::deposit_amounts(
+        amount_a,
+        amount_b,
+        reserve_a,
+        reserve_b,
+        ctx.accounts.depositor_account_a.amount,
+        ctx.accounts.depositor_account_b.amount,
+    )?;
 
-    // 在第一次存款时锁定一些最小流动性
-    if pool_creation {
-        if liquidity < MINIMUM_LIQUIDITY {
-            return err!(TutorialError::DepositTooSmall);
-        }
-        liquidity -= MINIMUM_LIQUIDITY;
+    // 池子实际收到的数量要扣掉Token-2022的转账手续费，LP按实际收到的数量铸造
+    let net_amount_a = math::sub(amount_a, get_transfer_fee(&ctx.accounts.mint_a.to_account_info(), amount_a)?)?;
+    let net_amount_b = math::sub(amount_b, get_transfer_fee(&ctx.accounts.mint_b.to_account_info(), amount_b)?)?;
+
+    // 计算存入的流动性数量，第一次存款时锁定一些最小流动性，之后按不变量的增长比例铸造
+    // 储备为空就算"池子创建"（让第一次deposit设置比例），这样定义会导致frontrun攻击风险
+    // 被锁定的MINIMUM_LIQUIDITY也算在总量里
+    let total_liquidity = math::add(ctx.accounts.mint_liquidity.supply, MINIMUM_LIQUIDITY)?;
+    let liquidity = curve::deposit_liquidity(
+        ctx.accounts.pool.curve(now),
+        net_amount_a,
+        net_amount_b,
+        reserve_a,
+        reserve_b,
+        total_liquidity,
+    )?;
+
+    // 滑点保护：按池子比例调整之后，存入量可能比预期多，铸造的LP可能比预期少
+    // （例如被三明治攻击改变了池子比例），这时交易直接失败
+    if max_amount_a.is_some_and(|max_amount_a| amount_a > max_amount_a)
+        || max_amount_b.is_some_and(|max_amount_b| amount_b > max_amount_b)
+    {
+        return err!(TutorialError::DepositTooLarge);
+    }
+    if liquidity < min_liquidity_out {
+        return err!(TutorialError::LiquidityTooSmall);
     }
 
     // 将代币转移到池子
     // token a
-    token::transfer(
+    // transfer_checked需要mint账户和精度，Token-2022只支持这个版本的转账
+    token_interface::transfer_checked(
         CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
+            ctx.accounts.token_program_a.to_account_info(),
+            TransferChecked {
                 from: ctx.accounts.depositor_account_a.to_account_info(),
+                mint: ctx.accounts.mint_a.to_account_info(),
                 to: ctx.accounts.pool_account_a.to_account_info(),
                 authority: ctx.accounts.depositor.to_account_info(),
             },
         ),
         amount_a,
+        ctx.accounts.mint_a.decimals,
     )?;
 
     // token b
-    token::transfer(
+    token_interface::transfer_checked(
         CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
+            ctx.accounts.token_program_b.to_account_info(),
+            TransferChecked {
                 from: ctx.accounts.depositor_account_b.to_account_info(),
+                mint: ctx.accounts.mint_b.to_account_info(),
                 to: ctx.accounts.pool_account_b.to_account_info(),
                 authority: ctx.accounts.depositor.to_account_info(),
             },
         ),
         amount_b,
+        ctx.accounts.mint_b.decimals,
     )?;
 
     // 给用户铸造LP代币
@@ -146,7 +141,7 @@ pub fn deposit_liquidity(
     ];
     let signer_seeds = &[&authority_seeds[..]];
 
-    token::mint_to(
+    token_interface::mint_to(
         CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
             MintTo {
@@ -159,24 +154,68 @@ pub fn deposit_liquidity(
         liquidity,
     )?;
 
+    ctx.accounts.pool_account_a.reload()?;
+    ctx.accounts.pool_account_b.reload()?;
+    let (reserve_a, reserve_b) = ctx
+        .accounts
+        .pool
+        .reserves(ctx.accounts.pool_account_a.amount, ctx.accounts.pool_account_b.amount)?;
+    // 保存这条指令结束时的储备，之后的价格按它累计
+    ctx.accounts.pool.sync_reserves(reserve_a, reserve_b);
+    emit!(LiquidityAdded {
+        pool: ctx.accounts.pool.key(),
+        depositor: ctx.accounts.depositor.key(),
+        amount_a,
+        amount_b,
+        liquidity,
+        reserve_a,
+        reserve_b,
+    });
+
     Ok(())
 }
 
 #[derive(Accounts)]
 pub struct DepositLiquidity<'info> {
 
+    // 只用来检查AMM是否被暂停
     #[account(
+        seeds = [
+            amm.id.as_ref()
+        ],
+        bump,
+        constraint = !amm.paused @ TutorialError::PoolPaused,
+    )]
+    pub amm: Box<Account<'info, Amm>>,
+
+    #[account(
+        mut,
         seeds = [  // 验证传入的pool账户地址确实是基于这些数据生成的正确PDA
             pool.amm.as_ref(),
             pool.mint_a.key().as_ref(),
             pool.mint_b.key().as_ref(),
         ],
         bump,
+        has_one = amm,
         has_one = mint_a, // 等价于 assert!(pool.mint_a == mint_a.key())
         has_one = mint_b,
+        constraint = !pool.paused @ TutorialError::PoolPaused,
+        constraint = !pool.flash_loan_active @ TutorialError::FlashLoanActive,
+        constraint = !pool.has_oracle || oracle.is_some() @ TutorialError::OracleRequired,
     )]
     pub pool: Box<Account<'info, Pool>>,
 
+    /// Price oracle of the pool, required once it was initialized so that every price change is observed
+    #[account(
+        mut,
+        seeds = [
+            pool.key().as_ref(),
+            ORACLE_SEED,
+        ],
+        bump,
+    )]
+    pub oracle: Option<Box<Account<'info, Oracle>>>,
+
     #[account(
         seeds = [
             pool.amm.as_ref(),
@@ -202,25 +241,33 @@ pub struct DepositLiquidity<'info> {
         ],
         bump,
     )]
-    pub mint_liquidity: Box<Account<'info, Mint>>,
+    pub mint_liquidity: Box<InterfaceAccount<'info, Mint>>,
 
-    pub mint_a: Box<Account<'info, Mint>>,
-    
-    pub mint_b: Box<Account<'info, Mint>>,
+    #[account(
+        mint::token_program = token_program_a,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
 
     #[account(
         mut,
         associated_token::mint = mint_a,
         associated_token::authority = pool_authority,
+        associated_token::token_program = token_program_a,
     )]
-    pub pool_account_a: Box<Account<'info, TokenAccount>>,
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
         associated_token::mint = mint_b,
         associated_token::authority = pool_authority,
+        associated_token::token_program = token_program_b,
     )]
-    pub pool_account_b: Box<Account<'info, TokenAccount>>,
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         init_if_needed,
@@ -229,7 +276,7 @@ pub struct DepositLiquidity<'info> {
         associated_token::mint = mint_liquidity,
         associated_token::authority = depositor,
     )]
-    pub depositor_account_liquidity: Box<Account<'info, TokenAccount>>,
+    pub depositor_account_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
 
     /// The account paying for all rents
     #[account(mut)]
@@ -239,19 +286,24 @@ pub struct DepositLiquidity<'info> {
         mut,
         associated_token::mint = mint_a,
         associated_token::authority = depositor,
+        associated_token::token_program = token_program_a,
     )]
-    pub depositor_account_a: Box<Account<'info, TokenAccount>>,
+    pub depositor_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
         associated_token::mint = mint_b,
         associated_token::authority = depositor,
+        associated_token::token_program = token_program_b,
     )]
-    pub depositor_account_b: Box<Account<'info, TokenAccount>>,
+    pub depositor_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
 
     pub depositor: Signer<'info>,
 
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Program<'info, Token>,
+    // 池子两边的代币可以各自属于Token或Token-2022程序
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
\ No newline at end of file
@@ -0,0 +1,327 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::Token,
+    token_interface::{self, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{
+    constants::{AUTHORITY_SEED, LIQUIDITY_SEED, MINIMUM_LIQUIDITY, ORACLE_SEED},
+    errors::TutorialError,
+    curve,
+    events::{LiquidityAdded, Swapped},
+    math,
+    state::{Amm, Deadline, Oracle, Pool},
+    utils::{check_deadline, get_transfer_fee},
+};
+
+// 单边存款：只持有一种代币时，不用先swap一半再deposit（两笔交易、两次滑点检查）
+// 在池子内部把一部分输入换成另一种代币，剩下的和换到的一起存入，只检查一次min_liquidity_out
+// 换出的代币不离开池子，所以用户只需要转入一次
+// 换出的数量用恒定乘积的闭式解计算，StableSwap和加权池子不支持单边存款，返回InvalidCurve
+pub fn deposit_single_sided(
+    ctx: Context<DepositSingleSided>,
+    deposit_a: bool,
+    amount_in: u64,
+    min_liquidity_out: u64,
+    deadline: Option<Deadline>,
+) -> Result<()> {
+    check_deadline(deadline)?;
+
+    let accounts = ctx.accounts;
+    let (token_program_in, depositor_account_in, pool_account_in, mint_in) = if deposit_a {
+        (
+            &accounts.token_program_a,
+            &accounts.depositor_account_a,
+            &accounts.pool_account_a,
+            &accounts.mint_a,
+        )
+    } else {
+        (
+            &accounts.token_program_b,
+            &accounts.depositor_account_b,
+            &accounts.pool_account_b,
+            &accounts.mint_b,
+        )
+    };
+
+    // 防止存款人存入不属于自己的资产
+    let amount_in = amount_in.min(depositor_account_in.amount);
+
+    // 池子实际收到的是扣掉Token-2022转账手续费之后的net_input
+    let net_input = math::sub(amount_in, get_transfer_fee(&mint_in.to_account_info(), amount_in)?)?;
+
+    let amm = &accounts.amm;
+    let now = Clock::get()?.unix_timestamp;
+    let fee = amm.current_fee(now);
+
+    let curve = accounts.pool.curve(now);
+    let (reserve_a, reserve_b) =
+        accounts.pool.reserves(accounts.pool_account_a.amount, accounts.pool_account_b.amount)?;
+
+    // 按上一条指令结束时保存的储备累计价格，池子有oracle时必须传入，记录一个观测点
+    accounts.pool.update_price_cumulative(now);
+    if let Some(oracle) = accounts.oracle.as_mut() {
+        oracle.write(accounts.pool.observation());
+    }
+
+    // 空池子没有价格，要先用deposit_liquidity设置比例
+    if reserve_a == 0 || reserve_b == 0 {
+        return err!(TutorialError::InsufficientLiquidity);
+    }
+    let (reserve_in, reserve_out) = if deposit_a {
+        (reserve_a, reserve_b)
+    } else {
+        (reserve_b, reserve_a)
+    };
+
+    // 内部swap：换出的数量按curve::single_sided_swap_amount计算，手续费和协议手续费都和普通swap一样
+    let swap_amount = curve::single_sided_swap_amount(curve, net_input, reserve_in, fee, amm.protocol_fee)?;
+    let quote = curve::swap_exact_input(curve.oriented(deposit_a), swap_amount, reserve_in, reserve_out, fee, amm.protocol_fee)?;
+
+    // swap之后的储备（协议手续费不计入），剩下的输入和换到的代币按这个比例存入
+    // 取整剩下的零头留在池子里，归LP
+    let reserve_in = math::sub(math::add(reserve_in, swap_amount)?, quote.protocol_fee_amount)?;
+    let reserve_out = math::sub(reserve_out, quote.output)?;
+    let remaining = math::sub(net_input, swap_amount)?;
+    let (deposit_in, deposit_out) =
+        curve::deposit_amounts(remaining, quote.output, reserve_in, reserve_out, remaining, quote.output)?;
+    let (deposit_amount_a, deposit_amount_b) = if deposit_a {
+        (deposit_in, deposit_out)
+    } else {
+        (deposit_out, deposit_in)
+    };
+
+    // LP按swap之后的储备和LP总量铸造（被锁定的MINIMUM_LIQUIDITY也算在总量里）
+    let (reserve_a, reserve_b) = if deposit_a {
+        (reserve_in, reserve_out)
+    } else {
+        (reserve_out, reserve_in)
+    };
+    let total_liquidity = math::add(accounts.mint_liquidity.supply, MINIMUM_LIQUIDITY)?;
+    let liquidity = curve::deposit_liquidity(
+        curve,
+        deposit_amount_a,
+        deposit_amount_b,
+        reserve_a,
+        reserve_b,
+        total_liquidity,
+    )?;
+    if liquidity == 0 {
+        return err!(TutorialError::DepositTooSmall);
+    }
+    // 滑点保护：swap和存款一起只检查最终铸造的LP数量
+    if liquidity < min_liquidity_out {
+        return err!(TutorialError::LiquidityTooSmall);
+    }
+
+    // 记录协议手续费
+    let pool = &mut accounts.pool;
+    if deposit_a {
+        pool.protocol_fees_a = math::add(pool.protocol_fees_a, quote.protocol_fee_amount)?;
+    } else {
+        pool.protocol_fees_b = math::add(pool.protocol_fees_b, quote.protocol_fee_amount)?;
+    }
+
+    // 用户的输入代币 → 池子
+    token_interface::transfer_checked(
+        CpiContext::new(
+            token_program_in.to_account_info(),
+            TransferChecked {
+                from: depositor_account_in.to_account_info(),
+                mint: mint_in.to_account_info(),
+                to: pool_account_in.to_account_info(),
+                authority: accounts.depositor.to_account_info(),
+            },
+        ),
+        amount_in,
+        mint_in.decimals,
+    )?;
+
+    // 内部swap也改变了池子的价格，和普通swap一样发出Swapped事件，储备是swap之后、存款之前的
+    emit!(Swapped {
+        pool: accounts.pool.key(),
+        trader: accounts.depositor.key(),
+        swap_a: deposit_a,
+        input_amount: swap_amount,
+        output_amount: quote.output,
+        fee_amount: quote.fee_amount,
+        protocol_fee_amount: quote.protocol_fee_amount,
+        reserve_a,
+        reserve_b,
+    });
+
+    // 给用户铸造LP代币
+    let authority_bump = ctx.bumps.pool_authority;
+    let authority_seeds = &[
+        &accounts.pool.amm.to_bytes(),
+        &accounts.mint_a.key().to_bytes(),
+        &accounts.mint_b.key().to_bytes(),
+        AUTHORITY_SEED,
+        &[authority_bump],
+    ];
+    let signer_seeds = &[&authority_seeds[..]];
+
+    token_interface::mint_to(
+        CpiContext::new_with_signer(
+            accounts.token_program.to_account_info(),
+            MintTo {
+                mint: accounts.mint_liquidity.to_account_info(),
+                to: accounts.depositor_account_liquidity.to_account_info(),
+                authority: accounts.pool_authority.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        liquidity,
+    )?;
+
+    accounts.pool_account_a.reload()?;
+    accounts.pool_account_b.reload()?;
+    let (reserve_a, reserve_b) =
+        accounts.pool.reserves(accounts.pool_account_a.amount, accounts.pool_account_b.amount)?;
+    // 保存这条指令结束时的储备，之后的价格按它累计
+    accounts.pool.sync_reserves(reserve_a, reserve_b);
+    let (amount_a, amount_b) = if deposit_a { (amount_in, 0) } else { (0, amount_in) };
+    emit!(LiquidityAdded {
+        pool: accounts.pool.key(),
+        depositor: accounts.depositor.key(),
+        amount_a,
+        amount_b,
+        liquidity,
+        reserve_a,
+        reserve_b,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct DepositSingleSided<'info> {
+    // 检查AMM是否被暂停，同时提供手续费
+    #[account(
+        seeds = [
+            amm.id.as_ref()
+        ],
+        bump,
+        constraint = !amm.paused @ TutorialError::PoolPaused,
+    )]
+    pub amm: Box<Account<'info, Amm>>,
+
+    #[account(
+        mut,
+        seeds = [
+            pool.amm.as_ref(),
+            pool.mint_a.key().as_ref(),
+            pool.mint_b.key().as_ref(),
+        ],
+        bump,
+        has_one = amm,
+        has_one = mint_a,
+        has_one = mint_b,
+        constraint = !pool.paused @ TutorialError::PoolPaused,
+        constraint = !pool.flash_loan_active @ TutorialError::FlashLoanActive,
+        constraint = !pool.has_oracle || oracle.is_some() @ TutorialError::OracleRequired,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    /// Price oracle of the pool, required once it was initialized so that every price change is observed
+    #[account(
+        mut,
+        seeds = [
+            pool.key().as_ref(),
+            ORACLE_SEED,
+        ],
+        bump,
+    )]
+    pub oracle: Option<Box<Account<'info, Oracle>>>,
+
+    #[account(
+        seeds = [
+            pool.amm.as_ref(),
+            mint_a.key().as_ref(),
+            mint_b.key().as_ref(),
+            AUTHORITY_SEED,
+        ],
+        bump,
+    )]
+    /// CHECK: Read only authority
+    pub pool_authority: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            pool.amm.as_ref(),
+            mint_a.key().as_ref(),
+            mint_b.key().as_ref(),
+            LIQUIDITY_SEED,
+        ],
+        bump,
+    )]
+    pub mint_liquidity: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program_a,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = pool_authority,
+        associated_token::token_program = token_program_a,
+    )]
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = pool_authority,
+        associated_token::token_program = token_program_b,
+    )]
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_liquidity,
+        associated_token::authority = depositor,
+    )]
+    pub depositor_account_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // 只有输入的一边需要有余额，另一边的账户不存在时创建
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_a,
+        associated_token::authority = depositor,
+        associated_token::token_program = token_program_a,
+    )]
+    pub depositor_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_b,
+        associated_token::authority = depositor,
+        associated_token::token_program = token_program_b,
+    )]
+    pub depositor_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub depositor: Signer<'info>,
+
+    /// The account paying for all rents
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Program<'info, Token>,
+    // 池子两边的代币可以各自属于Token或Token-2022程序
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
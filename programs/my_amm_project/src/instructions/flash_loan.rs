@@ -0,0 +1,394 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
+    Discriminator,
+};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{
+    constants::{AUTHORITY_SEED, ORACLE_SEED},
+    curve,
+    errors::TutorialError,
+    events::{FlashLoanBorrowed, FlashLoanRepaid},
+    math,
+    state::{Amm, Oracle, Pool},
+    utils::get_transfer_inverse_fee,
+};
+
+// flash_repay的账户里pool的位置（FlashRepay的第一个账户）
+const FLASH_REPAY_POOL_INDEX: usize = 0;
+
+// 闪电贷：在同一笔交易里从池子借出代币，之后的flash_repay必须还上本金加手续费
+// 手续费按AMM当前的交易费率收取，全部留在池子里归LP
+// 借款期间池子被锁住（flash_loan_active），不能交换、存取款或再借
+pub fn flash_borrow(ctx: Context<FlashBorrow>, amount_a: u64, amount_b: u64) -> Result<()> {
+    let (reserve_a, reserve_b) = ctx
+        .accounts
+        .pool
+        .reserves(ctx.accounts.pool_account_a.amount, ctx.accounts.pool_account_b.amount)?;
+    if (amount_a == 0 && amount_b == 0) || amount_a > reserve_a || amount_b > reserve_b {
+        return err!(TutorialError::InvalidFlashLoan);
+    }
+
+    check_flash_repay(&ctx.accounts.instructions, ctx.accounts.pool.key())?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let fee = ctx.accounts.amm.current_fee(now) as u64;
+    let fee_a = math::mul_div_ceil(amount_a, fee, 10000)?;
+    let fee_b = math::mul_div_ceil(amount_b, fee, 10000)?;
+
+    // 记录借款前的不变量和应还的数量，flash_repay按这些检查
+    let pool = &mut ctx.accounts.pool;
+    pool.flash_invariant = curve::invariant(pool.curve(now), reserve_a, reserve_b)?;
+    pool.flash_owed_a = math::add(amount_a, fee_a)?;
+    pool.flash_owed_b = math::add(amount_b, fee_b)?;
+    pool.flash_loan_active = true;
+
+    // 生成PDA签名
+    let authority_bump = ctx.bumps.pool_authority;
+    let authority_seeds = &[
+        &ctx.accounts.pool.amm.to_bytes(),
+        &ctx.accounts.mint_a.key().to_bytes(),
+        &ctx.accounts.mint_b.key().to_bytes(),
+        AUTHORITY_SEED,
+        &[authority_bump],
+    ];
+    let signer_seeds = &[&authority_seeds[..]];
+
+    // 池子 → 借款人
+    if amount_a > 0 {
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program_a.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.pool_account_a.to_account_info(),
+                    mint: ctx.accounts.mint_a.to_account_info(),
+                    to: ctx.accounts.borrower_account_a.to_account_info(),
+                    authority: ctx.accounts.pool_authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount_a,
+            ctx.accounts.mint_a.decimals,
+        )?;
+    }
+    if amount_b > 0 {
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program_b.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.pool_account_b.to_account_info(),
+                    mint: ctx.accounts.mint_b.to_account_info(),
+                    to: ctx.accounts.borrower_account_b.to_account_info(),
+                    authority: ctx.accounts.pool_authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount_b,
+            ctx.accounts.mint_b.decimals,
+        )?;
+    }
+
+    emit!(FlashLoanBorrowed {
+        pool: ctx.accounts.pool.key(),
+        borrower: ctx.accounts.borrower.key(),
+        amount_a,
+        amount_b,
+        fee_a,
+        fee_b,
+    });
+
+    Ok(())
+}
+
+// 还款：池子要正好收到本金加手续费（Token-2022的转账手续费由借款人另外承担）
+// 还款之后和swap_exact_tokens_for_tokens一样检查不变量没有变小
+pub fn flash_repay(ctx: Context<FlashRepay>) -> Result<()> {
+    let owed_a = ctx.accounts.pool.flash_owed_a;
+    let owed_b = ctx.accounts.pool.flash_owed_b;
+
+    // 借款人 → 池子
+    if owed_a > 0 {
+        let amount_a = math::add(owed_a, get_transfer_inverse_fee(&ctx.accounts.mint_a.to_account_info(), owed_a)?)?;
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program_a.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.borrower_account_a.to_account_info(),
+                    mint: ctx.accounts.mint_a.to_account_info(),
+                    to: ctx.accounts.pool_account_a.to_account_info(),
+                    authority: ctx.accounts.borrower.to_account_info(),
+                },
+            ),
+            amount_a,
+            ctx.accounts.mint_a.decimals,
+        )?;
+    }
+    if owed_b > 0 {
+        let amount_b = math::add(owed_b, get_transfer_inverse_fee(&ctx.accounts.mint_b.to_account_info(), owed_b)?)?;
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program_b.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.borrower_account_b.to_account_info(),
+                    mint: ctx.accounts.mint_b.to_account_info(),
+                    to: ctx.accounts.pool_account_b.to_account_info(),
+                    authority: ctx.accounts.borrower.to_account_info(),
+                },
+            ),
+            amount_b,
+            ctx.accounts.mint_b.decimals,
+        )?;
+    }
+
+    // 验证不变式仍然成立
+    ctx.accounts.pool_account_a.reload()?;
+    ctx.accounts.pool_account_b.reload()?;
+    let (reserve_a, reserve_b) = ctx
+        .accounts
+        .pool
+        .reserves(ctx.accounts.pool_account_a.amount, ctx.accounts.pool_account_b.amount)?;
+    let pool = &mut ctx.accounts.pool;
+    let now = Clock::get()?.unix_timestamp;
+    let curve = pool.curve(now);
+    if pool.flash_invariant > curve::invariant(curve, reserve_a, reserve_b)? {
+        return err!(TutorialError::InvariantViolated);
+    }
+
+    pool.flash_loan_active = false;
+    pool.flash_owed_a = 0;
+    pool.flash_owed_b = 0;
+    pool.flash_invariant = 0;
+
+    // 手续费留在池子里，储备变了：先按之前保存的储备累计价格，池子有oracle时记录一个观测点，再保存新的储备
+    pool.update_price_cumulative(now);
+    if let Some(oracle) = ctx.accounts.oracle.as_mut() {
+        oracle.write(pool.observation());
+    }
+    pool.sync_reserves(reserve_a, reserve_b);
+
+    emit!(FlashLoanRepaid {
+        pool: pool.key(),
+        borrower: ctx.accounts.borrower.key(),
+        amount_a: owed_a,
+        amount_b: owed_b,
+    });
+
+    Ok(())
+}
+
+// 用instructions sysvar检查同一笔交易里，当前指令之后有同一个池子的flash_repay
+// 没有找到时借款直接失败；找到之后，flash_repay失败也会让整笔交易（包括借款）回滚
+fn check_flash_repay(instructions: &AccountInfo, pool: Pubkey) -> Result<()> {
+    let current = load_current_index_checked(instructions)? as usize;
+    let mut index = current + 1;
+    while let Ok(instruction) = load_instruction_at_checked(index, instructions) {
+        if instruction.program_id == crate::ID
+            && instruction.data.starts_with(&crate::instruction::FlashRepay::DISCRIMINATOR)
+            && instruction
+                .accounts
+                .get(FLASH_REPAY_POOL_INDEX)
+                .is_some_and(|account| account.pubkey == pool)
+        {
+            return Ok(());
+        }
+        index += 1;
+    }
+    err!(TutorialError::MissingFlashRepay)
+}
+
+#[derive(Accounts)]
+pub struct FlashBorrow<'info> {
+    #[account(
+        seeds = [
+            amm.id.as_ref()
+        ],
+        bump,
+        constraint = !amm.paused @ TutorialError::PoolPaused,
+    )]
+    pub amm: Box<Account<'info, Amm>>,
+
+    #[account(
+        mut,
+        seeds = [
+            pool.amm.as_ref(),
+            pool.mint_a.key().as_ref(),
+            pool.mint_b.key().as_ref(),
+        ],
+        bump,
+        has_one = amm,
+        has_one = mint_a,
+        has_one = mint_b,
+        constraint = !pool.paused @ TutorialError::PoolPaused,
+        constraint = !pool.flash_loan_active @ TutorialError::FlashLoanActive,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        seeds = [
+            pool.amm.as_ref(),
+            mint_a.key().as_ref(),
+            mint_b.key().as_ref(),
+            AUTHORITY_SEED,
+        ],
+        bump,
+    )]
+    /// CHECK: Read only authority
+    pub pool_authority: AccountInfo<'info>,
+
+    /// The account borrowing from the pool
+    pub borrower: Signer<'info>,
+
+    #[account(
+        mint::token_program = token_program_a,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = pool_authority,
+        associated_token::token_program = token_program_a,
+    )]
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = pool_authority,
+        associated_token::token_program = token_program_b,
+    )]
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_a,
+        associated_token::authority = borrower,
+        associated_token::token_program = token_program_a,
+    )]
+    pub borrower_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_b,
+        associated_token::authority = borrower,
+        associated_token::token_program = token_program_b,
+    )]
+    pub borrower_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The account paying for all rents
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: The instructions sysvar, used to find the matching flash_repay
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: AccountInfo<'info>,
+
+    /// Solana ecosystem accounts
+    // 池子两边的代币可以各自属于Token或Token-2022程序
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FlashRepay<'info> {
+    // 必须是第一个账户：flash_borrow按FLASH_REPAY_POOL_INDEX读取
+    #[account(
+        mut,
+        seeds = [
+            pool.amm.as_ref(),
+            pool.mint_a.key().as_ref(),
+            pool.mint_b.key().as_ref(),
+        ],
+        bump,
+        has_one = mint_a,
+        has_one = mint_b,
+        constraint = pool.flash_loan_active @ TutorialError::FlashLoanNotActive,
+        constraint = !pool.has_oracle || oracle.is_some() @ TutorialError::OracleRequired,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    /// Price oracle of the pool, required once it was initialized so that every price change is observed
+    #[account(
+        mut,
+        seeds = [
+            pool.key().as_ref(),
+            ORACLE_SEED,
+        ],
+        bump,
+    )]
+    pub oracle: Option<Box<Account<'info, Oracle>>>,
+
+    #[account(
+        seeds = [
+            pool.amm.as_ref(),
+            mint_a.key().as_ref(),
+            mint_b.key().as_ref(),
+            AUTHORITY_SEED,
+        ],
+        bump,
+    )]
+    /// CHECK: Read only authority
+    pub pool_authority: AccountInfo<'info>,
+
+    /// The account repaying the loan, not necessarily the borrower
+    pub borrower: Signer<'info>,
+
+    #[account(
+        mint::token_program = token_program_a,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = pool_authority,
+        associated_token::token_program = token_program_a,
+    )]
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = pool_authority,
+        associated_token::token_program = token_program_b,
+    )]
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = borrower,
+        associated_token::token_program = token_program_a,
+    )]
+    pub borrower_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = borrower,
+        associated_token::token_program = token_program_b,
+    )]
+    pub borrower_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // 池子两边的代币可以各自属于Token或Token-2022程序
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+}
@@ -0,0 +1,232 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::{
+    constants::AUTHORITY_SEED,
+    errors::TutorialError,
+    events::PositionLiquidityAdded,
+    math,
+    state::{Amm, ClmmPool, Deadline, Position, TickArray},
+    tick_math,
+    utils::{check_deadline, get_transfer_fee, get_transfer_inverse_fee, update_position},
+};
+
+// 给集中流动性仓位添加流动性：最多存入amount_a、amount_b，按当前价格算出能提供的最大流动性
+// 当前价格低于区间时只存代币A，高于区间时只存代币B，在区间内时两种按比例存
+pub fn increase_liquidity(
+    ctx: Context<IncreaseLiquidity>,
+    amount_a: u64,
+    amount_b: u64,
+    min_liquidity_out: u128,
+    deadline: Option<Deadline>,
+) -> Result<()> {
+    check_deadline(deadline)?;
+
+    // 防止存款人存入不属于自己的资产
+    let amount_a = amount_a.min(ctx.accounts.owner_account_a.amount);
+    let amount_b = amount_b.min(ctx.accounts.owner_account_b.amount);
+
+    // 池子实际收到的数量要扣掉Token-2022的转账手续费，流动性按实际收到的数量计算
+    let net_amount_a = math::sub(amount_a, get_transfer_fee(&ctx.accounts.mint_a.to_account_info(), amount_a)?)?;
+    let net_amount_b = math::sub(amount_b, get_transfer_fee(&ctx.accounts.mint_b.to_account_info(), amount_b)?)?;
+
+    let accounts = ctx.accounts;
+    let sqrt_price = accounts.pool.sqrt_price;
+    let sqrt_price_lower = tick_math::sqrt_price_at_tick(accounts.position.tick_lower)?;
+    let sqrt_price_upper = tick_math::sqrt_price_at_tick(accounts.position.tick_upper)?;
+    let liquidity = tick_math::liquidity_for_amounts(
+        sqrt_price,
+        sqrt_price_lower,
+        sqrt_price_upper,
+        net_amount_a,
+        net_amount_b,
+    )?;
+    if liquidity == 0 {
+        return err!(TutorialError::DepositTooSmall);
+    }
+    if liquidity < min_liquidity_out {
+        return err!(TutorialError::LiquidityTooSmall);
+    }
+    let liquidity_delta = i128::try_from(liquidity).map_err(|_| error!(TutorialError::MathOverflow))?;
+
+    // 池子需要收到的数量向上取整，不会超过net_amount
+    let (required_a, required_b) = tick_math::amounts_for_liquidity(
+        sqrt_price,
+        sqrt_price_lower,
+        sqrt_price_upper,
+        liquidity,
+        true,
+    )?;
+
+    let same_tick_array = accounts.tick_array_lower.key() == accounts.tick_array_upper.key();
+    update_position(
+        &mut accounts.pool,
+        &mut accounts.position,
+        &mut accounts.tick_array_lower,
+        &mut accounts.tick_array_upper,
+        same_tick_array,
+        liquidity_delta,
+    )?;
+
+    // 要让池子正好收到required，转账时加上Token-2022的转账手续费
+    let amount_a = if required_a > 0 {
+        required_a + get_transfer_inverse_fee(&accounts.mint_a.to_account_info(), required_a)?
+    } else {
+        0
+    };
+    let amount_b = if required_b > 0 {
+        required_b + get_transfer_inverse_fee(&accounts.mint_b.to_account_info(), required_b)?
+    } else {
+        0
+    };
+
+    if amount_a > 0 {
+        token_interface::transfer_checked(
+            CpiContext::new(
+                accounts.token_program_a.to_account_info(),
+                TransferChecked {
+                    from: accounts.owner_account_a.to_account_info(),
+                    mint: accounts.mint_a.to_account_info(),
+                    to: accounts.pool_account_a.to_account_info(),
+                    authority: accounts.owner.to_account_info(),
+                },
+            ),
+            amount_a,
+            accounts.mint_a.decimals,
+        )?;
+    }
+    if amount_b > 0 {
+        token_interface::transfer_checked(
+            CpiContext::new(
+                accounts.token_program_b.to_account_info(),
+                TransferChecked {
+                    from: accounts.owner_account_b.to_account_info(),
+                    mint: accounts.mint_b.to_account_info(),
+                    to: accounts.pool_account_b.to_account_info(),
+                    authority: accounts.owner.to_account_info(),
+                },
+            ),
+            amount_b,
+            accounts.mint_b.decimals,
+        )?;
+    }
+
+    emit!(PositionLiquidityAdded {
+        pool: accounts.pool.key(),
+        position: accounts.position.key(),
+        owner: accounts.owner.key(),
+        liquidity,
+        amount_a,
+        amount_b,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct IncreaseLiquidity<'info> {
+    // 只用来检查AMM是否被暂停
+    #[account(
+        seeds = [
+            amm.id.as_ref()
+        ],
+        bump,
+        constraint = !amm.paused @ TutorialError::PoolPaused,
+    )]
+    pub amm: Box<Account<'info, Amm>>,
+
+    #[account(
+        mut,
+        seeds = [
+            pool.amm.as_ref(),
+            pool.mint_a.as_ref(),
+            pool.mint_b.as_ref(),
+            pool.tick_spacing.to_le_bytes().as_ref(),
+        ],
+        bump,
+        has_one = amm,
+        has_one = mint_a,
+        has_one = mint_b,
+        constraint = !pool.paused @ TutorialError::PoolPaused,
+    )]
+    pub pool: Box<Account<'info, ClmmPool>>,
+
+    /// CHECK: Read only authority
+    #[account(
+        seeds = [
+            pool.key().as_ref(),
+            AUTHORITY_SEED,
+        ],
+        bump,
+    )]
+    pub pool_authority: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        has_one = pool,
+        has_one = owner,
+    )]
+    pub position: Box<Account<'info, Position>>,
+
+    /// Tick array holding the lower tick of the position
+    #[account(
+        mut,
+        has_one = pool @ TutorialError::InvalidTickArray,
+    )]
+    pub tick_array_lower: Box<Account<'info, TickArray>>,
+
+    /// Tick array holding the upper tick of the position, can be the same account as `tick_array_lower`
+    #[account(
+        mut,
+        has_one = pool @ TutorialError::InvalidTickArray,
+    )]
+    pub tick_array_upper: Box<Account<'info, TickArray>>,
+
+    #[account(
+        mint::token_program = token_program_a,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = pool_authority,
+        associated_token::token_program = token_program_a,
+    )]
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = pool_authority,
+        associated_token::token_program = token_program_b,
+    )]
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_a,
+    )]
+    pub owner_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_b,
+    )]
+    pub owner_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub owner: Signer<'info>,
+
+    // 池子两边的代币可以各自属于Token或Token-2022程序
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+}
@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{MAX_TICK, MIN_TICK, TICK_ARRAY_SEED, TICK_ARRAY_SIZE},
+    errors::TutorialError,
+    state::{ClmmPool, Tick, TickArray},
+};
+
+// tick array保存连续TICK_ARRAY_SIZE个tick（间隔tick_spacing）的流动性和手续费数据
+// 开仓位之前，上下界所在的tick array必须已经创建；任何人都可以付租金创建
+pub fn initialize_tick_array(
+    ctx: Context<InitializeTickArray>,
+    start_tick: i32,
+) -> Result<()> {
+    let tick_spacing = ctx.accounts.pool.tick_spacing;
+    let span = TICK_ARRAY_SIZE as i32 * tick_spacing as i32;
+    if start_tick != TickArray::start_tick_for(start_tick, tick_spacing)
+        || start_tick > MAX_TICK
+        || start_tick + span <= MIN_TICK
+    {
+        return err!(TutorialError::InvalidTickArray);
+    }
+
+    let tick_array = &mut ctx.accounts.tick_array;
+    tick_array.pool = ctx.accounts.pool.key();
+    tick_array.start_tick = start_tick;
+    tick_array.ticks = vec![Tick::default(); TICK_ARRAY_SIZE];
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(start_tick: i32)]
+pub struct InitializeTickArray<'info> {
+    #[account(
+        seeds = [
+            pool.amm.as_ref(),
+            pool.mint_a.as_ref(),
+            pool.mint_b.as_ref(),
+            pool.tick_spacing.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub pool: Box<Account<'info, ClmmPool>>,
+
+    #[account(
+        init,
+        space = TickArray::LEN,
+        payer = payer,
+        seeds = [
+            pool.key().as_ref(),
+            TICK_ARRAY_SEED,
+            start_tick.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub tick_array: Box<Account<'info, TickArray>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -1,11 +1,67 @@
 pub mod create_amm;
 pub mod create_pool;
+pub mod create_pool_with_liquidity;
 pub mod deposit_liquidity;
+pub mod deposit_single_sided;
 pub mod withdraw_liquidity;
 pub mod swap_exact_tokens_for_tokens;
+pub mod swap_tokens_for_exact_tokens;
+pub mod swap_route;
+pub mod set_fee;
+pub mod propose_admin;
+pub mod accept_admin;
+pub mod cancel_admin_transfer;
+pub mod set_protocol_fee;
+pub mod collect_protocol_fees;
+pub mod set_pause;
+pub mod quote;
+pub mod ramp_amp;
+pub mod create_basket_pool;
+pub mod deposit_basket_liquidity;
+pub mod withdraw_basket_liquidity;
+pub mod swap_basket;
+pub mod collect_basket_protocol_fees;
+pub mod create_clmm_pool;
+pub mod initialize_tick_array;
+pub mod open_position;
+pub mod increase_liquidity;
+pub mod decrease_liquidity;
+pub mod collect_fees;
+pub mod swap_clmm;
+pub mod collect_clmm_protocol_fees;
+pub mod oracle;
+pub mod flash_loan;
 
 pub use create_amm::*;
 pub use create_pool::*;
+pub use create_pool_with_liquidity::*;
 pub use deposit_liquidity::*;
+pub use deposit_single_sided::*;
 pub use withdraw_liquidity::*;
-pub use swap_exact_tokens_for_tokens::*;
\ No newline at end of file
+pub use swap_exact_tokens_for_tokens::*;
+pub use swap_tokens_for_exact_tokens::*;
+pub use swap_route::*;
+pub use set_fee::*;
+pub use propose_admin::*;
+pub use accept_admin::*;
+pub use cancel_admin_transfer::*;
+pub use set_protocol_fee::*;
+pub use collect_protocol_fees::*;
+pub use set_pause::*;
+pub use quote::*;
+pub use ramp_amp::*;
+pub use create_basket_pool::*;
+pub use deposit_basket_liquidity::*;
+pub use withdraw_basket_liquidity::*;
+pub use swap_basket::*;
+pub use collect_basket_protocol_fees::*;
+pub use create_clmm_pool::*;
+pub use initialize_tick_array::*;
+pub use open_position::*;
+pub use increase_liquidity::*;
+pub use decrease_liquidity::*;
+pub use collect_fees::*;
+pub use swap_clmm::*;
+pub use collect_clmm_protocol_fees::*;
+pub use oracle::*;
+pub use flash_loan::*;
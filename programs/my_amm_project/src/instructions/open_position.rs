@@ -0,0 +1,102 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{MAX_TICK, MIN_TICK, POSITION_SEED},
+    errors::TutorialError,
+    events::PositionOpened,
+    state::{ClmmPool, Position},
+};
+
+// 在集中流动性池子里开一个价格区间为[tick_lower, tick_upper)的空仓位
+// 上下界必须是tick_spacing的倍数；流动性用increase_liquidity添加
+pub fn open_position(
+    ctx: Context<OpenPosition>,
+    tick_lower: i32,
+    tick_upper: i32,
+) -> Result<()> {
+    let tick_spacing = ctx.accounts.pool.tick_spacing as i32;
+    if tick_lower >= tick_upper
+        || tick_lower < MIN_TICK
+        || tick_upper > MAX_TICK
+        || tick_lower % tick_spacing != 0
+        || tick_upper % tick_spacing != 0
+    {
+        return err!(TutorialError::InvalidTickRange);
+    }
+
+    let position = &mut ctx.accounts.position;
+    position.pool = ctx.accounts.pool.key();
+    position.owner = ctx.accounts.owner.key();
+    position.tick_lower = tick_lower;
+    position.tick_upper = tick_upper;
+
+    emit!(PositionOpened {
+        pool: position.pool,
+        position: position.key(),
+        owner: position.owner,
+        tick_lower,
+        tick_upper,
+    });
+    Ok(())
+}
+
+// 关闭空仓位，租金退给owner
+pub fn close_position(ctx: Context<ClosePosition>) -> Result<()> {
+    let position = &ctx.accounts.position;
+    if position.liquidity != 0 || position.fees_owed_a != 0 || position.fees_owed_b != 0 {
+        return err!(TutorialError::PositionNotEmpty);
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(tick_lower: i32, tick_upper: i32)]
+pub struct OpenPosition<'info> {
+    #[account(
+        seeds = [
+            pool.amm.as_ref(),
+            pool.mint_a.as_ref(),
+            pool.mint_b.as_ref(),
+            pool.tick_spacing.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub pool: Box<Account<'info, ClmmPool>>,
+
+    // 同一个owner在同一个区间只有一个仓位
+    #[account(
+        init,
+        space = Position::LEN,
+        payer = payer,
+        seeds = [
+            pool.key().as_ref(),
+            POSITION_SEED,
+            owner.key().as_ref(),
+            tick_lower.to_le_bytes().as_ref(),
+            tick_upper.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub position: Box<Account<'info, Position>>,
+
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClosePosition<'info> {
+    #[account(
+        mut,
+        has_one = owner,
+        close = owner,
+    )]
+    pub position: Box<Account<'info, Position>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
@@ -0,0 +1,210 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{MAX_ORACLE_OBSERVATIONS, ORACLE_SEED},
+    errors::TutorialError,
+    math::{self, U256},
+    state::{ClmmPool, Observation, Oracle, Pool},
+};
+
+// 池子的价格累加器在每次swap/deposit/withdraw时更新（Uniswap v2的做法）
+// 价格来自上一条指令结束时保存在池子里的储备（Pool::sync_reserves），不是vault的余额
+// oracle账户是观测点的环形缓冲区：池子创建oracle之后，这些指令都必须传入oracle，顺便记录一次累加器的值
+// 每次价格变化之前都有观测点，两个观测点之间价格不变，窗口起点的插值是精确的
+// 集中流动性池子的价格就是sqrt_price，只有swap_clmm会改变它，所以只在swap_clmm里更新
+// 两个时间点的累加器之差除以时间间隔就是这段时间的时间加权平均价格（TWAP），单笔交易很难操纵
+
+/// Time-weighted average prices of a pool over a window, as Q64.64 fixed-point numbers
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct Twap {
+    /// Average price of token B in token A (A per B)
+    pub price_a_per_b: u128,
+    /// Average price of token A in token B (B per A)
+    pub price_b_per_a: u128,
+}
+
+// 给池子创建oracle，最多保存capacity个观测点；任何人都可以付租金创建
+pub fn initialize_oracle(ctx: Context<InitializeOracle>, capacity: u16) -> Result<()> {
+    if capacity == 0 || capacity > MAX_ORACLE_OBSERVATIONS {
+        return err!(TutorialError::InvalidOracleCapacity);
+    }
+
+    let oracle = &mut ctx.accounts.oracle;
+    oracle.pool = ctx.accounts.pool.key();
+    oracle.capacity = capacity;
+    // 第一个观测点是池子上次更新时的累加器
+    oracle.write(ctx.accounts.pool.observation());
+    // 之后改变价格的指令都必须传入oracle
+    ctx.accounts.pool.has_oracle = true;
+
+    Ok(())
+}
+
+// 给集中流动性池子创建oracle，和initialize_oracle一样
+pub fn initialize_clmm_oracle(ctx: Context<InitializeClmmOracle>, capacity: u16) -> Result<()> {
+    if capacity == 0 || capacity > MAX_ORACLE_OBSERVATIONS {
+        return err!(TutorialError::InvalidOracleCapacity);
+    }
+
+    let oracle = &mut ctx.accounts.oracle;
+    oracle.pool = ctx.accounts.pool.key();
+    oracle.capacity = capacity;
+    oracle.write(ctx.accounts.pool.observation());
+    ctx.accounts.pool.has_oracle = true;
+
+    Ok(())
+}
+
+// 只读：返回过去window秒的TWAP，由Anchor通过set_return_data写入
+// 当前的累加器按池子保存的储备推算到当前时间（不读vault，直接转进vault的代币不影响结果）
+pub fn consult_twap(ctx: Context<ConsultTwap>, window: u32) -> Result<Twap> {
+    if window == 0 {
+        return err!(TutorialError::InvalidTwapWindow);
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    let mut pool = Pool::clone(&ctx.accounts.pool);
+    pool.update_price_cumulative(now);
+
+    twap(&ctx.accounts.oracle, &pool.observation(), now, window)
+}
+
+// 只读：返回集中流动性池子过去window秒的TWAP，当前的累加器按现在的sqrt_price推算到当前时间
+pub fn consult_clmm_twap(ctx: Context<ConsultClmmTwap>, window: u32) -> Result<Twap> {
+    if window == 0 {
+        return err!(TutorialError::InvalidTwapWindow);
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    let mut pool = ClmmPool::clone(&ctx.accounts.pool);
+    pool.update_price_cumulative(now);
+
+    twap(&ctx.accounts.oracle, &pool.observation(), now, window)
+}
+
+// current是推算到now的累加器，窗口起点的累加器在观测点之间插值
+fn twap(oracle: &Oracle, current: &Observation, now: i64, window: u32) -> Result<Twap> {
+    let (start_a_per_b, start_b_per_a) = oracle.cumulative_at(now - window as i64, current)?;
+    let average = |start: u128, end: u128| {
+        math::u256_to_u128(U256::from(end.wrapping_sub(start)) / U256::from(window))
+    };
+
+    Ok(Twap {
+        price_a_per_b: average(start_a_per_b, current.price_a_per_b_cumulative)?,
+        price_b_per_a: average(start_b_per_a, current.price_b_per_a_cumulative)?,
+    })
+}
+
+#[derive(Accounts)]
+#[instruction(capacity: u16)]
+pub struct InitializeOracle<'info> {
+    #[account(
+        mut,
+        seeds = [
+            pool.amm.as_ref(),
+            pool.mint_a.key().as_ref(),
+            pool.mint_b.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        init,
+        space = Oracle::space(capacity as usize),
+        payer = payer,
+        seeds = [
+            pool.key().as_ref(),
+            ORACLE_SEED,
+        ],
+        bump,
+    )]
+    pub oracle: Box<Account<'info, Oracle>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// 和报价指令一样，账户都是只读的
+#[derive(Accounts)]
+pub struct ConsultTwap<'info> {
+    #[account(
+        seeds = [
+            pool.amm.as_ref(),
+            pool.mint_a.key().as_ref(),
+            pool.mint_b.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        seeds = [
+            pool.key().as_ref(),
+            ORACLE_SEED,
+        ],
+        bump,
+        has_one = pool,
+    )]
+    pub oracle: Box<Account<'info, Oracle>>,
+}
+
+#[derive(Accounts)]
+#[instruction(capacity: u16)]
+pub struct InitializeClmmOracle<'info> {
+    #[account(
+        mut,
+        seeds = [
+            pool.amm.as_ref(),
+            pool.mint_a.as_ref(),
+            pool.mint_b.as_ref(),
+            pool.tick_spacing.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub pool: Box<Account<'info, ClmmPool>>,
+
+    #[account(
+        init,
+        space = Oracle::space(capacity as usize),
+        payer = payer,
+        seeds = [
+            pool.key().as_ref(),
+            ORACLE_SEED,
+        ],
+        bump,
+    )]
+    pub oracle: Box<Account<'info, Oracle>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// 和ConsultTwap一样，价格在池子账户里，不需要读vault
+#[derive(Accounts)]
+pub struct ConsultClmmTwap<'info> {
+    #[account(
+        seeds = [
+            pool.amm.as_ref(),
+            pool.mint_a.as_ref(),
+            pool.mint_b.as_ref(),
+            pool.tick_spacing.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub pool: Box<Account<'info, ClmmPool>>,
+
+    #[account(
+        seeds = [
+            pool.key().as_ref(),
+            ORACLE_SEED,
+        ],
+        bump,
+        has_one = pool,
+    )]
+    pub oracle: Box<Account<'info, Oracle>>,
+}
@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::*, events::AdminTransferProposed, state::Amm};
+
+// admin转移分两步：
+// 1. 当前admin调用propose_admin提名新admin
+// 2. 新admin调用accept_admin签名接受
+// 新admin必须签名，避免把权限转给一个没人控制的地址（例如填错的地址）
+pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+    ctx.accounts.amm.pending_admin = new_admin;
+
+    emit!(AdminTransferProposed {
+        amm: ctx.accounts.amm.key(),
+        admin: ctx.accounts.admin.key(),
+        pending_admin: new_admin,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ProposeAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [
+            amm.id.as_ref()
+        ],
+        bump,
+        has_one = admin @ TutorialError::Unauthorized,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    pub admin: Signer<'info>,
+}
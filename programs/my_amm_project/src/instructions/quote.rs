@@ -0,0 +1,267 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    constants::{AUTHORITY_SEED, LIQUIDITY_SEED, MINIMUM_LIQUIDITY},
+    curve,
+    math,
+    state::{Amm, Pool},
+    utils::get_transfer_fee,
+};
+
+// 只读的报价指令：不转账、不修改任何账户
+// 返回值由Anchor通过set_return_data写入，客户端可以用simulate读取，其他程序可以CPI调用
+// 计算和真正的swap/deposit/withdraw共用curve模块和转账手续费的算法，结果逐位一致
+
+/// Expected result of `swap_exact_tokens_for_tokens`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct SwapPreview {
+    /// Amount of the input token sent by the trader
+    pub input_amount: u64,
+    /// Amount of the output token received by the trader, net of transfer fees
+    pub output_amount: u64,
+    /// Trading fee taken from the input, LP and protocol parts together
+    pub fee_amount: u64,
+    /// Part of `fee_amount` kept by the protocol
+    pub protocol_fee_amount: u64,
+    /// Difference between the spot price and the execution price, in basis points, excluding fees
+    pub price_impact: u16,
+}
+
+/// Expected result of `deposit_liquidity`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct DepositPreview {
+    /// Amount of token A sent by the depositor after adjusting to the pool ratio
+    pub amount_a: u64,
+    /// Amount of token B sent by the depositor after adjusting to the pool ratio
+    pub amount_b: u64,
+    /// LP tokens minted to the depositor
+    pub liquidity: u64,
+}
+
+/// Expected result of `withdraw_liquidity`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct WithdrawPreview {
+    /// Amount of token A received by the depositor, net of transfer fees
+    pub amount_a: u64,
+    /// Amount of token B received by the depositor, net of transfer fees
+    pub amount_b: u64,
+}
+
+pub fn quote_swap(ctx: Context<QuoteSwap>, swap_a: bool, input_amount: u64) -> Result<SwapPreview> {
+    let amm = &ctx.accounts.amm;
+    let now = Clock::get()?.unix_timestamp;
+    let fee = amm.current_fee(now);
+    let curve = ctx.accounts.pool.curve(now).oriented(swap_a);
+
+    let (reserve_a, reserve_b) = ctx
+        .accounts
+        .pool
+        .reserves(ctx.accounts.pool_account_a.amount, ctx.accounts.pool_account_b.amount)?;
+    let (reserve_in, reserve_out, mint_in, mint_out) = if swap_a {
+        (reserve_a, reserve_b, ctx.accounts.mint_a.to_account_info(), ctx.accounts.mint_b.to_account_info())
+    } else {
+        (reserve_b, reserve_a, ctx.accounts.mint_b.to_account_info(), ctx.accounts.mint_a.to_account_info())
+    };
+
+    let net_input = math::sub(input_amount, get_transfer_fee(&mint_in, input_amount)?)?;
+    let quote = curve::swap_exact_input(curve, net_input, reserve_in, reserve_out, fee, amm.protocol_fee)?;
+    let received = math::sub(quote.output, get_transfer_fee(&mint_out, quote.output)?)?;
+
+    Ok(SwapPreview {
+        input_amount,
+        output_amount: received,
+        fee_amount: quote.fee_amount,
+        protocol_fee_amount: quote.protocol_fee_amount,
+        price_impact: curve::price_impact(curve, &quote, reserve_in, reserve_out)?,
+    })
+}
+
+// balance_a / balance_b 是存款人的余额，deposit_liquidity会按余额截断并选择比例
+// 传None时假设存款人正好持有amount_a / amount_b
+pub fn quote_deposit(
+    ctx: Context<QuoteLiquidity>,
+    amount_a: u64,
+    amount_b: u64,
+    balance_a: Option<u64>,
+    balance_b: Option<u64>,
+) -> Result<DepositPreview> {
+    let balance_a = balance_a.unwrap_or(amount_a);
+    let balance_b = balance_b.unwrap_or(amount_b);
+
+    let (reserve_a, reserve_b) = ctx
+        .accounts
+        .pool
+        .reserves(ctx.accounts.pool_account_a.amount, ctx.accounts.pool_account_b.amount)?;
+
+    let (amount_a, amount_b) = curve::deposit_amounts(
+        amount_a.min(balance_a),
+        amount_b.min(balance_b),
+        reserve_a,
+        reserve_b,
+        balance_a,
+        balance_b,
+    )?;
+
+    let net_amount_a = math::sub(amount_a, get_transfer_fee(&ctx.accounts.mint_a.to_account_info(), amount_a)?)?;
+    let net_amount_b = math::sub(amount_b, get_transfer_fee(&ctx.accounts.mint_b.to_account_info(), amount_b)?)?;
+
+    Ok(DepositPreview {
+        amount_a,
+        amount_b,
+        liquidity: curve::deposit_liquidity(
+            ctx.accounts.pool.curve(Clock::get()?.unix_timestamp),
+            net_amount_a,
+            net_amount_b,
+            reserve_a,
+            reserve_b,
+            math::add(ctx.accounts.mint_liquidity.supply, MINIMUM_LIQUIDITY)?,
+        )?,
+    })
+}
+
+pub fn quote_withdraw(ctx: Context<QuoteLiquidity>, amount: u64) -> Result<WithdrawPreview> {
+    let (reserve_a, reserve_b) = ctx
+        .accounts
+        .pool
+        .reserves(ctx.accounts.pool_account_a.amount, ctx.accounts.pool_account_b.amount)?;
+
+    let total_liquidity = math::add(ctx.accounts.mint_liquidity.supply, MINIMUM_LIQUIDITY)?;
+    let (amount_a, amount_b) = curve::withdraw_amounts(amount, reserve_a, reserve_b, total_liquidity)?;
+
+    Ok(WithdrawPreview {
+        amount_a: math::sub(amount_a, get_transfer_fee(&ctx.accounts.mint_a.to_account_info(), amount_a)?)?,
+        amount_b: math::sub(amount_b, get_transfer_fee(&ctx.accounts.mint_b.to_account_info(), amount_b)?)?,
+    })
+}
+
+// 报价指令的账户都是只读的，不检查暂停状态（暂停时真正的指令会失败，报价仍然可以查看）
+#[derive(Accounts)]
+pub struct QuoteSwap<'info> {
+    // 读取当前生效的手续费和协议手续费
+    #[account(
+        seeds = [
+            amm.id.as_ref()
+        ],
+        bump,
+    )]
+    pub amm: Box<Account<'info, Amm>>,
+
+    #[account(
+        seeds = [
+            pool.amm.as_ref(),
+            pool.mint_a.key().as_ref(),
+            pool.mint_b.key().as_ref(),
+        ],
+        bump,
+        has_one = amm,
+        has_one = mint_a,
+        has_one = mint_b,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        seeds = [
+            pool.amm.as_ref(),
+            mint_a.key().as_ref(),
+            mint_b.key().as_ref(),
+            AUTHORITY_SEED,
+        ],
+        bump,
+    )]
+    /// CHECK: Read only authority
+    pub pool_authority: AccountInfo<'info>,
+
+    #[account(
+        mint::token_program = token_program_a,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        associated_token::mint = mint_a,
+        associated_token::authority = pool_authority,
+        associated_token::token_program = token_program_a,
+    )]
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        associated_token::mint = mint_b,
+        associated_token::authority = pool_authority,
+        associated_token::token_program = token_program_b,
+    )]
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+}
+
+// quote_deposit和quote_withdraw共用，需要LP mint的总供应量
+#[derive(Accounts)]
+pub struct QuoteLiquidity<'info> {
+    #[account(
+        seeds = [
+            pool.amm.as_ref(),
+            pool.mint_a.key().as_ref(),
+            pool.mint_b.key().as_ref(),
+        ],
+        bump,
+        has_one = mint_a,
+        has_one = mint_b,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(
+        seeds = [
+            pool.amm.as_ref(),
+            mint_a.key().as_ref(),
+            mint_b.key().as_ref(),
+            AUTHORITY_SEED,
+        ],
+        bump,
+    )]
+    /// CHECK: Read only authority
+    pub pool_authority: AccountInfo<'info>,
+
+    #[account(
+        seeds = [
+            pool.amm.as_ref(),
+            mint_a.key().as_ref(),
+            mint_b.key().as_ref(),
+            LIQUIDITY_SEED,
+        ],
+        bump,
+    )]
+    pub mint_liquidity: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program_a,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        associated_token::mint = mint_a,
+        associated_token::authority = pool_authority,
+        associated_token::token_program = token_program_a,
+    )]
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        associated_token::mint = mint_b,
+        associated_token::authority = pool_authority,
+        associated_token::token_program = token_program_b,
+    )]
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+}
@@ -0,0 +1,150 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{MAX_AMP, MAX_AMP_CHANGE, MIN_AMP, MIN_AMP_RAMP_DURATION},
+    errors::*,
+    events::{AmpRampStarted, AmpRampStopped},
+    state::{Amm, AmpRamp, BasketPool, CurveType, Pool},
+};
+
+// 在[now, ramp_end]之间把StableSwap池子的amp从当前值线性调整到target_amp
+// 直接修改amp会让池子的价格瞬间跳变，别人可以在修改前后套利LP的资金
+// 所以每次调整至少持续MIN_AMP_RAMP_DURATION，幅度不超过MAX_AMP_CHANGE倍
+pub fn ramp_amp(ctx: Context<RampAmp>, target_amp: u64, ramp_end: i64) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    let key = pool.key();
+    start_amp_ramp(&mut **pool, key, target_amp, ramp_end)
+}
+
+// 停止正在进行的调整，amp固定在当前插值出的值
+// amp_ramp_start不变，仍然要等到上一次调整开始MIN_AMP_RAMP_DURATION之后才能开始新的调整
+pub fn stop_ramp_amp(ctx: Context<RampAmp>) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    let key = pool.key();
+    stop_amp_ramp(&mut **pool, key)
+}
+
+// StableSwap篮子池子的amp调整，和ramp_amp共用同一套规则
+pub fn ramp_basket_amp(ctx: Context<RampBasketAmp>, target_amp: u64, ramp_end: i64) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    let key = pool.key();
+    start_amp_ramp(&mut **pool, key, target_amp, ramp_end)
+}
+
+// 停止篮子池子正在进行的调整，和stop_ramp_amp一样
+pub fn stop_ramp_basket_amp(ctx: Context<RampBasketAmp>) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    let key = pool.key();
+    stop_amp_ramp(&mut **pool, key)
+}
+
+// 开始一次新的调整：检查调整的规则，从当前插值出的amp开始
+// 两种代币的池子和篮子池子都通过AmpRamp调用这里，规则不会分叉
+fn start_amp_ramp(pool: &mut impl AmpRamp, key: Pubkey, target_amp: u64, ramp_end: i64) -> Result<()> {
+    if pool.curve_type() != CurveType::StableSwap {
+        return err!(TutorialError::InvalidCurve);
+    }
+    if !(MIN_AMP..=MAX_AMP).contains(&target_amp) {
+        return err!(TutorialError::InvalidAmp);
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    let amp = pool.current_amp(now);
+    let (_, _, amp_ramp_start, _) = pool.amp_ramp();
+
+    // 两次调整的开始时间至少间隔MIN_AMP_RAMP_DURATION（stop_amp_ramp不改变开始时间），
+    // 否则可以用很多次短调整绕过速度限制
+    if now < amp_ramp_start.checked_add(MIN_AMP_RAMP_DURATION).ok_or(TutorialError::MathOverflow)? {
+        return err!(TutorialError::InvalidAmpRamp);
+    }
+    if ramp_end < now.checked_add(MIN_AMP_RAMP_DURATION).ok_or(TutorialError::MathOverflow)? {
+        return err!(TutorialError::InvalidAmpRamp);
+    }
+
+    // 上调和下调都不能超过MAX_AMP_CHANGE倍，amp和target_amp都不小于MIN_AMP，乘法不会溢出
+    if target_amp > amp * MAX_AMP_CHANGE || amp > target_amp * MAX_AMP_CHANGE {
+        return err!(TutorialError::InvalidAmpRamp);
+    }
+
+    pool.set_amp_ramp(amp, target_amp, now, ramp_end);
+
+    emit!(AmpRampStarted {
+        pool: key,
+        amp,
+        target_amp,
+        ramp_start: now,
+        ramp_end,
+    });
+
+    Ok(())
+}
+
+// 把amp固定在当前插值出的值，保留上一次调整的开始时间
+fn stop_amp_ramp(pool: &mut impl AmpRamp, key: Pubkey) -> Result<()> {
+    if pool.curve_type() != CurveType::StableSwap {
+        return err!(TutorialError::InvalidCurve);
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    let amp = pool.current_amp(now);
+    let (_, _, amp_ramp_start, _) = pool.amp_ramp();
+    pool.set_amp_ramp(amp, amp, amp_ramp_start, now);
+
+    emit!(AmpRampStopped {
+        pool: key,
+        amp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RampAmp<'info> {
+    #[account(
+        seeds = [
+            amm.id.as_ref()
+        ],
+        bump,
+        has_one = admin @ TutorialError::Unauthorized,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    #[account(
+        mut,
+        seeds = [
+            pool.amm.as_ref(),
+            pool.mint_a.key().as_ref(),
+            pool.mint_b.key().as_ref(),
+        ],
+        bump,
+        has_one = amm,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RampBasketAmp<'info> {
+    #[account(
+        seeds = [
+            amm.id.as_ref()
+        ],
+        bump,
+        has_one = admin @ TutorialError::Unauthorized,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    #[account(
+        mut,
+        seeds = [
+            pool.amm.as_ref(),
+            pool.id.as_ref(),
+        ],
+        bump,
+        has_one = amm,
+    )]
+    pub pool: Account<'info, BasketPool>,
+
+    pub admin: Signer<'info>,
+}
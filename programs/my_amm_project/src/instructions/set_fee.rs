@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::*, events::FeeChanged, state::Amm};
+
+// 手续费修改分两步：
+// 1. admin调用set_fee提出新手续费，写入pending_fee
+// 2. 经过amm.fee_delay秒之后，新手续费自动生效（swap通过amm.current_fee()读取）
+// 这样LP在手续费变化之前有足够的时间决定是否撤出流动性
+pub fn set_fee(ctx: Context<SetFee>, fee: u16) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let amm = &mut ctx.accounts.amm;
+
+    // 先把已经生效的pending_fee落地，避免被新提案覆盖掉
+    amm.settle_fee(now);
+
+    amm.pending_fee = fee;
+    amm.pending_fee_activation = now
+        .checked_add(amm.fee_delay)
+        .ok_or(TutorialError::MathOverflow)?;
+
+    emit!(FeeChanged {
+        amm: amm.key(),
+        fee: amm.fee,
+        pending_fee: amm.pending_fee,
+        pending_fee_activation: amm.pending_fee_activation,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(fee: u16)]
+pub struct SetFee<'info> {
+    #[account(
+        mut,
+        seeds = [
+            amm.id.as_ref()
+        ],
+        bump,
+        has_one = admin @ TutorialError::Unauthorized,
+        constraint = fee < 10000 @ TutorialError::InvalidFee,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    pub admin: Signer<'info>,
+}
@@ -0,0 +1,149 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::*,
+    events::{AmmPauseChanged, PoolPauseChanged},
+    state::{Amm, BasketPool, ClmmPool, Pool},
+};
+
+// 暂停整个AMM：所有池子都不能swap和deposit
+// withdraw不受影响，保证LP随时都能撤出
+pub fn set_amm_pause(ctx: Context<SetAmmPause>, paused: bool) -> Result<()> {
+    ctx.accounts.amm.paused = paused;
+
+    emit!(AmmPauseChanged {
+        amm: ctx.accounts.amm.key(),
+        paused,
+    });
+
+    Ok(())
+}
+
+// 只暂停单个池子
+pub fn set_pool_pause(ctx: Context<SetPoolPause>, paused: bool) -> Result<()> {
+    ctx.accounts.pool.paused = paused;
+
+    emit!(PoolPauseChanged {
+        pool: ctx.accounts.pool.key(),
+        paused,
+    });
+
+    Ok(())
+}
+
+// 只暂停单个篮子池子，和set_pool_pause一样withdraw不受影响
+pub fn set_basket_pool_pause(ctx: Context<SetBasketPoolPause>, paused: bool) -> Result<()> {
+    ctx.accounts.pool.paused = paused;
+
+    emit!(PoolPauseChanged {
+        pool: ctx.accounts.pool.key(),
+        paused,
+    });
+
+    Ok(())
+}
+
+// 只暂停单个集中流动性池子：不能swap和增加流动性，减少流动性和领取手续费不受影响
+pub fn set_clmm_pool_pause(ctx: Context<SetClmmPoolPause>, paused: bool) -> Result<()> {
+    ctx.accounts.pool.paused = paused;
+
+    emit!(PoolPauseChanged {
+        pool: ctx.accounts.pool.key(),
+        paused,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetAmmPause<'info> {
+    #[account(
+        mut,
+        seeds = [
+            amm.id.as_ref()
+        ],
+        bump,
+        has_one = admin @ TutorialError::Unauthorized,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPoolPause<'info> {
+    #[account(
+        seeds = [
+            amm.id.as_ref()
+        ],
+        bump,
+        has_one = admin @ TutorialError::Unauthorized,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    #[account(
+        mut,
+        seeds = [
+            pool.amm.as_ref(),
+            pool.mint_a.key().as_ref(),
+            pool.mint_b.key().as_ref(),
+        ],
+        bump,
+        has_one = amm,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetBasketPoolPause<'info> {
+    #[account(
+        seeds = [
+            amm.id.as_ref()
+        ],
+        bump,
+        has_one = admin @ TutorialError::Unauthorized,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    #[account(
+        mut,
+        seeds = [
+            pool.amm.as_ref(),
+            pool.id.as_ref(),
+        ],
+        bump,
+        has_one = amm,
+    )]
+    pub pool: Account<'info, BasketPool>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetClmmPoolPause<'info> {
+    #[account(
+        seeds = [
+            amm.id.as_ref()
+        ],
+        bump,
+        has_one = admin @ TutorialError::Unauthorized,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    #[account(
+        mut,
+        seeds = [
+            pool.amm.as_ref(),
+            pool.mint_a.as_ref(),
+            pool.mint_b.as_ref(),
+            pool.tick_spacing.to_le_bytes().as_ref(),
+        ],
+        bump,
+        has_one = amm,
+    )]
+    pub pool: Account<'info, ClmmPool>,
+
+    pub admin: Signer<'info>,
+}
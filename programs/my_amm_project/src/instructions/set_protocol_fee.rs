@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::*, events::ProtocolFeeChanged, state::Amm};
+
+// protocol_fee是交易手续费中协议所占的比例（以手续费的基点计算）
+// 例如 fee = 30（0.3%），protocol_fee = 2000（20%），则每笔交易的0.06%归协议，0.24%归LP
+pub fn set_protocol_fee(
+    ctx: Context<SetProtocolFee>,
+    protocol_fee: u16,
+    treasury: Pubkey,
+) -> Result<()> {
+    let amm = &mut ctx.accounts.amm;
+    amm.protocol_fee = protocol_fee;
+    amm.treasury = treasury;
+
+    emit!(ProtocolFeeChanged {
+        amm: amm.key(),
+        protocol_fee,
+        treasury,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(protocol_fee: u16)]
+pub struct SetProtocolFee<'info> {
+    #[account(
+        mut,
+        seeds = [
+            amm.id.as_ref()
+        ],
+        bump,
+        has_one = admin @ TutorialError::Unauthorized,
+        constraint = protocol_fee <= 10000 @ TutorialError::InvalidProtocolFee,
+    )]
+    pub amm: Account<'info, Amm>,
+
+    pub admin: Signer<'info>,
+}
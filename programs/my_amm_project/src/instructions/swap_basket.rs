@@ -0,0 +1,216 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+use crate::{
+    constants::AUTHORITY_SEED,
+    errors::*,
+    curve,
+    events::BasketSwapped,
+    math,
+    state::{Amm, BasketPool, Deadline},
+    utils::{check_deadline, get_transfer_fee, load_basket_reserves},
+};
+
+// 篮子池子交换：用第input_index种代币换第output_index种代币（下标按pool.mints的顺序）
+// StableSwap的D和所有代币的储备有关，所以每种代币的vault都要按顺序通过remaining_accounts传入
+pub fn swap_basket<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SwapBasket<'info>>,
+    input_index: u8,
+    output_index: u8,
+    input_amount: u64,
+    min_output_amount: u64,
+    deadline: Option<Deadline>,
+) -> Result<()> {
+    check_deadline(deadline)?;
+
+    let pool = &ctx.accounts.pool;
+    let (i, j) = (input_index as usize, output_index as usize);
+    if i == j || i >= pool.mints.len() || j >= pool.mints.len() {
+        return err!(TutorialError::InvalidTokenIndex);
+    }
+    if pool.mints[i] != ctx.accounts.mint_in.key() || pool.mints[j] != ctx.accounts.mint_out.key() {
+        return err!(TutorialError::InvalidMint);
+    }
+
+    let vaults = ctx.remaining_accounts;
+    let reserves = load_basket_reserves(pool, &ctx.accounts.pool_authority.key(), vaults)?;
+
+    // 防止存款人存入不属于自己的资产
+    let input = if input_amount > ctx.accounts.trader_account_in.amount {
+        ctx.accounts.trader_account_in.amount
+    } else {
+        input_amount
+    };
+
+    // 池子实际收到的数量要扣掉Token-2022的转账手续费
+    let net_input = math::sub(input, get_transfer_fee(&ctx.accounts.mint_in.to_account_info(), input)?)?;
+
+    // 使用当前生效的手续费
+    let amm = &ctx.accounts.amm;
+    let now = Clock::get()?.unix_timestamp;
+    let fee = amm.current_fee(now);
+    let curve = pool.curve(now)?;
+
+    // 应用交易费，计算输出（公式见curve::basket_swap_exact_input）
+    let quote = curve::basket_swap_exact_input(curve, net_input, &reserves, i, j, fee, amm.protocol_fee)?;
+    let output = quote.output;
+
+    // 滑点保护针对用户实际收到的数量
+    let received = math::sub(output, get_transfer_fee(&ctx.accounts.mint_out.to_account_info(), output)?)?;
+    if received < min_output_amount {
+        return err!(TutorialError::OutputTooSmall);
+    }
+
+    // 计算交易前的不变量（只算LP的储备，协议手续费不参与）
+    let invariant = curve::basket_invariant(curve, &reserves, i, j)?;
+
+    // 记录协议手续费，留在输入代币的vault里但不计入LP的储备
+    let pool = &mut ctx.accounts.pool;
+    pool.protocol_fees[i] = math::add(pool.protocol_fees[i], quote.protocol_fee_amount)?;
+    let pool = &ctx.accounts.pool;
+
+    token_interface::transfer_checked(
+        CpiContext::new(
+            ctx.accounts.token_program_in.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.trader_account_in.to_account_info(),
+                mint: ctx.accounts.mint_in.to_account_info(),
+                to: vaults[i].clone(),
+                authority: ctx.accounts.trader.to_account_info(),
+            },
+        ),
+        input,
+        ctx.accounts.mint_in.decimals,
+    )?;
+
+    let authority_bump = ctx.bumps.pool_authority;
+    let authority_seeds = &[
+        &pool.amm.to_bytes(),
+        &pool.id.to_bytes(),
+        AUTHORITY_SEED,
+        &[authority_bump],
+    ];
+    let signer_seeds = &[&authority_seeds[..]];
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program_out.to_account_info(),
+            TransferChecked {
+                from: vaults[j].clone(),
+                mint: ctx.accounts.mint_out.to_account_info(),
+                to: ctx.accounts.trader_account_out.to_account_info(),
+                authority: ctx.accounts.pool_authority.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        output,
+        ctx.accounts.mint_out.decimals,
+    )?;
+
+    msg!(
+        "Traded {} of token {} for {} of token {}",
+        input,
+        input_index,
+        output,
+        output_index
+    );
+
+    // 验证不变量仍然成立
+    let reserves = load_basket_reserves(pool, &ctx.accounts.pool_authority.key(), vaults)?;
+    if invariant > curve::basket_invariant(curve, &reserves, i, j)? {
+        return err!(TutorialError::InvariantViolated);
+    }
+
+    emit!(BasketSwapped {
+        pool: pool.key(),
+        trader: ctx.accounts.trader.key(),
+        input_index,
+        output_index,
+        input_amount: input,
+        output_amount: output,
+        fee_amount: quote.fee_amount,
+        protocol_fee_amount: quote.protocol_fee_amount,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SwapBasket<'info> {
+    #[account(
+        seeds = [
+            amm.id.as_ref()
+        ],
+        bump,
+        constraint = !amm.paused @ TutorialError::PoolPaused,
+    )]
+    pub amm: Box<Account<'info, Amm>>,
+
+    #[account(
+        mut,
+        seeds = [
+            pool.amm.as_ref(),
+            pool.id.as_ref(),
+        ],
+        bump,
+        has_one = amm,
+        constraint = !pool.paused @ TutorialError::PoolPaused,
+    )]
+    pub pool: Box<Account<'info, BasketPool>>,
+
+    /// CHECK: Read only authority
+    #[account(
+        seeds = [
+            pool.amm.as_ref(),
+            pool.id.as_ref(),
+            AUTHORITY_SEED,
+        ],
+        bump,
+    )]
+    pub pool_authority: AccountInfo<'info>,
+
+    /// The account doing the swap
+    pub trader: Signer<'info>,
+
+    /// Mint of the token the trader pays with
+    #[account(
+        mint::token_program = token_program_in,
+    )]
+    pub mint_in: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Mint of the token the trader receives
+    #[account(
+        mint::token_program = token_program_out,
+    )]
+    pub mint_out: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_in,
+        associated_token::authority = trader,
+        associated_token::token_program = token_program_in,
+    )]
+    pub trader_account_in: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_out,
+        associated_token::authority = trader,
+        associated_token::token_program = token_program_out,
+    )]
+    pub trader_account_out: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The account paying for all rents
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Solana ecosystem accounts
+    // 输入、输出代币可以各自属于Token或Token-2022程序
+    pub token_program_in: Interface<'info, TokenInterface>,
+    pub token_program_out: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
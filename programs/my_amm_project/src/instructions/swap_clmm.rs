@@ -0,0 +1,330 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{
+    constants::{AUTHORITY_SEED, MAX_SQRT_PRICE, MAX_SWAP_TICK_ARRAYS, MAX_TICK, MIN_SQRT_PRICE, MIN_TICK, ORACLE_SEED},
+    errors::TutorialError,
+    events::ClmmSwapped,
+    math,
+    state::{Amm, ClmmPool, Deadline, Oracle, TickArray},
+    tick_math,
+    utils::{check_deadline, get_transfer_fee},
+};
+
+// 在集中流动性池子里用input_amount个输入代币交换
+// 价格在两个已初始化的tick之间时流动性不变，按恒定乘积计算；到达tick时穿过它，流动性加上或减去liquidity_net
+// 价格会经过的tick array按交换方向依次通过remaining_accounts传入（最多MAX_SWAP_TICK_ARRAYS个）
+pub fn swap_clmm<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SwapClmm<'info>>,
+    swap_a: bool,
+    input_amount: u64,
+    min_output_amount: u64,
+    deadline: Option<Deadline>,
+) -> Result<()> {
+    check_deadline(deadline)?;
+
+    let remaining_accounts = ctx.remaining_accounts;
+    if remaining_accounts.is_empty() || remaining_accounts.len() > MAX_SWAP_TICK_ARRAYS as usize {
+        return err!(TutorialError::InvalidTickArray);
+    }
+    let pool_key = ctx.accounts.pool.key();
+    let mut tick_arrays: Vec<Account<'info, TickArray>> = Vec::with_capacity(remaining_accounts.len());
+    for (i, info) in remaining_accounts.iter().enumerate() {
+        if !info.is_writable || remaining_accounts[..i].iter().any(|other| other.key == info.key) {
+            return err!(TutorialError::InvalidTickArray);
+        }
+        let tick_array = Account::<TickArray>::try_from(info)?;
+        if tick_array.pool != pool_key {
+            return err!(TutorialError::InvalidTickArray);
+        }
+        tick_arrays.push(tick_array);
+    }
+
+    // 防止存款人存入不属于自己的资产
+    let input = if swap_a {
+        input_amount.min(ctx.accounts.trader_account_a.amount)
+    } else {
+        input_amount.min(ctx.accounts.trader_account_b.amount)
+    };
+
+    // Token-2022的transfer-fee mint在转账时会扣一部分，池子实际收到的是net_input
+    let (mint_in, mint_out) = if swap_a {
+        (ctx.accounts.mint_a.to_account_info(), ctx.accounts.mint_b.to_account_info())
+    } else {
+        (ctx.accounts.mint_b.to_account_info(), ctx.accounts.mint_a.to_account_info())
+    };
+    let net_input = math::sub(input, get_transfer_fee(&mint_in, input)?)?;
+
+    // 使用当前生效的手续费（set_fee提出的新手续费要等延迟结束才生效）
+    let now = Clock::get()?.unix_timestamp;
+    let fee = ctx.accounts.amm.current_fee(now);
+    let protocol_fee = ctx.accounts.amm.protocol_fee;
+
+    // 按交换之前的价格累计，池子有oracle时必须传入，记录一个观测点
+    ctx.accounts.pool.update_price_cumulative(now);
+    if let Some(oracle) = ctx.accounts.oracle.as_mut() {
+        oracle.write(ctx.accounts.pool.observation());
+    }
+
+    let pool = &mut ctx.accounts.pool;
+    let tick_spacing = pool.tick_spacing;
+    let spacing = tick_spacing as i32;
+    let mut sqrt_price = pool.sqrt_price;
+    let mut tick = pool.tick;
+    let mut liquidity = pool.liquidity;
+    let mut fee_growth_global_a = pool.fee_growth_global_a;
+    let mut fee_growth_global_b = pool.fee_growth_global_b;
+
+    let mut amount_remaining = net_input;
+    let mut output: u64 = 0;
+    let mut fee_total: u64 = 0;
+    let mut protocol_fee_total: u64 = 0;
+    while amount_remaining > 0 {
+        // 找到价格下一步会到达的tick所在的tick array
+        let first = tick.div_euclid(spacing) * spacing + if swap_a { 0 } else { spacing };
+        let start_tick = TickArray::start_tick_for(first, tick_spacing);
+        let tick_array = tick_arrays
+            .iter_mut()
+            .find(|tick_array| tick_array.start_tick == start_tick)
+            .ok_or_else(|| error!(TutorialError::InvalidTickArray))?;
+
+        let (next_tick, initialized) = tick_array.next_initialized_tick(tick, tick_spacing, swap_a)?;
+        let next_tick = next_tick.clamp(MIN_TICK, MAX_TICK);
+        let sqrt_price_target = tick_math::sqrt_price_at_tick(next_tick)?;
+
+        let step = tick_math::compute_swap_step(
+            sqrt_price,
+            sqrt_price_target,
+            liquidity,
+            amount_remaining,
+            fee,
+            swap_a,
+        )?;
+        amount_remaining = math::sub(amount_remaining, math::add(step.amount_in, step.fee_amount)?)?;
+        output = math::add(output, step.amount_out)?;
+        fee_total = math::add(fee_total, step.fee_amount)?;
+
+        // 手续费中属于协议的部分留在vault里，剩下的按当前区间的流动性分给LP
+        let protocol_fee_amount = math::mul_div(step.fee_amount, protocol_fee as u64, 10000)?;
+        protocol_fee_total = math::add(protocol_fee_total, protocol_fee_amount)?;
+        let growth = tick_math::fee_growth(math::sub(step.fee_amount, protocol_fee_amount)?, liquidity)?;
+        if swap_a {
+            fee_growth_global_a = fee_growth_global_a.wrapping_add(growth);
+        } else {
+            fee_growth_global_b = fee_growth_global_b.wrapping_add(growth);
+        }
+
+        sqrt_price = step.sqrt_price_next;
+        if sqrt_price == sqrt_price_target {
+            if initialized {
+                let liquidity_net = tick_array
+                    .tick_mut(next_tick, tick_spacing)?
+                    .cross(fee_growth_global_a, fee_growth_global_b);
+                // 价格向下穿过tick时反向应用liquidity_net
+                let liquidity_delta = if swap_a { -liquidity_net } else { liquidity_net };
+                liquidity = tick_math::add_liquidity_delta(liquidity, liquidity_delta)?;
+            }
+            if sqrt_price == MIN_SQRT_PRICE || sqrt_price == MAX_SQRT_PRICE {
+                return err!(TutorialError::InsufficientLiquidity);
+            }
+            tick = if swap_a { next_tick - 1 } else { next_tick };
+        } else {
+            tick = tick_math::tick_at_sqrt_price(sqrt_price)?;
+        }
+    }
+
+    pool.sqrt_price = sqrt_price;
+    pool.tick = tick;
+    pool.liquidity = liquidity;
+    pool.fee_growth_global_a = fee_growth_global_a;
+    pool.fee_growth_global_b = fee_growth_global_b;
+    if swap_a {
+        pool.protocol_fees_a = math::add(pool.protocol_fees_a, protocol_fee_total)?;
+    } else {
+        pool.protocol_fees_b = math::add(pool.protocol_fees_b, protocol_fee_total)?;
+    }
+    for tick_array in &tick_arrays {
+        tick_array.exit(ctx.program_id)?;
+    }
+
+    // 滑点保护针对用户实际收到的数量（输出代币同样可能有转账手续费）
+    let received = math::sub(output, get_transfer_fee(&mint_out, output)?)?;
+    if received < min_output_amount {
+        return err!(TutorialError::OutputTooSmall);
+    }
+
+    // swap
+    let authority_bump = ctx.bumps.pool_authority;
+    let authority_seeds = &[&pool_key.to_bytes(), AUTHORITY_SEED, &[authority_bump]];
+    let signer_seeds = &[&authority_seeds[..]];
+    let accounts = &ctx.accounts;
+    let (token_program_in, trader_account_in, pool_account_in, decimals_in) = if swap_a {
+        (&accounts.token_program_a, &accounts.trader_account_a, &accounts.pool_account_a, accounts.mint_a.decimals)
+    } else {
+        (&accounts.token_program_b, &accounts.trader_account_b, &accounts.pool_account_b, accounts.mint_b.decimals)
+    };
+    let (token_program_out, trader_account_out, pool_account_out, decimals_out) = if swap_a {
+        (&accounts.token_program_b, &accounts.trader_account_b, &accounts.pool_account_b, accounts.mint_b.decimals)
+    } else {
+        (&accounts.token_program_a, &accounts.trader_account_a, &accounts.pool_account_a, accounts.mint_a.decimals)
+    };
+
+    // 用户的输入代币 → 池子
+    token_interface::transfer_checked(
+        CpiContext::new(
+            token_program_in.to_account_info(),
+            TransferChecked {
+                from: trader_account_in.to_account_info(),
+                mint: mint_in,
+                to: pool_account_in.to_account_info(),
+                authority: accounts.trader.to_account_info(),
+            },
+        ),
+        input,
+        decimals_in,
+    )?;
+
+    // 池子的输出代币 → 用户
+    if output > 0 {
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                token_program_out.to_account_info(),
+                TransferChecked {
+                    from: pool_account_out.to_account_info(),
+                    mint: mint_out,
+                    to: trader_account_out.to_account_info(),
+                    authority: accounts.pool_authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            output,
+            decimals_out,
+        )?;
+    }
+
+    emit!(ClmmSwapped {
+        pool: pool_key,
+        trader: accounts.trader.key(),
+        swap_a,
+        input_amount: input,
+        output_amount: output,
+        fee_amount: fee_total,
+        protocol_fee_amount: protocol_fee_total,
+        sqrt_price,
+        tick,
+        liquidity,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SwapClmm<'info> {
+    #[account(
+        seeds = [
+            amm.id.as_ref()
+        ],
+        bump,
+        constraint = !amm.paused @ TutorialError::PoolPaused,
+    )]
+    pub amm: Box<Account<'info, Amm>>,
+
+    #[account(
+        mut,
+        seeds = [
+            pool.amm.as_ref(),
+            pool.mint_a.as_ref(),
+            pool.mint_b.as_ref(),
+            pool.tick_spacing.to_le_bytes().as_ref(),
+        ],
+        bump,
+        has_one = amm,
+        has_one = mint_a,
+        has_one = mint_b,
+        constraint = !pool.paused @ TutorialError::PoolPaused,
+        constraint = !pool.has_oracle || oracle.is_some() @ TutorialError::OracleRequired,
+    )]
+    pub pool: Box<Account<'info, ClmmPool>>,
+
+    /// Price oracle of the pool, required once it was initialized so that every price change is observed
+    #[account(
+        mut,
+        seeds = [
+            pool.key().as_ref(),
+            ORACLE_SEED,
+        ],
+        bump,
+    )]
+    pub oracle: Option<Box<Account<'info, Oracle>>>,
+
+    /// CHECK: Read only authority
+    #[account(
+        seeds = [
+            pool.key().as_ref(),
+            AUTHORITY_SEED,
+        ],
+        bump,
+    )]
+    pub pool_authority: AccountInfo<'info>,
+
+    /// The account doing the swap
+    pub trader: Signer<'info>,
+
+    #[account(
+        mint::token_program = token_program_a,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = pool_authority,
+        associated_token::token_program = token_program_a,
+    )]
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = pool_authority,
+        associated_token::token_program = token_program_b,
+    )]
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_a,
+        associated_token::authority = trader,
+        associated_token::token_program = token_program_a,
+    )]
+    pub trader_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_b,
+        associated_token::authority = trader,
+        associated_token::token_program = token_program_b,
+    )]
+    pub trader_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The account paying for all rents
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Solana ecosystem accounts
+    // 池子两边的代币可以各自属于Token或Token-2022程序
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
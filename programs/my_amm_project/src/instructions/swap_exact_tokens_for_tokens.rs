@@ -1,14 +1,16 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{self, Mint, Token, Transfer, TokenAccount},
+    token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
-use fixed::types::I64F64;
-
 use crate::{
-    constants::AUTHORITY_SEED,
+    constants::{AUTHORITY_SEED, ORACLE_SEED},
     errors::*,
-    state::{Amm, Pool},
+    curve,
+    events::Swapped,
+    math,
+    state::{Amm, Deadline, Oracle, Pool},
+    utils::{check_deadline, get_transfer_fee},
 };
 
 // 输入：input_amount_a
@@ -23,7 +25,10 @@ pub fn swap_exact_tokens_for_tokens(
     swap_a: bool,
     input_amount: u64,
     min_output_amount: u64,
+    deadline: Option<Deadline>,
 ) -> Result<()> {
+    check_deadline(deadline)?;
+
     // 防止存款人存入不属于自己的资产
     let input = if swap_a && input_amount > ctx.accounts.trader_account_a.amount {
         ctx.accounts.trader_account_a.amount
@@ -33,44 +38,59 @@ pub fn swap_exact_tokens_for_tokens(
         input_amount
     };
 
-    // 应用交易费，用于计算输出
-    let amm = &ctx.accounts.amm;
-    let taxed_input = input - input * amm.fee as u64 / 10000; // fee: u16 but input: u64
-
-    let pool_a = &ctx.accounts.pool_account_a;
-    let pool_b = &ctx.accounts.pool_account_b;
-
-    let output = if swap_a {
-        // old_y - ((old_x * old_y) / (old_x + taxed_input)) 
-        // = taxed_input * old_y / (old_x + taxed_input)
-        I64F64::from_num(taxed_input)
-        .checked_mul(I64F64::from_num(pool_b.amount))
-        .unwrap()
-        .checked_div(
-            I64F64::from_num(pool_a.amount)
-            .checked_add(I64F64::from_num(taxed_input))
-            .unwrap(),
-        )
-        .unwrap()
+    // Token-2022的transfer-fee mint在转账时会扣一部分，池子实际收到的是net_input
+    let (mint_in, mint_out) = if swap_a {
+        (ctx.accounts.mint_a.to_account_info(), ctx.accounts.mint_b.to_account_info())
     } else {
-        I64F64::from_num(taxed_input)
-        .checked_mul(I64F64::from_num(pool_a.amount))
-        .unwrap()
-        .checked_div(
-            I64F64::from_num(pool_b.amount)
-            .checked_add(I64F64::from_num(taxed_input))
-            .unwrap(),
-        )
-        .unwrap()
+        (ctx.accounts.mint_b.to_account_info(), ctx.accounts.mint_a.to_account_info())
+    };
+    let net_input = math::sub(input, get_transfer_fee(&mint_in, input)?)?;
+
+    // 使用当前生效的手续费（set_fee提出的新手续费要等延迟结束才生效）
+    let amm = &ctx.accounts.amm;
+    let now = Clock::get()?.unix_timestamp;
+    let fee = amm.current_fee(now);
+
+    let curve = ctx.accounts.pool.curve(now);
+    let (reserve_a, reserve_b) = ctx
+        .accounts
+        .pool
+        .reserves(ctx.accounts.pool_account_a.amount, ctx.accounts.pool_account_b.amount)?;
+
+    // 按上一条指令结束时保存的储备累计价格，池子有oracle时必须传入，记录一个观测点
+    ctx.accounts.pool.update_price_cumulative(now);
+    if let Some(oracle) = ctx.accounts.oracle.as_mut() {
+        oracle.write(ctx.accounts.pool.observation());
     }
-    .to_num::<u64>();
+    let (reserve_in, reserve_out) = if swap_a {
+        (reserve_a, reserve_b)
+    } else {
+        (reserve_b, reserve_a)
+    };
+
+    // 应用交易费，计算输出（公式见curve::swap_exact_input）
+    let quote = curve::swap_exact_input(curve.oriented(swap_a), net_input, reserve_in, reserve_out, fee, amm.protocol_fee)?;
+    let output = quote.output;
+
+    // 手续费中属于协议的部分，留在池子的vault里但不计入LP的储备
+    let protocol_fee_amount = quote.protocol_fee_amount;
 
-    if output < min_output_amount {
+    // 滑点保护针对用户实际收到的数量（输出代币同样可能有转账手续费）
+    let received = math::sub(output, get_transfer_fee(&mint_out, output)?)?;
+    if received < min_output_amount {
         return err!(TutorialError::OutputTooSmall);
     }
 
-    // 计算交易前的不变量
-    let invariant = pool_a.amount * pool_b.amount;
+    // 计算交易前的不变量（只算LP的储备，协议手续费不参与）
+    let invariant = curve::invariant(curve, reserve_a, reserve_b)?;
+
+    // 记录协议手续费
+    let pool = &mut ctx.accounts.pool;
+    if swap_a {
+        pool.protocol_fees_a = math::add(pool.protocol_fees_a, protocol_fee_amount)?;
+    } else {
+        pool.protocol_fees_b = math::add(pool.protocol_fees_b, protocol_fee_amount)?;
+    }
 
     // swap
     let authority_bump = ctx.bumps.pool_authority;
@@ -84,74 +104,101 @@ pub fn swap_exact_tokens_for_tokens(
     let signer_seeds = &[&authority_seeds[..]];
     if swap_a {
         // 用户的token_a → 池子
-        token::transfer(
+        token_interface::transfer_checked(
             CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
+                ctx.accounts.token_program_a.to_account_info(),
+                TransferChecked {
                     from: ctx.accounts.trader_account_a.to_account_info(),
+                    mint: ctx.accounts.mint_a.to_account_info(),
                     to: ctx.accounts.pool_account_a.to_account_info(),
                     authority: ctx.accounts.trader.to_account_info(),
                 }
             ),
             input,
+            ctx.accounts.mint_a.decimals,
         )?;
 
         // 池子的token_b → 用户 
-        token::transfer(
+        token_interface::transfer_checked(
             CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
+                ctx.accounts.token_program_b.to_account_info(),
+                TransferChecked {
                     from: ctx.accounts.pool_account_b.to_account_info(),
+                    mint: ctx.accounts.mint_b.to_account_info(),
                     to: ctx.accounts.trader_account_b.to_account_info(),
                     authority: ctx.accounts.pool_authority.to_account_info(),
                 },
                 signer_seeds,
             ),
             output,
+            ctx.accounts.mint_b.decimals,
         )?;
     } else {
         // 用户的token_b → 池子
-        token::transfer(
+        token_interface::transfer_checked(
             CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
+                ctx.accounts.token_program_b.to_account_info(),
+                TransferChecked {
                     from: ctx.accounts.trader_account_b.to_account_info(),
+                    mint: ctx.accounts.mint_b.to_account_info(),
                     to: ctx.accounts.pool_account_b.to_account_info(),
                     authority: ctx.accounts.trader.to_account_info(),
                 }
             ),
             input,
+            ctx.accounts.mint_b.decimals,
         )?;
 
         // 池子的token_a → 用户 
-        token::transfer(
+        token_interface::transfer_checked(
             CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
+                ctx.accounts.token_program_a.to_account_info(),
+                TransferChecked {
                     from: ctx.accounts.pool_account_a.to_account_info(),
+                    mint: ctx.accounts.mint_a.to_account_info(),
                     to: ctx.accounts.trader_account_a.to_account_info(),
                     authority: ctx.accounts.pool_authority.to_account_info(),
                 },
                 signer_seeds,
             ),
             output,
+            ctx.accounts.mint_a.decimals,
         )?;
     }
 
     msg!(
-        "Traded {} tokens ({} after fees) for {}",
+        "Traded {} tokens ({} after fees) for {} ({} received)",
         input,
-        taxed_input,
-        output
+        math::sub(net_input, quote.fee_amount)?,
+        output,
+        received
     );
 
     // 验证不变式仍然成立
     ctx.accounts.pool_account_a.reload()?;
     ctx.accounts.pool_account_b.reload()?;
-    if invariant > ctx.accounts.pool_account_a.amount * ctx.accounts.pool_account_b.amount {
+    let (reserve_a, reserve_b) = ctx
+        .accounts
+        .pool
+        .reserves(ctx.accounts.pool_account_a.amount, ctx.accounts.pool_account_b.amount)?;
+    if invariant > curve::invariant(curve, reserve_a, reserve_b)? {
         return err!(TutorialError::InvariantViolated);
     }
 
+    // 保存这条指令结束时的储备，之后的价格按它累计
+    ctx.accounts.pool.sync_reserves(reserve_a, reserve_b);
+    emit!(Swapped {
+        pool: ctx.accounts.pool.key(),
+        trader: ctx.accounts.trader.key(),
+        swap_a,
+        input_amount: input,
+        output_amount: output,
+        fee_amount: quote.fee_amount,
+        protocol_fee_amount,
+        reserve_a,
+        reserve_b,
+    });
+
     Ok(())
 }
 
@@ -162,10 +209,12 @@ pub struct SwapExactTokensForTokens<'info> {
             amm.id.as_ref()
         ],
         bump,
+        constraint = !amm.paused @ TutorialError::PoolPaused,
     )]
     pub amm: Box<Account<'info, Amm>>,
 
     #[account(
+        mut,
         seeds = [
             pool.amm.as_ref(),
             pool.mint_a.key().as_ref(),
@@ -175,9 +224,23 @@ pub struct SwapExactTokensForTokens<'info> {
         has_one = amm,
         has_one = mint_a,
         has_one = mint_b,
+        constraint = !pool.paused @ TutorialError::PoolPaused,
+        constraint = !pool.flash_loan_active @ TutorialError::FlashLoanActive,
+        constraint = !pool.has_oracle || oracle.is_some() @ TutorialError::OracleRequired,
     )]
     pub pool: Box<Account<'info, Pool>>,
 
+    /// Price oracle of the pool, required once it was initialized so that every price change is observed
+    #[account(
+        mut,
+        seeds = [
+            pool.key().as_ref(),
+            ORACLE_SEED,
+        ],
+        bump,
+    )]
+    pub oracle: Option<Box<Account<'info, Oracle>>>,
+
     #[account(
         seeds = [
             pool.amm.as_ref(),
@@ -193,46 +256,58 @@ pub struct SwapExactTokensForTokens<'info> {
     /// The account doing the swap
     pub trader: Signer<'info>,
 
-    pub mint_a: Box<Account<'info, Mint>>,
+    #[account(
+        mint::token_program = token_program_a,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
 
-    pub mint_b: Box<Account<'info, Mint>>,
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
 
     #[account(
         mut,
         associated_token::mint = mint_a,
         associated_token::authority = pool_authority,
+        associated_token::token_program = token_program_a,
     )]
-    pub pool_account_a: Box<Account<'info, TokenAccount>>,
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
         associated_token::mint = mint_b,
         associated_token::authority = pool_authority,
+        associated_token::token_program = token_program_b,
     )]
-    pub pool_account_b: Box<Account<'info, TokenAccount>>,
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         init_if_needed,
         payer = payer,
         associated_token::mint = mint_a,
         associated_token::authority = trader,
+        associated_token::token_program = token_program_a,
     )]
-    pub trader_account_a: Box<Account<'info, TokenAccount>>,
+    pub trader_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         init_if_needed,
         payer = payer,
         associated_token::mint = mint_b,
         associated_token::authority = trader,
+        associated_token::token_program = token_program_b,
     )]
-    pub trader_account_b: Box<Account<'info, TokenAccount>>,
+    pub trader_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
 
     /// The account paying for all rents
     #[account(mut)]
     pub payer: Signer<'info>,
 
     /// Solana ecosystem accounts
-    pub token_program: Program<'info, Token>,
+    // 池子两边的代币可以各自属于Token或Token-2022程序
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
\ No newline at end of file
@@ -0,0 +1,371 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+use crate::{
+    constants::{AUTHORITY_SEED, MAX_ROUTE_LEGS, ORACLE_SEED},
+    errors::*,
+    curve::{self, Curve},
+    events::Swapped,
+    math,
+    state::{Amm, Deadline, Oracle, Pool},
+    utils::{check_deadline, get_transfer_fee},
+};
+
+// 每一跳在remaining_accounts里占用的账户数量：
+// [pool, pool_authority, pool_account_in, pool_account_out, mint_out, token_program_out, oracle]
+// 输出代币的mint和token程序用于transfer_checked（输入代币的在上一跳或SwapRoute里）
+// oracle是池子的价格oracle，池子没有oracle时传本程序的ID（和Anchor的可选账户一样）；池子有oracle时必须传入
+const ROUTE_LEG_ACCOUNTS: usize = 7;
+
+// 路由中的一跳
+struct RouteLeg<'info> {
+    pool: Account<'info, Pool>,
+    oracle: Option<Account<'info, Oracle>>,
+    pool_authority: &'info AccountInfo<'info>,
+    pool_account_in: &'info AccountInfo<'info>,
+    pool_account_out: &'info AccountInfo<'info>,
+    mint_out: &'info AccountInfo<'info>,
+    token_program_out: &'info AccountInfo<'info>,
+    decimals_out: u8,
+    authority_bump: u8,
+    curve: Curve,
+    swap_a: bool,
+    input: u64,
+    output: u64,
+    fee_amount: u64,
+    protocol_fee_amount: u64,
+    invariant: u128,
+}
+
+// 多跳交换：例如 A → B → C
+// 每一跳都按swap_exact_tokens_for_tokens的公式计算，上一跳的输出就是下一跳的输入
+// 中间代币直接从上一个池子的vault转到下一个池子的vault，不经过用户的ATA
+// 滑点只在最后一跳检查一次（min_output_amount）
+pub fn swap_route<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SwapRoute<'info>>,
+    input_amount: u64,
+    min_output_amount: u64,
+    deadline: Option<Deadline>,
+) -> Result<()> {
+    check_deadline(deadline)?;
+
+    let remaining_accounts = ctx.remaining_accounts;
+    let leg_count = remaining_accounts.len() / ROUTE_LEG_ACCOUNTS;
+    if leg_count == 0
+        || leg_count * ROUTE_LEG_ACCOUNTS != remaining_accounts.len()
+        || leg_count > MAX_ROUTE_LEGS as usize
+    {
+        return err!(TutorialError::InvalidRoute);
+    }
+
+    // 防止存款人存入不属于自己的资产
+    let input = if input_amount > ctx.accounts.trader_account_in.amount {
+        ctx.accounts.trader_account_in.amount
+    } else {
+        input_amount
+    };
+
+    let amm = &ctx.accounts.amm;
+    let now = Clock::get()?.unix_timestamp;
+    let fee = amm.current_fee(now);
+
+    let mut legs: Vec<RouteLeg<'info>> = Vec::new();
+    let mut current_mint = ctx.accounts.mint_in.key();
+    let mut current_token_program = ctx.accounts.token_program_in.key();
+    let mut current_mint_info = ctx.accounts.mint_in.to_account_info();
+    let mut amount = input;
+    for accounts in remaining_accounts.chunks(ROUTE_LEG_ACCOUNTS) {
+        // 池子账户必须属于本程序，并且在同一个AMM下
+        let mut pool = Account::<Pool>::try_from(&accounts[0])?;
+        if !accounts[0].is_writable || pool.amm != amm.key() {
+            return err!(TutorialError::InvalidRoute);
+        }
+        if pool.paused {
+            return err!(TutorialError::PoolPaused);
+        }
+        if pool.flash_loan_active {
+            return err!(TutorialError::FlashLoanActive);
+        }
+        // 同一个池子出现两次时，后一跳读到的储备不对
+        if legs.iter().any(|leg| leg.pool.key() == pool.key()) {
+            return err!(TutorialError::InvalidRoute);
+        }
+
+        let (pool_authority, authority_bump) = Pubkey::find_program_address(
+            &[
+                pool.amm.as_ref(),
+                pool.mint_a.as_ref(),
+                pool.mint_b.as_ref(),
+                AUTHORITY_SEED,
+            ],
+            ctx.program_id,
+        );
+        if accounts[1].key() != pool_authority {
+            return err!(TutorialError::InvalidRoute);
+        }
+
+        // 根据上一跳的输出代币确定这一跳的方向
+        let swap_a = if current_mint == pool.mint_a {
+            true
+        } else if current_mint == pool.mint_b {
+            false
+        } else {
+            return err!(TutorialError::InvalidMint);
+        };
+        let mint_out = if swap_a { pool.mint_b } else { pool.mint_a };
+
+        // 输出代币的mint必须对得上，token程序必须是这个mint的owner（Token或Token-2022）
+        // 否则别人可以传一个假的程序，拿着pool_authority的签名去做任意操作
+        let decimals_out = InterfaceAccount::<Mint>::try_from(&accounts[4])?.decimals;
+        if accounts[4].key() != mint_out || accounts[5].key() != *accounts[4].owner {
+            return err!(TutorialError::InvalidRoute);
+        }
+        let token_program_out = accounts[5].key();
+
+        // vault必须是pool_authority的ATA，否则别人可以传一个假的vault来操纵价格
+        if accounts[2].key()
+            != get_associated_token_address_with_program_id(
+                &pool_authority,
+                &current_mint,
+                &current_token_program,
+            )
+            || accounts[3].key()
+                != get_associated_token_address_with_program_id(
+                    &pool_authority,
+                    &mint_out,
+                    &token_program_out,
+                )
+        {
+            return err!(TutorialError::InvalidRoute);
+        }
+        let vault_in = InterfaceAccount::<TokenAccount>::try_from(&accounts[2])?.amount;
+        let vault_out = InterfaceAccount::<TokenAccount>::try_from(&accounts[3])?.amount;
+
+        let (reserve_a, reserve_b) = if swap_a {
+            pool.reserves(vault_in, vault_out)?
+        } else {
+            pool.reserves(vault_out, vault_in)?
+        };
+        let (reserve_in, reserve_out) = if swap_a {
+            (reserve_a, reserve_b)
+        } else {
+            (reserve_b, reserve_a)
+        };
+
+        // 按上一条指令结束时保存的储备累计价格，池子有oracle时必须传入，记录一个观测点
+        pool.update_price_cumulative(now);
+        let oracle = if accounts[6].key() == crate::ID {
+            if pool.has_oracle {
+                return err!(TutorialError::OracleRequired);
+            }
+            None
+        } else {
+            let (oracle_key, _) =
+                Pubkey::find_program_address(&[pool.key().as_ref(), ORACLE_SEED], ctx.program_id);
+            if accounts[6].key() != oracle_key || !accounts[6].is_writable {
+                return err!(TutorialError::InvalidRoute);
+            }
+            let mut oracle = Account::<Oracle>::try_from(&accounts[6])?;
+            oracle.write(pool.observation());
+            Some(oracle)
+        };
+
+        // StableSwap池子的amp可能正在调整，按当前时间插值
+        let curve = pool.curve(now);
+
+        // 池子实际收到的数量要扣掉Token-2022的转账手续费
+        let net_amount = math::sub(amount, get_transfer_fee(&current_mint_info, amount)?)?;
+
+        // 应用交易费，计算输出（和swap_exact_tokens_for_tokens一样用curve::swap_exact_input）
+        let quote = curve::swap_exact_input(curve.oriented(swap_a), net_amount, reserve_in, reserve_out, fee, amm.protocol_fee)?;
+        let output = quote.output;
+
+        // 记录协议手续费
+        if swap_a {
+            pool.protocol_fees_a = math::add(pool.protocol_fees_a, quote.protocol_fee_amount)?;
+        } else {
+            pool.protocol_fees_b = math::add(pool.protocol_fees_b, quote.protocol_fee_amount)?;
+        }
+
+        let invariant = curve::invariant(curve, reserve_a, reserve_b)?;
+        legs.push(RouteLeg {
+            pool,
+            oracle,
+            pool_authority: &accounts[1],
+            pool_account_in: &accounts[2],
+            pool_account_out: &accounts[3],
+            mint_out: &accounts[4],
+            token_program_out: &accounts[5],
+            decimals_out,
+            authority_bump,
+            curve,
+            swap_a,
+            input: amount,
+            output,
+            fee_amount: quote.fee_amount,
+            protocol_fee_amount: quote.protocol_fee_amount,
+            invariant,
+        });
+
+        current_mint = mint_out;
+        current_token_program = token_program_out;
+        current_mint_info = accounts[4].clone();
+        amount = output;
+    }
+
+    // 最后一跳的输出必须是用户想要的代币
+    if current_mint != ctx.accounts.mint_out.key() {
+        return err!(TutorialError::InvalidRoute);
+    }
+
+    // 滑点保护针对用户实际收到的数量
+    let received = math::sub(amount, get_transfer_fee(&current_mint_info, amount)?)?;
+    if received < min_output_amount {
+        return err!(TutorialError::OutputTooSmall);
+    }
+
+    // 用户的输入代币 → 第一个池子
+    token_interface::transfer_checked(
+        CpiContext::new(
+            ctx.accounts.token_program_in.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.trader_account_in.to_account_info(),
+                mint: ctx.accounts.mint_in.to_account_info(),
+                to: legs[0].pool_account_in.to_account_info(),
+                authority: ctx.accounts.trader.to_account_info(),
+            },
+        ),
+        input,
+        ctx.accounts.mint_in.decimals,
+    )?;
+
+    // 每个池子的输出 → 下一个池子（最后一跳 → 用户）
+    for (i, leg) in legs.iter().enumerate() {
+        let to = match legs.get(i + 1) {
+            Some(next) => next.pool_account_in.to_account_info(),
+            None => ctx.accounts.trader_account_out.to_account_info(),
+        };
+
+        let authority_seeds = &[
+            &leg.pool.amm.to_bytes(),
+            &leg.pool.mint_a.to_bytes(),
+            &leg.pool.mint_b.to_bytes(),
+            AUTHORITY_SEED,
+            &[leg.authority_bump],
+        ];
+        let signer_seeds = &[&authority_seeds[..]];
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                leg.token_program_out.to_account_info(),
+                TransferChecked {
+                    from: leg.pool_account_out.to_account_info(),
+                    mint: leg.mint_out.to_account_info(),
+                    to,
+                    authority: leg.pool_authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            leg.output,
+            leg.decimals_out,
+        )?;
+
+        msg!(
+            "Leg {}: traded {} tokens for {}",
+            i,
+            leg.input,
+            leg.output
+        );
+    }
+
+    // 验证每个池子的不变式仍然成立，并保存协议手续费、储备和oracle
+    for leg in legs.iter_mut() {
+        let vault_in = InterfaceAccount::<TokenAccount>::try_from(leg.pool_account_in)?.amount;
+        let vault_out = InterfaceAccount::<TokenAccount>::try_from(leg.pool_account_out)?.amount;
+        let (reserve_a, reserve_b) = if leg.swap_a {
+            leg.pool.reserves(vault_in, vault_out)?
+        } else {
+            leg.pool.reserves(vault_out, vault_in)?
+        };
+        if leg.invariant > curve::invariant(leg.curve, reserve_a, reserve_b)? {
+            return err!(TutorialError::InvariantViolated);
+        }
+        leg.pool.sync_reserves(reserve_a, reserve_b);
+
+        emit!(Swapped {
+            pool: leg.pool.key(),
+            trader: ctx.accounts.trader.key(),
+            swap_a: leg.swap_a,
+            input_amount: leg.input,
+            output_amount: leg.output,
+            fee_amount: leg.fee_amount,
+            protocol_fee_amount: leg.protocol_fee_amount,
+            reserve_a,
+            reserve_b,
+        });
+
+        leg.pool.exit(ctx.program_id)?;
+        if let Some(oracle) = &leg.oracle {
+            oracle.exit(ctx.program_id)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SwapRoute<'info> {
+    #[account(
+        seeds = [
+            amm.id.as_ref()
+        ],
+        bump,
+        constraint = !amm.paused @ TutorialError::PoolPaused,
+    )]
+    pub amm: Box<Account<'info, Amm>>,
+
+    /// The account doing the swap
+    pub trader: Signer<'info>,
+
+    /// Mint of the token the trader pays with
+    #[account(
+        mint::token_program = token_program_in,
+    )]
+    pub mint_in: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Mint of the token the trader receives
+    #[account(
+        mint::token_program = token_program_out,
+    )]
+    pub mint_out: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_in,
+        associated_token::authority = trader,
+        associated_token::token_program = token_program_in,
+    )]
+    pub trader_account_in: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_out,
+        associated_token::authority = trader,
+        associated_token::token_program = token_program_out,
+    )]
+    pub trader_account_out: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The account paying for all rents
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Solana ecosystem accounts
+    // 输入、输出代币可以各自属于Token或Token-2022程序
+    pub token_program_in: Interface<'info, TokenInterface>,
+    pub token_program_out: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
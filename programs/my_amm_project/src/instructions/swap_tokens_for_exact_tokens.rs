@@ -0,0 +1,308 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+use crate::{
+    constants::{AUTHORITY_SEED, ORACLE_SEED},
+    errors::*,
+    curve,
+    events::Swapped,
+    math,
+    state::{Amm, Deadline, Oracle, Pool},
+    utils::{check_deadline, get_transfer_inverse_fee},
+};
+
+// 输入：output_amount_b
+// 计算：input_amount_a = ?
+
+// 公式（swap_exact_tokens_for_tokens的反推）：
+// new_y = old_y - output_amount_b
+// new_x = k / new_y
+// taxed_input = new_x - old_x = output_amount_b * old_x / (old_y - output_amount_b)
+// input_amount_a = taxed_input / (1 - fee)
+// 两步都向上取整，保证池子收到的不少于理论值
+// 如果代币有Token-2022的转账手续费，output_amount_b是用户实际收到的数量，
+// 池子要多转出手续费；用户也要多转入手续费，池子才能收到input_amount_a
+pub fn swap_tokens_for_exact_tokens(
+    ctx: Context<SwapTokensForExactTokens>,
+    swap_a: bool,
+    output_amount: u64,
+    max_input_amount: u64,
+    deadline: Option<Deadline>,
+) -> Result<()> {
+    check_deadline(deadline)?;
+
+    let amm = &ctx.accounts.amm;
+    let now = Clock::get()?.unix_timestamp;
+    let fee = amm.current_fee(now);
+
+    let curve = ctx.accounts.pool.curve(now);
+    let (reserve_a, reserve_b) = ctx
+        .accounts
+        .pool
+        .reserves(ctx.accounts.pool_account_a.amount, ctx.accounts.pool_account_b.amount)?;
+
+    // 按上一条指令结束时保存的储备累计价格，池子有oracle时必须传入，记录一个观测点
+    ctx.accounts.pool.update_price_cumulative(now);
+    if let Some(oracle) = ctx.accounts.oracle.as_mut() {
+        oracle.write(ctx.accounts.pool.observation());
+    }
+    let (reserve_in, reserve_out) = if swap_a {
+        (reserve_a, reserve_b)
+    } else {
+        (reserve_b, reserve_a)
+    };
+    let (mint_in, mint_out) = if swap_a {
+        (ctx.accounts.mint_a.to_account_info(), ctx.accounts.mint_b.to_account_info())
+    } else {
+        (ctx.accounts.mint_b.to_account_info(), ctx.accounts.mint_a.to_account_info())
+    };
+
+    // 池子要转出的数量（用户收到output_amount，转账手续费另算）
+    let output = math::add(output_amount, get_transfer_inverse_fee(&mint_out, output_amount)?)?;
+
+    // 池子需要收到的数量（公式见curve::swap_exact_output，储备不够时返回InsufficientLiquidity）
+    let quote = curve::swap_exact_output(curve.oriented(swap_a), output, reserve_in, reserve_out, fee, amm.protocol_fee)?;
+    let net_input = quote.input;
+
+    // 用户要转出的数量，池子扣掉转账手续费后正好收到net_input
+    let input = math::add(net_input, get_transfer_inverse_fee(&mint_in, net_input)?)?;
+
+    if input > max_input_amount {
+        return err!(TutorialError::InputTooLarge);
+    }
+
+    // 手续费中属于协议的部分，留在池子的vault里但不计入LP的储备
+    let protocol_fee_amount = quote.protocol_fee_amount;
+
+    // 计算交易前的不变量（只算LP的储备，协议手续费不参与）
+    let invariant = curve::invariant(curve, reserve_a, reserve_b)?;
+
+    // 记录协议手续费
+    let pool = &mut ctx.accounts.pool;
+    if swap_a {
+        pool.protocol_fees_a = math::add(pool.protocol_fees_a, protocol_fee_amount)?;
+    } else {
+        pool.protocol_fees_b = math::add(pool.protocol_fees_b, protocol_fee_amount)?;
+    }
+
+    // swap
+    let authority_bump = ctx.bumps.pool_authority;
+    let authority_seeds = &[
+        &ctx.accounts.pool.amm.to_bytes(),
+        &ctx.accounts.pool.mint_a.to_bytes(),
+        &ctx.accounts.pool.mint_b.to_bytes(),
+        AUTHORITY_SEED,
+        &[authority_bump],
+    ];
+    let signer_seeds = &[&authority_seeds[..]];
+    if swap_a {
+        // 用户的token_a → 池子
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program_a.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.trader_account_a.to_account_info(),
+                    mint: ctx.accounts.mint_a.to_account_info(),
+                    to: ctx.accounts.pool_account_a.to_account_info(),
+                    authority: ctx.accounts.trader.to_account_info(),
+                }
+            ),
+            input,
+            ctx.accounts.mint_a.decimals,
+        )?;
+
+        // 池子的token_b → 用户 
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program_b.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.pool_account_b.to_account_info(),
+                    mint: ctx.accounts.mint_b.to_account_info(),
+                    to: ctx.accounts.trader_account_b.to_account_info(),
+                    authority: ctx.accounts.pool_authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            output,
+            ctx.accounts.mint_b.decimals,
+        )?;
+    } else {
+        // 用户的token_b → 池子
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program_b.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.trader_account_b.to_account_info(),
+                    mint: ctx.accounts.mint_b.to_account_info(),
+                    to: ctx.accounts.pool_account_b.to_account_info(),
+                    authority: ctx.accounts.trader.to_account_info(),
+                }
+            ),
+            input,
+            ctx.accounts.mint_b.decimals,
+        )?;
+
+        // 池子的token_a → 用户 
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program_a.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.pool_account_a.to_account_info(),
+                    mint: ctx.accounts.mint_a.to_account_info(),
+                    to: ctx.accounts.trader_account_a.to_account_info(),
+                    authority: ctx.accounts.pool_authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            output,
+            ctx.accounts.mint_a.decimals,
+        )?;
+    }
+
+    msg!(
+        "Traded {} tokens ({} after fees) for {} ({} received)",
+        input,
+        math::sub(net_input, quote.fee_amount)?,
+        output,
+        output_amount
+    );
+
+    // 验证不变式仍然成立
+    ctx.accounts.pool_account_a.reload()?;
+    ctx.accounts.pool_account_b.reload()?;
+    let (reserve_a, reserve_b) = ctx
+        .accounts
+        .pool
+        .reserves(ctx.accounts.pool_account_a.amount, ctx.accounts.pool_account_b.amount)?;
+    if invariant > curve::invariant(curve, reserve_a, reserve_b)? {
+        return err!(TutorialError::InvariantViolated);
+    }
+
+    // 保存这条指令结束时的储备，之后的价格按它累计
+    ctx.accounts.pool.sync_reserves(reserve_a, reserve_b);
+    emit!(Swapped {
+        pool: ctx.accounts.pool.key(),
+        trader: ctx.accounts.trader.key(),
+        swap_a,
+        input_amount: input,
+        output_amount: output,
+        fee_amount: quote.fee_amount,
+        protocol_fee_amount,
+        reserve_a,
+        reserve_b,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SwapTokensForExactTokens<'info> {
+    #[account(
+        seeds = [
+            amm.id.as_ref()
+        ],
+        bump,
+        constraint = !amm.paused @ TutorialError::PoolPaused,
+    )]
+    pub amm: Box<Account<'info, Amm>>,
+
+    #[account(
+        mut,
+        seeds = [
+            pool.amm.as_ref(),
+            pool.mint_a.key().as_ref(),
+            pool.mint_b.key().as_ref(),
+        ],
+        bump,
+        has_one = amm,
+        has_one = mint_a,
+        has_one = mint_b,
+        constraint = !pool.paused @ TutorialError::PoolPaused,
+        constraint = !pool.flash_loan_active @ TutorialError::FlashLoanActive,
+        constraint = !pool.has_oracle || oracle.is_some() @ TutorialError::OracleRequired,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+
+    /// Price oracle of the pool, required once it was initialized so that every price change is observed
+    #[account(
+        mut,
+        seeds = [
+            pool.key().as_ref(),
+            ORACLE_SEED,
+        ],
+        bump,
+    )]
+    pub oracle: Option<Box<Account<'info, Oracle>>>,
+
+    #[account(
+        seeds = [
+            pool.amm.as_ref(),
+            mint_a.key().as_ref(),
+            mint_b.key().as_ref(),
+            AUTHORITY_SEED,
+        ],
+        bump,
+    )]
+    /// CHECK: Read only authority
+    pub pool_authority: AccountInfo<'info>,
+
+    /// The account doing the swap
+    pub trader: Signer<'info>,
+
+    #[account(
+        mint::token_program = token_program_a,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = pool_authority,
+        associated_token::token_program = token_program_a,
+    )]
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = pool_authority,
+        associated_token::token_program = token_program_b,
+    )]
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_a,
+        associated_token::authority = trader,
+        associated_token::token_program = token_program_a,
+    )]
+    pub trader_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_b,
+        associated_token::authority = trader,
+        associated_token::token_program = token_program_b,
+    )]
+    pub trader_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The account paying for all rents
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Solana ecosystem accounts
+    // 池子两边的代币可以各自属于Token或Token-2022程序
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
\ No newline at end of file
@@ -0,0 +1,145 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::Token,
+    token_interface::{self, Burn, Mint, TokenAccount, TransferChecked},
+};
+
+use crate::{
+    constants::{AUTHORITY_SEED, LIQUIDITY_SEED, MINIMUM_LIQUIDITY},
+    errors::TutorialError,
+    curve,
+    events::BasketLiquidityRemoved,
+    math,
+    state::{BasketPool, Deadline},
+    utils::{check_deadline, get_transfer_fee, load_basket_tokens},
+};
+
+// 篮子池子取款：销毁amount个LP，按占比取回每种代币
+// min_amounts按pool.mints的顺序给出每种代币最少收到的数量
+// 每种代币的[mint, vault, depositor_account, token_program]通过remaining_accounts传入
+pub fn withdraw_basket_liquidity<'info>(
+    ctx: Context<'_, '_, 'info, 'info, WithdrawBasketLiquidity<'info>>,
+    amount: u64,
+    min_amounts: Vec<u64>,
+    deadline: Option<Deadline>,
+) -> Result<()> {
+    check_deadline(deadline)?;
+
+    let pool = &ctx.accounts.pool;
+    let tokens = load_basket_tokens(
+        pool,
+        &ctx.accounts.pool_authority.key(),
+        &ctx.accounts.depositor.key(),
+        ctx.remaining_accounts,
+    )?;
+    if min_amounts.len() != tokens.len() {
+        return err!(TutorialError::InvalidTokenCount);
+    }
+
+    // 被锁定的MINIMUM_LIQUIDITY也算在总量里
+    let reserves: Vec<u64> = tokens.iter().map(|token| token.reserve).collect();
+    let total_liquidity = math::add(ctx.accounts.mint_liquidity.supply, MINIMUM_LIQUIDITY)?;
+    let amounts = curve::basket_withdraw_amounts(amount, &reserves, total_liquidity)?;
+
+    // 滑点保护：比较的是用户实际收到的数量（扣掉Token-2022的转账手续费）
+    for ((token, &amount), &min_amount) in tokens.iter().zip(&amounts).zip(&min_amounts) {
+        if math::sub(amount, get_transfer_fee(token.mint, amount)?)? < min_amount {
+            return err!(TutorialError::WithdrawOutputTooSmall);
+        }
+    }
+
+    // 生成PDA签名
+    let authority_bump = ctx.bumps.pool_authority;
+    let authority_seeds = &[
+        &pool.amm.to_bytes(),
+        &pool.id.to_bytes(),
+        AUTHORITY_SEED,
+        &[authority_bump],
+    ];
+    let signer_seeds = &[&authority_seeds[..]];
+
+    // 将每种代币转移给用户
+    for (token, &amount) in tokens.iter().zip(&amounts) {
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                token.token_program.clone(),
+                TransferChecked {
+                    from: token.vault.clone(),
+                    mint: token.mint.clone(),
+                    to: token.user_account.clone(),
+                    authority: ctx.accounts.pool_authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+            token.decimals,
+        )?;
+    }
+
+    token_interface::burn(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.mint_liquidity.to_account_info(),
+                from: ctx.accounts.depositor_account_liquidity.to_account_info(),
+                authority: ctx.accounts.depositor.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    emit!(BasketLiquidityRemoved {
+        pool: pool.key(),
+        depositor: ctx.accounts.depositor.key(),
+        amounts,
+        liquidity: amount,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct WithdrawBasketLiquidity<'info> {
+    // 取款不检查暂停，和withdraw_liquidity一样
+    #[account(
+        seeds = [
+            pool.amm.as_ref(),
+            pool.id.as_ref(),
+        ],
+        bump,
+    )]
+    pub pool: Box<Account<'info, BasketPool>>,
+
+    /// CHECK: Read only authority
+    #[account(
+        seeds = [
+            pool.amm.as_ref(),
+            pool.id.as_ref(),
+            AUTHORITY_SEED,
+        ],
+        bump,
+    )]
+    pub pool_authority: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            pool.amm.as_ref(),
+            pool.id.as_ref(),
+            LIQUIDITY_SEED,
+        ],
+        bump,
+    )]
+    pub mint_liquidity: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_liquidity,
+        associated_token::authority = depositor,
+    )]
+    pub depositor_account_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub depositor: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
@@ -1,29 +1,47 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{self, Burn, Mint, Token, TokenAccount, Transfer},
+    token::Token,
+    token_interface::{self, Burn, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
-use fixed::types::I64F64;
-
 use crate::{
-    constants::{AUTHORITY_SEED, LIQUIDITY_SEED, MINIMUM_LIQUIDITY},
-    state::{Pool},
+    constants::{AUTHORITY_SEED, LIQUIDITY_SEED, MINIMUM_LIQUIDITY, ORACLE_SEED},
+    errors::TutorialError,
+    curve,
+    events::LiquidityRemoved,
+    math,
+    state::{Deadline, Oracle, Pool},
+    utils::{check_deadline, get_transfer_fee},
 };
 
 pub fn withdraw_liquidity(
     ctx: Context<WithdrawLiquidity>,
     amount: u64,
+    min_amount_a: u64,
+    min_amount_b: u64,
+    deadline: Option<Deadline>,
 ) -> Result<()> {
+    check_deadline(deadline)?;
+
     // 计算从池子中提取的代币 A / B 的数量
-    let amount_a = I64F64::from_num(amount)
-    .checked_mul(I64F64::from_num(ctx.accounts.pool_account_a.amount))
-    .unwrap()
-    .checked_div(I64F64::from_num(ctx.accounts.mint_liquidity.supply + MINIMUM_LIQUIDITY))
-    .unwrap()
-    .floor()
-    .to_num::<u64>();
-
-    // floor() = 向下取整，总是舍弃小数部分，取最接近的较小整数
+    // 储备要扣除vault里属于协议的手续费，LP不能取走这部分
+    let (reserve_a, reserve_b) = ctx
+        .accounts
+        .pool
+        .reserves(ctx.accounts.pool_account_a.amount, ctx.accounts.pool_account_b.amount)?;
+    let now = Clock::get()?.unix_timestamp;
+
+    // 按上一条指令结束时保存的储备累计价格，池子有oracle时必须传入，记录一个观测点
+    ctx.accounts.pool.update_price_cumulative(now);
+    if let Some(oracle) = ctx.accounts.oracle.as_mut() {
+        oracle.write(ctx.accounts.pool.observation());
+    }
+
+    // 被锁定的MINIMUM_LIQUIDITY也算在总量里
+    let total_liquidity = math::add(ctx.accounts.mint_liquidity.supply, MINIMUM_LIQUIDITY)?;
+    let (amount_a, amount_b) = curve::withdraw_amounts(amount, reserve_a, reserve_b, total_liquidity)?;
+
+    // 向下取整，总是舍弃小数部分，取最接近的较小整数
     // 123.1   → 123
     // 123.7   → 123  (不是124！)
     // - 用户得到略少于理论值的代币
@@ -31,13 +49,13 @@ pub fn withdraw_liquidity(
     // - 防止池子被意外清空
     // - 保护其他LP提供者的利益
 
-    let amount_b = I64F64::from_num(amount)
-    .checked_mul(I64F64::from_num(ctx.accounts.pool_account_b.amount))
-    .unwrap()
-    .checked_div(I64F64::from_num(ctx.accounts.mint_liquidity.supply + MINIMUM_LIQUIDITY))
-    .unwrap()
-    .floor()
-    .to_num::<u64>();
+    // 滑点保护：池子储备在交易打包前可能被改变（例如三明治攻击）
+    // 比较的是用户实际收到的数量（扣掉Token-2022的转账手续费）
+    let received_a = math::sub(amount_a, get_transfer_fee(&ctx.accounts.mint_a.to_account_info(), amount_a)?)?;
+    let received_b = math::sub(amount_b, get_transfer_fee(&ctx.accounts.mint_b.to_account_info(), amount_b)?)?;
+    if received_a < min_amount_a || received_b < min_amount_b {
+        return err!(TutorialError::WithdrawOutputTooSmall);
+    }
 
     // 生成PDA签名
     let authority_bump = ctx.bumps.pool_authority;
@@ -51,38 +69,42 @@ pub fn withdraw_liquidity(
     let signer_seeds = &[&authority_seeds[..]];
 
     // 转移Token A 给用户
-    token::transfer(
+    token_interface::transfer_checked(
         CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
+            ctx.accounts.token_program_a.to_account_info(),
+            TransferChecked {
                 from: ctx.accounts.pool_account_a.to_account_info(),
+                mint: ctx.accounts.mint_a.to_account_info(),
                 to: ctx.accounts.depositor_account_a.to_account_info(),
                 authority: ctx.accounts.pool_authority.to_account_info(),
             },
             signer_seeds,
         ),
         amount_a,
+        ctx.accounts.mint_a.decimals,
     )?;
 
     // 转移Token B 给用户
-    token::transfer(
+    token_interface::transfer_checked(
         CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
+            ctx.accounts.token_program_b.to_account_info(),
+            TransferChecked {
                 from: ctx.accounts.pool_account_b.to_account_info(),
+                mint: ctx.accounts.mint_b.to_account_info(),
                 to: ctx.accounts.depositor_account_b.to_account_info(),
                 authority: ctx.accounts.pool_authority.to_account_info(),
             },
             signer_seeds,
         ),
         amount_b,
+        ctx.accounts.mint_b.decimals,
     )?;
 
-    // token::burn() 会自动减少总供应量
+    // token_interface::burn() 会自动减少总供应量
     // // 执行后：
     // // - 用户LP账户余额 -= lp_amount
     // // - mint_liquidity.supply -= lp_amount  ← 自动减少！
-    token::burn(
+    token_interface::burn(
         CpiContext::new(
             ctx.accounts.token_program.to_account_info(),
             Burn {
@@ -94,7 +116,24 @@ pub fn withdraw_liquidity(
         amount,
     )?;
 
-    
+    ctx.accounts.pool_account_a.reload()?;
+    ctx.accounts.pool_account_b.reload()?;
+    let (reserve_a, reserve_b) = ctx
+        .accounts
+        .pool
+        .reserves(ctx.accounts.pool_account_a.amount, ctx.accounts.pool_account_b.amount)?;
+    // 保存这条指令结束时的储备，之后的价格按它累计
+    ctx.accounts.pool.sync_reserves(reserve_a, reserve_b);
+    emit!(LiquidityRemoved {
+        pool: ctx.accounts.pool.key(),
+        depositor: ctx.accounts.depositor.key(),
+        amount_a,
+        amount_b,
+        liquidity: amount,
+        reserve_a,
+        reserve_b,
+    });
+
     Ok(())
 }
 
@@ -109,6 +148,7 @@ pub struct WithdrawLiquidity<'info> {
     // pub amm: Account<'info, Amm>,
 
     #[account(
+        mut,
         seeds = [
             pool.amm.as_ref(),
             pool.mint_a.key().as_ref(),
@@ -117,9 +157,22 @@ pub struct WithdrawLiquidity<'info> {
         bump,
         has_one = mint_a,
         has_one = mint_b,
+        constraint = !pool.flash_loan_active @ TutorialError::FlashLoanActive,
+        constraint = !pool.has_oracle || oracle.is_some() @ TutorialError::OracleRequired,
     )]
     pub pool: Account<'info, Pool>,
 
+    /// Price oracle of the pool, required once it was initialized so that every price change is observed
+    #[account(
+        mut,
+        seeds = [
+            pool.key().as_ref(),
+            ORACLE_SEED,
+        ],
+        bump,
+    )]
+    pub oracle: Option<Box<Account<'info, Oracle>>>,
+
     #[account(
         seeds = [
             pool.amm.as_ref(),
@@ -142,48 +195,58 @@ pub struct WithdrawLiquidity<'info> {
         ],
         bump,
     )]
-    pub mint_liquidity: Box<Account<'info, Mint>>,
+    pub mint_liquidity: Box<InterfaceAccount<'info, Mint>>,
 
-    pub mint_a: Box<Account<'info, Mint>>,
+    #[account(
+        mint::token_program = token_program_a,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
 
-    pub mint_b: Box<Account<'info, Mint>>,
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
 
     #[account(
         mut,
         associated_token::mint = mint_a,
         associated_token::authority = pool_authority,
+        associated_token::token_program = token_program_a,
     )]
-    pub pool_account_a: Box<Account<'info, TokenAccount>>,
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
         associated_token::mint = mint_b,
         associated_token::authority = pool_authority,
+        associated_token::token_program = token_program_b,
     )]
-    pub pool_account_b: Box<Account<'info, TokenAccount>>,
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         init_if_needed,
         payer = payer,
         associated_token::mint = mint_a,
         associated_token::authority = depositor,
+        associated_token::token_program = token_program_a,
     )]
-    pub depositor_account_a: Box<Account<'info, TokenAccount>>,
+    pub depositor_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         init_if_needed,
         payer = payer,
         associated_token::mint = mint_b,
         associated_token::authority = depositor,
+        associated_token::token_program = token_program_b,
     )]
-    pub depositor_account_b: Box<Account<'info, TokenAccount>>,
+    pub depositor_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
         associated_token::mint = mint_liquidity,
         associated_token::authority = depositor,
     )]
-    pub depositor_account_liquidity: Box<Account<'info, TokenAccount>>,
+    pub depositor_account_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
 
     pub depositor: Signer<'info>,
 
@@ -192,6 +255,9 @@ pub struct WithdrawLiquidity<'info> {
 
     /// Solana ecosystem accounts
     pub token_program: Program<'info, Token>,
+    // 池子两边的代币可以各自属于Token或Token-2022程序
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
\ No newline at end of file
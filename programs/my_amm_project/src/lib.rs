@@ -2,11 +2,17 @@
 #![allow(unexpected_cfgs)]
 
 use anchor_lang::prelude::*;
+use state::{CurveType, Deadline};
 
 mod constants;
+pub mod curve;
 mod errors;
+pub mod events;
 mod instructions;
-mod state;
+mod math;
+pub mod state;
+pub mod tick_math;
+mod utils;
 
 declare_id!("EuB1XVzgMPt1bFYY1wW3hcNAZEuT4y4qWiTH7n8j3Pz5");
 
@@ -15,27 +21,161 @@ pub mod my_amm_project {
     pub use super::instructions::*;
     use super::*;
 
-    pub fn create_amm(ctx: Context<CreateAmm>, id: Pubkey, fee: u16) -> Result<()> {
-        instructions::create_amm(ctx, id, fee)
+    pub fn create_amm(
+        ctx: Context<CreateAmm>,
+        id: Pubkey,
+        fee: u16,
+        fee_delay: i64,
+    ) -> Result<()> {
+        instructions::create_amm(ctx, id, fee, fee_delay)
+    }
+
+    pub fn set_fee(ctx: Context<SetFee>, fee: u16) -> Result<()> {
+        instructions::set_fee(ctx, fee)
+    }
+
+    pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+        instructions::propose_admin(ctx, new_admin)
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        instructions::accept_admin(ctx)
+    }
+
+    pub fn cancel_admin_transfer(ctx: Context<CancelAdminTransfer>) -> Result<()> {
+        instructions::cancel_admin_transfer(ctx)
+    }
+
+    pub fn set_protocol_fee(
+        ctx: Context<SetProtocolFee>,
+        protocol_fee: u16,
+        treasury: Pubkey,
+    ) -> Result<()> {
+        instructions::set_protocol_fee(ctx, protocol_fee, treasury)
+    }
+
+    pub fn collect_protocol_fees(ctx: Context<CollectProtocolFees>) -> Result<()> {
+        instructions::collect_protocol_fees(ctx)
+    }
+
+    pub fn set_amm_pause(ctx: Context<SetAmmPause>, paused: bool) -> Result<()> {
+        instructions::set_amm_pause(ctx, paused)
+    }
+
+    pub fn set_pool_pause(ctx: Context<SetPoolPause>, paused: bool) -> Result<()> {
+        instructions::set_pool_pause(ctx, paused)
+    }
+
+    pub fn set_basket_pool_pause(ctx: Context<SetBasketPoolPause>, paused: bool) -> Result<()> {
+        instructions::set_basket_pool_pause(ctx, paused)
+    }
+
+    pub fn set_clmm_pool_pause(ctx: Context<SetClmmPoolPause>, paused: bool) -> Result<()> {
+        instructions::set_clmm_pool_pause(ctx, paused)
+    }
+
+    pub fn ramp_amp(ctx: Context<RampAmp>, target_amp: u64, ramp_end: i64) -> Result<()> {
+        instructions::ramp_amp(ctx, target_amp, ramp_end)
+    }
+
+    pub fn stop_ramp_amp(ctx: Context<RampAmp>) -> Result<()> {
+        instructions::stop_ramp_amp(ctx)
+    }
+
+    pub fn ramp_basket_amp(ctx: Context<RampBasketAmp>, target_amp: u64, ramp_end: i64) -> Result<()> {
+        instructions::ramp_basket_amp(ctx, target_amp, ramp_end)
+    }
+
+    pub fn stop_ramp_basket_amp(ctx: Context<RampBasketAmp>) -> Result<()> {
+        instructions::stop_ramp_basket_amp(ctx)
     }
 
     pub fn create_pool(ctx: Context<CreatePool>) -> Result<()> {
         instructions::create_pool(ctx)
     }
 
+    pub fn create_stable_pool(ctx: Context<CreatePool>, amp: u64) -> Result<()> {
+        instructions::create_stable_pool(ctx, amp)
+    }
+
+    pub fn create_weighted_pool(ctx: Context<CreatePool>, weight_a: u16) -> Result<()> {
+        instructions::create_weighted_pool(ctx, weight_a)
+    }
+
+    pub fn create_basket_pool<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CreateBasketPool<'info>>,
+        id: Pubkey,
+        token_count: u8,
+        curve_type: CurveType,
+        amp: u64,
+    ) -> Result<()> {
+        instructions::create_basket_pool(ctx, id, token_count, curve_type, amp)
+    }
+
+    pub fn create_pool_with_liquidity(
+        ctx: Context<CreatePoolWithLiquidity>,
+        amount_a: u64,
+        amount_b: u64,
+    ) -> Result<()> {
+        instructions::create_pool_with_liquidity(ctx, amount_a, amount_b)
+    }
+
+    pub fn create_stable_pool_with_liquidity(
+        ctx: Context<CreatePoolWithLiquidity>,
+        amp: u64,
+        amount_a: u64,
+        amount_b: u64,
+    ) -> Result<()> {
+        instructions::create_stable_pool_with_liquidity(ctx, amp, amount_a, amount_b)
+    }
+
+    pub fn create_weighted_pool_with_liquidity(
+        ctx: Context<CreatePoolWithLiquidity>,
+        weight_a: u16,
+        amount_a: u64,
+        amount_b: u64,
+    ) -> Result<()> {
+        instructions::create_weighted_pool_with_liquidity(ctx, weight_a, amount_a, amount_b)
+    }
+
     pub fn deposit_liquidity(
         ctx: Context<DepositLiquidity>,
         amount_a: u64,
         amount_b: u64,
+        min_liquidity_out: u64,
+        max_amount_a: Option<u64>,
+        max_amount_b: Option<u64>,
+        deadline: Option<Deadline>,
     ) -> Result<()> {
-        instructions::deposit_liquidity(ctx, amount_a, amount_b)
+        instructions::deposit_liquidity(
+            ctx,
+            amount_a,
+            amount_b,
+            min_liquidity_out,
+            max_amount_a,
+            max_amount_b,
+            deadline,
+        )
+    }
+
+    pub fn deposit_single_sided(
+        ctx: Context<DepositSingleSided>,
+        deposit_a: bool,
+        amount_in: u64,
+        min_liquidity_out: u64,
+        deadline: Option<Deadline>,
+    ) -> Result<()> {
+        instructions::deposit_single_sided(ctx, deposit_a, amount_in, min_liquidity_out, deadline)
     }
 
     pub fn withdraw_liquidity(
         ctx: Context<WithdrawLiquidity>,
         amount: u64,
+        min_amount_a: u64,
+        min_amount_b: u64,
+        deadline: Option<Deadline>,
     ) -> Result<()> {
-        instructions::withdraw_liquidity(ctx, amount)
+        instructions::withdraw_liquidity(ctx, amount, min_amount_a, min_amount_b, deadline)
     }
 
     pub fn swap_exact_tokens_for_tokens(
@@ -43,8 +183,167 @@ pub mod my_amm_project {
         swap_a: bool,
         input_amount: u64,
         min_output_amount: u64,
+        deadline: Option<Deadline>,
+    ) -> Result<()> {
+        instructions::swap_exact_tokens_for_tokens(ctx, swap_a, input_amount, min_output_amount, deadline)
+    }
+
+    pub fn swap_tokens_for_exact_tokens(
+        ctx: Context<SwapTokensForExactTokens>,
+        swap_a: bool,
+        output_amount: u64,
+        max_input_amount: u64,
+        deadline: Option<Deadline>,
+    ) -> Result<()> {
+        instructions::swap_tokens_for_exact_tokens(ctx, swap_a, output_amount, max_input_amount, deadline)
+    }
+
+    pub fn swap_route<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SwapRoute<'info>>,
+        input_amount: u64,
+        min_output_amount: u64,
+        deadline: Option<Deadline>,
     ) -> Result<()> {
-        instructions::swap_exact_tokens_for_tokens(ctx, swap_a, input_amount, min_output_amount)
+        instructions::swap_route(ctx, input_amount, min_output_amount, deadline)
+    }
+
+    pub fn deposit_basket_liquidity<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DepositBasketLiquidity<'info>>,
+        amounts: Vec<u64>,
+        min_liquidity_out: u64,
+        deadline: Option<Deadline>,
+    ) -> Result<()> {
+        instructions::deposit_basket_liquidity(ctx, amounts, min_liquidity_out, deadline)
+    }
+
+    pub fn withdraw_basket_liquidity<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WithdrawBasketLiquidity<'info>>,
+        amount: u64,
+        min_amounts: Vec<u64>,
+        deadline: Option<Deadline>,
+    ) -> Result<()> {
+        instructions::withdraw_basket_liquidity(ctx, amount, min_amounts, deadline)
+    }
+
+    pub fn swap_basket<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SwapBasket<'info>>,
+        input_index: u8,
+        output_index: u8,
+        input_amount: u64,
+        min_output_amount: u64,
+        deadline: Option<Deadline>,
+    ) -> Result<()> {
+        instructions::swap_basket(ctx, input_index, output_index, input_amount, min_output_amount, deadline)
+    }
+
+    pub fn collect_basket_protocol_fees<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CollectBasketProtocolFees<'info>>,
+    ) -> Result<()> {
+        instructions::collect_basket_protocol_fees(ctx)
+    }
+
+    pub fn create_clmm_pool(
+        ctx: Context<CreateClmmPool>,
+        tick_spacing: u16,
+        sqrt_price: u128,
+    ) -> Result<()> {
+        instructions::create_clmm_pool(ctx, tick_spacing, sqrt_price)
+    }
+
+    pub fn initialize_tick_array(ctx: Context<InitializeTickArray>, start_tick: i32) -> Result<()> {
+        instructions::initialize_tick_array(ctx, start_tick)
+    }
+
+    pub fn open_position(
+        ctx: Context<OpenPosition>,
+        tick_lower: i32,
+        tick_upper: i32,
+    ) -> Result<()> {
+        instructions::open_position(ctx, tick_lower, tick_upper)
+    }
+
+    pub fn close_position(ctx: Context<ClosePosition>) -> Result<()> {
+        instructions::close_position(ctx)
+    }
+
+    pub fn increase_liquidity(
+        ctx: Context<IncreaseLiquidity>,
+        amount_a: u64,
+        amount_b: u64,
+        min_liquidity_out: u128,
+        deadline: Option<Deadline>,
+    ) -> Result<()> {
+        instructions::increase_liquidity(ctx, amount_a, amount_b, min_liquidity_out, deadline)
+    }
+
+    pub fn decrease_liquidity(
+        ctx: Context<DecreaseLiquidity>,
+        liquidity: u128,
+        min_amount_a: u64,
+        min_amount_b: u64,
+        deadline: Option<Deadline>,
+    ) -> Result<()> {
+        instructions::decrease_liquidity(ctx, liquidity, min_amount_a, min_amount_b, deadline)
+    }
+
+    pub fn collect_fees(ctx: Context<CollectFees>) -> Result<()> {
+        instructions::collect_fees(ctx)
+    }
+
+    pub fn swap_clmm<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SwapClmm<'info>>,
+        swap_a: bool,
+        input_amount: u64,
+        min_output_amount: u64,
+        deadline: Option<Deadline>,
+    ) -> Result<()> {
+        instructions::swap_clmm(ctx, swap_a, input_amount, min_output_amount, deadline)
+    }
+
+    pub fn collect_clmm_protocol_fees(ctx: Context<CollectClmmProtocolFees>) -> Result<()> {
+        instructions::collect_clmm_protocol_fees(ctx)
+    }
+
+    pub fn initialize_oracle(ctx: Context<InitializeOracle>, capacity: u16) -> Result<()> {
+        instructions::initialize_oracle(ctx, capacity)
+    }
+
+    pub fn consult_twap(ctx: Context<ConsultTwap>, window: u32) -> Result<Twap> {
+        instructions::consult_twap(ctx, window)
+    }
+
+    pub fn initialize_clmm_oracle(ctx: Context<InitializeClmmOracle>, capacity: u16) -> Result<()> {
+        instructions::initialize_clmm_oracle(ctx, capacity)
+    }
+
+    pub fn consult_clmm_twap(ctx: Context<ConsultClmmTwap>, window: u32) -> Result<Twap> {
+        instructions::consult_clmm_twap(ctx, window)
+    }
+
+    pub fn flash_borrow(ctx: Context<FlashBorrow>, amount_a: u64, amount_b: u64) -> Result<()> {
+        instructions::flash_borrow(ctx, amount_a, amount_b)
+    }
+
+    pub fn flash_repay(ctx: Context<FlashRepay>) -> Result<()> {
+        instructions::flash_repay(ctx)
+    }
+
+    pub fn quote_swap(ctx: Context<QuoteSwap>, swap_a: bool, input_amount: u64) -> Result<SwapPreview> {
+        instructions::quote_swap(ctx, swap_a, input_amount)
+    }
+
+    pub fn quote_deposit(
+        ctx: Context<QuoteLiquidity>,
+        amount_a: u64,
+        amount_b: u64,
+        balance_a: Option<u64>,
+        balance_b: Option<u64>,
+    ) -> Result<DepositPreview> {
+        instructions::quote_deposit(ctx, amount_a, amount_b, balance_a, balance_b)
+    }
+
+    pub fn quote_withdraw(ctx: Context<QuoteLiquidity>, amount: u64) -> Result<WithdrawPreview> {
+        instructions::quote_withdraw(ctx, amount)
     }
 }
 
@@ -0,0 +1,452 @@
+use anchor_lang::prelude::*;
+// 固定点数学库，用于精确计算：
+// - I64F64 = 64位整数 + 64位小数
+// - 避免浮点数精度丢失问题
+// - 区块链上不能用浮点数，必须用整数或固定点
+use fixed::types::I64F64;
+
+use crate::errors::TutorialError;
+
+// 256位无符号整数，StableSwap的D^3这类中间结果会超过u128
+// 放在单独的模块里：construct_uint!展开的代码用的是标准库的Result，和anchor prelude的冲突
+#[allow(clippy::all)]
+mod u256 {
+    uint::construct_uint! {
+        pub struct U256(4);
+    }
+}
+pub use u256::U256;
+
+// 所有数学运算都返回Result，溢出或除以0时返回明确的错误，而不是让程序panic
+
+// I64F64的整数部分只有63位，超过i64::MAX的u64不能直接转换
+pub fn to_fixed(value: u64) -> Result<I64F64> {
+    I64F64::checked_from_num(value).ok_or_else(|| error!(TutorialError::MathOverflow))
+}
+
+// 向下取整转回u64，负数或超过u64::MAX都算溢出
+pub fn to_u64(value: I64F64) -> Result<u64> {
+    value
+        .floor()
+        .checked_to_num::<u64>()
+        .ok_or_else(|| error!(TutorialError::MathOverflow))
+}
+
+pub fn checked_mul(a: I64F64, b: I64F64) -> Result<I64F64> {
+    a.checked_mul(b).ok_or_else(|| error!(TutorialError::MathOverflow))
+}
+
+pub fn checked_div(a: I64F64, b: I64F64) -> Result<I64F64> {
+    if b == I64F64::ZERO {
+        return err!(TutorialError::DivisionByZero);
+    }
+    a.checked_div(b).ok_or_else(|| error!(TutorialError::MathOverflow))
+}
+
+pub fn add(a: u64, b: u64) -> Result<u64> {
+    a.checked_add(b).ok_or_else(|| error!(TutorialError::MathOverflow))
+}
+
+pub fn sub(a: u64, b: u64) -> Result<u64> {
+    a.checked_sub(b).ok_or_else(|| error!(TutorialError::MathOverflow))
+}
+
+// a * b / denominator，向下取整
+// 中间结果用u128计算，两个u64相乘不会溢出
+pub fn mul_div(a: u64, b: u64, denominator: u64) -> Result<u64> {
+    if denominator == 0 {
+        return err!(TutorialError::DivisionByZero);
+    }
+    let result = a as u128 * b as u128 / denominator as u128;
+    u64::try_from(result).map_err(|_| error!(TutorialError::MathOverflow))
+}
+
+// a * b / denominator，向上取整
+pub fn mul_div_ceil(a: u64, b: u64, denominator: u64) -> Result<u64> {
+    if denominator == 0 {
+        return err!(TutorialError::DivisionByZero);
+    }
+    let result = (a as u128 * b as u128).div_ceil(denominator as u128);
+    u64::try_from(result).map_err(|_| error!(TutorialError::MathOverflow))
+}
+
+// 恒定乘积 k = reserve_a * reserve_b，用u128保存，不会溢出
+pub fn invariant(reserve_a: u64, reserve_b: u64) -> u128 {
+    reserve_a as u128 * reserve_b as u128
+}
+
+// sqrt(a * b)，向下取整
+// 结果不会超过max(a, b)，所以一定能放进u64
+pub fn sqrt_product(a: u64, b: u64) -> u64 {
+    let product = a as u128 * b as u128;
+    if product < 2 {
+        return product as u64;
+    }
+
+    // 牛顿迭代，从一个不小于结果的初始值开始单调递减
+    let mut x = 1u128 << (128 - product.leading_zeros()).div_ceil(2);
+    loop {
+        let y = (x + product / x) / 2;
+        if y >= x {
+            return x as u64;
+        }
+        x = y;
+    }
+}
+
+// StableSwap的Newton迭代最多执行的次数，正常情况下几次就收敛
+const STABLE_MAX_ITERATIONS: usize = 255;
+
+pub fn u256_to_u64(value: U256) -> Result<u64> {
+    if value > U256::from(u64::MAX) {
+        return err!(TutorialError::MathOverflow);
+    }
+    Ok(value.as_u64())
+}
+
+pub fn u256_to_u128(value: U256) -> Result<u128> {
+    if value > U256::from(u128::MAX) {
+        return err!(TutorialError::MathOverflow);
+    }
+    Ok(value.as_u128())
+}
+
+// U256的运算符溢出时会panic，StableSwap的迭代这类中间结果用下面这些检查过的版本
+pub fn u256_add(a: U256, b: U256) -> Result<U256> {
+    a.checked_add(b).ok_or_else(|| error!(TutorialError::MathOverflow))
+}
+
+pub fn u256_sub(a: U256, b: U256) -> Result<U256> {
+    a.checked_sub(b).ok_or_else(|| error!(TutorialError::MathOverflow))
+}
+
+pub fn u256_mul(a: U256, b: U256) -> Result<U256> {
+    a.checked_mul(b).ok_or_else(|| error!(TutorialError::MathOverflow))
+}
+
+pub fn u256_div(a: U256, b: U256) -> Result<U256> {
+    a.checked_div(b).ok_or_else(|| error!(TutorialError::DivisionByZero))
+}
+
+// StableSwap不变量D（n种代币，Curve的写法，ann = amp * n）：
+// ann * S + D = ann * D + D^(n+1) / (n^n * prod(x_i))，S = sum(x_i)
+// 两种代币时：ann * (x + y) + D = ann * D + D^3 / (4 * x * y)
+// 从D = S开始牛顿迭代：
+// D_P = D^(n+1) / (n^n * prod(x_i))
+// D = (ann * S + n * D_P) * D / ((ann - 1) * D + (n + 1) * D_P)
+// 任意一种代币的储备为0时返回0（池子为空）
+pub fn stable_invariant(amp: u64, balances: &[u64]) -> Result<u128> {
+    if balances.contains(&0) {
+        return Ok(0);
+    }
+
+    // 储备很不平衡时D_P会超过U256，这时返回MathOverflow
+    let n = U256::from(balances.len());
+    let ann = u256_mul(U256::from(amp), n)?;
+    let sum = balances
+        .iter()
+        .try_fold(U256::zero(), |sum, &balance| u256_add(sum, U256::from(balance)))?;
+    let mut d = sum;
+    for _ in 0..STABLE_MAX_ITERATIONS {
+        let mut d_p = d;
+        for &balance in balances {
+            d_p = u256_div(u256_mul(d_p, d)?, u256_mul(U256::from(balance), n)?)?;
+        }
+        let d_prev = d;
+        let numerator = u256_mul(u256_add(u256_mul(ann, sum)?, u256_mul(d_p, n)?)?, d)?;
+        let denominator = u256_add(
+            u256_mul(u256_sub(ann, U256::one())?, d)?,
+            u256_mul(n + 1, d_p)?,
+        )?;
+        d = u256_div(numerator, denominator)?;
+        if d.abs_diff(d_prev) <= U256::one() {
+            break;
+        }
+    }
+
+    u256_to_u128(d)
+}
+
+// 已知其他代币的储备和不变量D，求第index种代币的储备y（balances[index]被忽略）：
+// y^2 + (S' + D / ann - D) * y = D^(n+1) / (n^n * prod'(x_k) * ann)，S'、prod'不含y
+// 牛顿迭代：y = (y^2 + c) / (2 * y + b - D)，其中 b = S' + D / ann，c = D^(n+1) / (n^n * prod' * ann)
+// 结果向上取整，池子付出的数量因此向下取整
+pub fn stable_reserve(amp: u64, balances: &[u64], index: usize, d: u128) -> Result<u64> {
+    let n = U256::from(balances.len());
+    let ann = u256_mul(U256::from(amp), n)?;
+    let d = U256::from(d);
+
+    let mut c = d;
+    let mut sum = U256::zero();
+    for (k, &balance) in balances.iter().enumerate() {
+        if k == index {
+            continue;
+        }
+        if balance == 0 {
+            return err!(TutorialError::DivisionByZero);
+        }
+        sum = u256_add(sum, U256::from(balance))?;
+        c = u256_div(u256_mul(c, d)?, u256_mul(U256::from(balance), n)?)?;
+    }
+    c = u256_div(u256_mul(c, d)?, u256_mul(ann, n)?)?;
+
+    let b = u256_add(sum, u256_div(d, ann)?)?;
+    let mut y = d;
+    for _ in 0..STABLE_MAX_ITERATIONS {
+        let y_prev = y;
+        let denominator = u256_sub(u256_add(u256_mul(y, U256::from(2))?, b)?, d)?;
+        y = u256_div(u256_add(u256_mul(y, y)?, c)?, denominator)?;
+        if y.abs_diff(y_prev) <= U256::one() {
+            break;
+        }
+    }
+
+    u256_to_u64(u256_add(y, U256::one())?)
+}
+
+// 加权曲线的幂运算误差上界（相对误差2^-40），取整时按这个余量对池子有利的方向调整
+const POW_ERROR_SHIFT: u32 = 40;
+
+// log2(x)，x > 0
+// 整数部分是最高位的位置，小数部分对尾数m∈[1, 2)反复平方：m^2 >= 2时这一位是1
+pub fn log2(x: u64) -> I64F64 {
+    let integer = 63 - x.leading_zeros();
+    // 尾数用Q63表示，平方不会超过u128
+    let mut mantissa = ((x as u128) << 63) >> integer;
+    let mut fraction: u128 = 0;
+    for bit in (0..64).rev() {
+        mantissa = (mantissa * mantissa) >> 63;
+        if mantissa >= 1 << 64 {
+            fraction |= 1 << bit;
+            mantissa >>= 1;
+        }
+    }
+    I64F64::from_bits(((integer as i128) << 64) | fraction as i128)
+}
+
+// 2^y = 2^k * e^(f * ln2)，k = floor(y)，f∈[0, 1)
+// e^z用泰勒级数计算，z < ln2，30项之后的误差远小于I64F64的精度
+fn exp2(y: I64F64) -> Result<I64F64> {
+    let k = y.floor();
+    let z = checked_mul(y - k, I64F64::LN_2)?;
+
+    let mut term = I64F64::ONE;
+    let mut sum = I64F64::ONE;
+    for n in 1..30 {
+        term = checked_mul(term, z)? / I64F64::from_num(n);
+        if term == I64F64::ZERO {
+            break;
+        }
+        sum += term;
+    }
+
+    let k = k.to_num::<i64>();
+    if k >= 0 {
+        let scale = u64::try_from(k)
+            .ok()
+            .and_then(|k| 1u64.checked_shl(k as u32))
+            .ok_or_else(|| error!(TutorialError::MathOverflow))?;
+        checked_mul(sum, to_fixed(scale)?)
+    } else if k > -128 {
+        Ok(sum >> (-k) as u32)
+    } else {
+        Ok(I64F64::ZERO)
+    }
+}
+
+// (base_numerator / base_denominator) ^ (exponent_numerator / exponent_denominator)
+// = 2^((log2(base_numerator) - log2(base_denominator)) * exponent)
+// round_up为true时结果不小于真实值，否则不大于真实值
+pub fn pow(
+    base_numerator: u64,
+    base_denominator: u64,
+    exponent_numerator: u64,
+    exponent_denominator: u64,
+    round_up: bool,
+) -> Result<I64F64> {
+    if base_numerator == 0 || base_denominator == 0 {
+        return err!(TutorialError::DivisionByZero);
+    }
+
+    let log = log2(base_numerator) - log2(base_denominator);
+    let exponent = checked_div(to_fixed(exponent_numerator)?, to_fixed(exponent_denominator)?)?;
+    let result = exp2(checked_mul(log, exponent)?)?;
+
+    let error = (result >> POW_ERROR_SHIFT) + I64F64::DELTA;
+    if round_up {
+        result.checked_add(error).ok_or_else(|| error!(TutorialError::MathOverflow))
+    } else {
+        Ok(if result > error { result - error } else { I64F64::ZERO })
+    }
+}
+
+// value * factor，factor是非负的定点数，中间结果用U256
+pub fn mul_fixed(value: u64, factor: I64F64, round_up: bool) -> Result<u64> {
+    if factor < I64F64::ZERO {
+        return err!(TutorialError::MathOverflow);
+    }
+
+    let product = U256::from(value) * U256::from(factor.to_bits() as u128);
+    let mut result = product >> 64;
+    if round_up && result << 64 != product {
+        result += U256::one();
+    }
+    u256_to_u64(result)
+}
+
+// 加权乘积不变量 V = x^weight_x * y^weight_y（权重之和为1）的log2：
+// log2(V) = weight_x * log2(x) + weight_y * log2(y)
+// 和V的单调性相同，用来比较交易前后的不变量；任意一边为0时返回0
+pub fn weighted_log_invariant(x: u64, y: u64, weight_x: u16, weight_y: u16) -> Result<u128> {
+    if x == 0 || y == 0 {
+        return Ok(0);
+    }
+
+    let total = to_fixed(weight_x as u64 + weight_y as u64)?;
+    let log = checked_mul(log2(x), checked_div(to_fixed(weight_x as u64)?, total)?)?
+        + checked_mul(log2(y), checked_div(to_fixed(weight_y as u64)?, total)?)?;
+    // x、y >= 1，log2(V) >= 0
+    Ok(log.to_bits() as u128)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 相对误差不超过2^-shift（再加上I64F64最后一位的取整）
+    fn assert_close(actual: I64F64, expected: f64, shift: u32) {
+        let actual = actual.to_num::<f64>();
+        let tolerance = expected.abs() / (1u64 << shift) as f64 + 2.0 * I64F64::DELTA.to_num::<f64>();
+        assert!(
+            (actual - expected).abs() <= tolerance,
+            "{actual} is not within 2^-{shift} of {expected}"
+        );
+    }
+
+    #[test]
+    fn mul_div_rounds_down_and_mul_div_ceil_rounds_up() {
+        assert_eq!(mul_div(10, 10, 3).unwrap(), 33);
+        assert_eq!(mul_div_ceil(10, 10, 3).unwrap(), 34);
+        // 整除时两者相同
+        assert_eq!(mul_div(9, 10, 3).unwrap(), 30);
+        assert_eq!(mul_div_ceil(9, 10, 3).unwrap(), 30);
+        // 中间结果超过u64也没关系
+        assert_eq!(mul_div(u64::MAX, u64::MAX, u64::MAX).unwrap(), u64::MAX);
+        assert_eq!(mul_div_ceil(u64::MAX, u64::MAX - 1, u64::MAX).unwrap(), u64::MAX - 1);
+    }
+
+    #[test]
+    fn mul_div_errors() {
+        assert_eq!(mul_div(1, 1, 0), Err(TutorialError::DivisionByZero.into()));
+        assert_eq!(mul_div_ceil(1, 1, 0), Err(TutorialError::DivisionByZero.into()));
+        assert_eq!(mul_div(u64::MAX, 2, 1), Err(TutorialError::MathOverflow.into()));
+        assert_eq!(mul_div_ceil(u64::MAX, 3, 2), Err(TutorialError::MathOverflow.into()));
+    }
+
+    #[test]
+    fn sqrt_product_rounds_down() {
+        assert_eq!(sqrt_product(0, 5), 0);
+        assert_eq!(sqrt_product(1, 1), 1);
+        assert_eq!(sqrt_product(2, 3), 2);
+        assert_eq!(sqrt_product(1000, 4000), 2000);
+        assert_eq!(sqrt_product(u64::MAX, u64::MAX), u64::MAX);
+
+        for (a, b) in [(3, 5), (12345, 67890), (u64::MAX, 2), (1 << 40, (1 << 40) + 1)] {
+            let root = sqrt_product(a, b) as u128;
+            let product = a as u128 * b as u128;
+            assert!(root * root <= product);
+            assert!((root + 1) * (root + 1) > product);
+        }
+    }
+
+    #[test]
+    fn stable_invariant_of_balanced_pool_is_the_sum() {
+        assert_eq!(stable_invariant(100, &[1000, 1000]).unwrap(), 2000);
+        assert_eq!(stable_invariant(100, &[1000, 1000, 1000]).unwrap(), 3000);
+        assert_eq!(stable_invariant(100, &[0, 1000]).unwrap(), 0);
+    }
+
+    #[test]
+    fn stable_invariant_converges_between_product_and_sum() {
+        for (amp, balances) in [
+            (1, vec![1_000_000, 3_000_000]),
+            (100, vec![1_000_000, 3_000_000]),
+            (1_000_000, vec![1_000_000, 3_000_000]),
+            (100, vec![10u64.pow(12), 5 * 10u64.pow(11), 2 * 10u64.pow(12)]),
+        ] {
+            let d = stable_invariant(amp, &balances).unwrap();
+            let sum: u128 = balances.iter().map(|&balance| balance as u128).sum();
+            assert!(d <= sum);
+
+            // D介于几何平均（恒定乘积）和算术平均（恒定和）之间
+            let n = balances.len() as f64;
+            let mean = balances.iter().map(|&balance| (balance as f64).ln()).sum::<f64>() / n;
+            assert!(d as f64 >= n * mean.exp() * (1.0 - 1e-9));
+
+            // 用D反解任意一种代币的储备，结果向上取整，和原来的储备最多差几个单位
+            for index in 0..balances.len() {
+                let reserve = stable_reserve(amp, &balances, index, d).unwrap();
+                assert!(reserve >= balances[index]);
+                assert!(reserve - balances[index] <= 3);
+            }
+        }
+    }
+
+    #[test]
+    fn stable_math_overflow_is_an_error() {
+        // 8种代币，7种的储备是1，D_P每乘一次增长约2^61，很快超过U256
+        let mut balances = vec![1u64; 7];
+        balances.push(u64::MAX);
+        assert_eq!(stable_invariant(100, &balances), Err(TutorialError::MathOverflow.into()));
+        assert_eq!(
+            stable_reserve(100, &balances, 7, u64::MAX as u128),
+            Err(TutorialError::MathOverflow.into())
+        );
+    }
+
+    #[test]
+    fn stable_reserve_with_empty_reserve_is_an_error() {
+        assert_eq!(stable_reserve(100, &[0, 0, 1000], 2, 3000), Err(TutorialError::DivisionByZero.into()));
+    }
+
+    #[test]
+    fn log2_matches_within_bound() {
+        assert_eq!(log2(1), I64F64::ZERO);
+        assert_eq!(log2(2), I64F64::ONE);
+        assert_eq!(log2(1024), I64F64::from_num(10));
+        for x in [3u64, 10, 12345, 1 << 40, u64::MAX] {
+            assert_close(log2(x), (x as f64).log2(), POW_ERROR_SHIFT);
+        }
+    }
+
+    #[test]
+    fn exp2_matches_within_bound() {
+        assert_eq!(exp2(I64F64::ZERO).unwrap(), I64F64::ONE);
+        for y in [0.5f64, -0.5, 1.25, -3.75, 10.1, 40.9, -60.3] {
+            assert_close(exp2(I64F64::from_num(y)).unwrap(), y.exp2(), POW_ERROR_SHIFT);
+        }
+        assert_eq!(exp2(I64F64::from_num(64)), Err(TutorialError::MathOverflow.into()));
+    }
+
+    #[test]
+    fn pow_brackets_the_exact_value() {
+        for (base_numerator, base_denominator, exponent_numerator, exponent_denominator) in [
+            (3u64, 2u64, 1u64, 2u64),
+            (1, 4, 8000, 2000),
+            (80, 20, 2000, 8000),
+            (1_000_000, 1_000_001, 5000, 5000),
+            (u64::MAX, 1 << 32, 1, 3),
+        ] {
+            let exact = (base_numerator as f64 / base_denominator as f64)
+                .powf(exponent_numerator as f64 / exponent_denominator as f64);
+            let up = pow(base_numerator, base_denominator, exponent_numerator, exponent_denominator, true).unwrap();
+            let down = pow(base_numerator, base_denominator, exponent_numerator, exponent_denominator, false).unwrap();
+            assert!(up.to_num::<f64>() >= exact * (1.0 - 1e-15));
+            assert!(down.to_num::<f64>() <= exact * (1.0 + 1e-15));
+            // 计算误差不超过2^-40，取整又按同样的余量偏向一边
+            assert_close(up, exact, POW_ERROR_SHIFT - 1);
+            assert_close(down, exact, POW_ERROR_SHIFT - 1);
+        }
+        assert_eq!(pow(0, 1, 1, 2, false), Err(TutorialError::DivisionByZero.into()));
+    }
+}
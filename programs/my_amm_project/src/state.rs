@@ -1,5 +1,13 @@
 use anchor_lang::prelude::*;
 
+use crate::{
+    constants::TICK_ARRAY_SIZE,
+    curve::Curve,
+    errors::TutorialError,
+    math::{self, U256},
+    tick_math,
+};
+
 #[account]
 #[derive(Default)]
 pub struct Amm {
@@ -9,10 +17,63 @@ pub struct Amm {
     pub admin: Pubkey,
     /// The LP fee taken on each trade, in basis points
     pub fee: u16,
+    /// Fee proposed by the admin, in basis points
+    pub pending_fee: u16,
+    /// Unix timestamp at which `pending_fee` takes effect, 0 when nothing is pending
+    pub pending_fee_activation: i64,
+    /// Delay in seconds between proposing a fee and it taking effect
+    pub fee_delay: i64,
+    /// Account proposed as the next admin, default when no transfer is pending
+    pub pending_admin: Pubkey,
+    /// Share of the trading fee kept by the protocol, in basis points of the fee
+    pub protocol_fee: u16,
+    /// Account receiving the protocol fees
+    pub treasury: Pubkey,
+    /// Blocks swaps and deposits in every pool of the AMM
+    pub paused: bool,
 }
 
 impl Amm {
-    pub const LEN: usize = 8 + 32 + 32 + 2;
+    pub const LEN: usize = 8 + 32 + 32 + 2 + 2 + 8 + 8 + 32 + 2 + 32 + 1;
+
+    /// The fee in force at `now`
+    pub fn current_fee(&self, now: i64) -> u16 {
+        if self.pending_fee_activation != 0 && now >= self.pending_fee_activation {
+            self.pending_fee
+        } else {
+            self.fee
+        }
+    }
+
+    /// Moves a pending fee into `fee` once it has become effective
+    pub fn settle_fee(&mut self, now: i64) {
+        self.fee = self.current_fee(now);
+        if self.pending_fee_activation != 0 && now >= self.pending_fee_activation {
+            self.pending_fee = 0;
+            self.pending_fee_activation = 0;
+        }
+    }
+}
+
+/// The invariant a pool trades on
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CurveType {
+    /// x * y = k
+    #[default]
+    ConstantProduct,
+    /// Curve-style amplified invariant for pegged pairs
+    StableSwap,
+    /// Balancer-style weighted product for non-50/50 pools
+    Weighted,
+}
+
+/// Latest point at which a trading instruction may still execute
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Deadline {
+    /// Compared with the unix timestamp of the `Clock` sysvar
+    Timestamp { unix_timestamp: i64 },
+    /// Compared with the slot of the `Clock` sysvar
+    Slot { slot: u64 },
 }
 
 #[account]
@@ -24,8 +85,586 @@ pub struct Pool {
     pub mint_a: Pubkey,
     /// Mint of token B
     pub mint_b: Pubkey,
+    /// Protocol fees in token A held by the pool vault but not owned by the LPs
+    pub protocol_fees_a: u64,
+    /// Protocol fees in token B held by the pool vault but not owned by the LPs
+    pub protocol_fees_b: u64,
+    /// Blocks swaps and deposits in this pool
+    pub paused: bool,
+    /// The invariant the pool trades on
+    pub curve_type: CurveType,
+    /// Amplification coefficient of a StableSwap pool at `amp_ramp_start`, 0 for other curves
+    pub amp: u64,
+    /// Amplification coefficient reached at `amp_ramp_end`, equal to `amp` when no ramp was started
+    pub target_amp: u64,
+    /// Unix timestamp at which the current amp ramp started
+    pub amp_ramp_start: i64,
+    /// Unix timestamp at which the current amp ramp ends
+    pub amp_ramp_end: i64,
+    /// Weight of token A in a weighted pool, in basis points, 0 for other curves
+    pub weight_a: u16,
+    /// Weight of token B in a weighted pool, in basis points, 0 for other curves
+    pub weight_b: u16,
+    /// Time-weighted sum of the price of token B in token A (A per B), as a wrapping Q64.64 number
+    pub price_a_per_b_cumulative: u128,
+    /// Time-weighted sum of the price of token A in token B (B per A), as a wrapping Q64.64 number
+    pub price_b_per_a_cumulative: u128,
+    /// Unix timestamp at which the price accumulators were last updated
+    pub last_price_update: i64,
+    /// Token A reserve owned by the LPs at the end of the last instruction that changed it, priced by the accumulators
+    pub reserve_a: u64,
+    /// Token B reserve owned by the LPs at the end of the last instruction that changed it, priced by the accumulators
+    pub reserve_b: u64,
+    /// Set by `initialize_oracle`, every instruction that moves the price must then pass the oracle
+    pub has_oracle: bool,
+    /// Set between `flash_borrow` and `flash_repay`, blocks every other trade on the pool
+    pub flash_loan_active: bool,
+    /// Token A the pool must receive back in `flash_repay`, loan plus fee
+    pub flash_owed_a: u64,
+    /// Token B the pool must receive back in `flash_repay`, loan plus fee
+    pub flash_owed_b: u64,
+    /// Invariant of the reserves before the flash loan, checked again after repayment
+    pub flash_invariant: u128,
 }
 
 impl Pool {
-    pub const LEN: usize = 8 + 32 + 32 + 32;
-}
\ No newline at end of file
+    pub const LEN: usize =
+        8 + 32 + 32 + 32 + 8 + 8 + 1 + 1 + 8 + 8 + 8 + 8 + 2 + 2 + 16 + 16 + 8 + 8 + 8 + 1 + 1 + 8 + 8 + 16;
+
+    /// Adds the prices of the reserve snapshot held since the last update, weighted by the elapsed seconds
+    pub fn update_price_cumulative(&mut self, now: i64) {
+        if now <= self.last_price_update {
+            return;
+        }
+        // Uniswap v2的做法：价格是Q64.64，乘上经过的秒数后只保留低128位，累加器允许溢出回绕
+        // 读取方只用两个时间点的差值，回绕不影响结果；储备为0时没有价格，只更新时间
+        // 用上一条指令结束时保存的储备而不是vault的余额，直接转进vault的代币不会影响价格
+        let (reserve_a, reserve_b) = (self.reserve_a, self.reserve_b);
+        if reserve_a > 0 && reserve_b > 0 {
+            let elapsed = U256::from((now - self.last_price_update) as u64);
+            let a_per_b = (U256::from(reserve_a) << 64) / U256::from(reserve_b) * elapsed;
+            let b_per_a = (U256::from(reserve_b) << 64) / U256::from(reserve_a) * elapsed;
+            self.price_a_per_b_cumulative = self.price_a_per_b_cumulative.wrapping_add(a_per_b.low_u128());
+            self.price_b_per_a_cumulative = self.price_b_per_a_cumulative.wrapping_add(b_per_a.low_u128());
+        }
+        self.last_price_update = now;
+    }
+
+    /// Saves the reserves at the end of an instruction, priced from now on by `update_price_cumulative`
+    pub fn sync_reserves(&mut self, reserve_a: u64, reserve_b: u64) {
+        self.reserve_a = reserve_a;
+        self.reserve_b = reserve_b;
+    }
+
+    /// The price accumulators at their last update
+    pub fn observation(&self) -> Observation {
+        Observation {
+            timestamp: self.last_price_update,
+            price_a_per_b_cumulative: self.price_a_per_b_cumulative,
+            price_b_per_a_cumulative: self.price_b_per_a_cumulative,
+        }
+    }
+
+    /// The curve used to price swaps and deposits at `now`, with its parameters in token A, B order
+    pub fn curve(&self, now: i64) -> Curve {
+        match self.curve_type {
+            CurveType::ConstantProduct => Curve::ConstantProduct,
+            CurveType::StableSwap => Curve::StableSwap { amp: self.current_amp(now) },
+            CurveType::Weighted => Curve::Weighted {
+                weight_x: self.weight_a,
+                weight_y: self.weight_b,
+            },
+        }
+    }
+
+    /// Orders a pair of mints the way pools store them: `mint_a < mint_b` by pubkey bytes
+    pub fn canonical_mints(mint_x: Pubkey, mint_y: Pubkey) -> (Pubkey, Pubkey) {
+        if mint_x < mint_y {
+            (mint_x, mint_y)
+        } else {
+            (mint_y, mint_x)
+        }
+    }
+
+    /// Derives the address of the pool of `amm` trading `mint_x` against `mint_y`, in either order
+    pub fn find_address(amm: &Pubkey, mint_x: &Pubkey, mint_y: &Pubkey) -> (Pubkey, u8) {
+        let (mint_a, mint_b) = Self::canonical_mints(*mint_x, *mint_y);
+        Pubkey::find_program_address(
+            &[amm.as_ref(), mint_a.as_ref(), mint_b.as_ref()],
+            &crate::ID,
+        )
+    }
+
+    /// The reserves owned by the LPs, i.e. the vault balances minus the accrued protocol fees
+    pub fn reserves(&self, vault_a: u64, vault_b: u64) -> Result<(u64, u64)> {
+        Ok((
+            math::sub(vault_a, self.protocol_fees_a)?,
+            math::sub(vault_b, self.protocol_fees_b)?,
+        ))
+    }
+}
+
+#[account]
+#[derive(Default)]
+pub struct BasketPool {
+    /// Primary key of the AMM
+    pub amm: Pubkey,
+    /// Primary key of the basket pool, used in its PDA seeds in place of the mints
+    pub id: Pubkey,
+    /// The invariant the pool trades on, constant product or StableSwap
+    pub curve_type: CurveType,
+    /// Amplification coefficient of a StableSwap pool at `amp_ramp_start`, 0 for constant product
+    pub amp: u64,
+    /// Amplification coefficient reached at `amp_ramp_end`, equal to `amp` when no ramp was started
+    pub target_amp: u64,
+    /// Unix timestamp at which the current amp ramp started
+    pub amp_ramp_start: i64,
+    /// Unix timestamp at which the current amp ramp ends
+    pub amp_ramp_end: i64,
+    /// Blocks swaps and deposits in this pool
+    pub paused: bool,
+    /// Mints of the pool tokens in ascending order, each with its vault owned by the pool authority
+    pub mints: Vec<Pubkey>,
+    /// Protocol fees of each token, in the order of `mints`, held by the vaults but not owned by the LPs
+    pub protocol_fees: Vec<u64>,
+}
+
+impl BasketPool {
+    /// Account size of a basket pool holding `token_count` tokens
+    pub fn space(token_count: usize) -> usize {
+        8 + 32 + 32 + 1 + 8 + 8 + 8 + 8 + 1 + 4 + 32 * token_count + 4 + 8 * token_count
+    }
+
+    /// The curve used to price swaps and the first deposit at `now`
+    pub fn curve(&self, now: i64) -> Result<Curve> {
+        match self.curve_type {
+            CurveType::ConstantProduct => Ok(Curve::ConstantProduct),
+            CurveType::StableSwap => Ok(Curve::StableSwap { amp: self.current_amp(now) }),
+            // create_basket_pool不允许加权曲线
+            CurveType::Weighted => err!(TutorialError::InvalidCurve),
+        }
+    }
+
+    /// The reserve of the token at `index` owned by the LPs, i.e. the vault balance minus the accrued protocol fees
+    pub fn reserve(&self, index: usize, vault: u64) -> Result<u64> {
+        math::sub(vault, self.protocol_fees[index])
+    }
+}
+
+/// The amp ramp of a StableSwap pool, shared by `Pool` and `BasketPool` so that both follow the same rules
+pub trait AmpRamp {
+    /// The curve the pool trades on, only StableSwap pools have an amp to ramp
+    fn curve_type(&self) -> CurveType;
+
+    /// `(amp, target_amp, amp_ramp_start, amp_ramp_end)` of the current ramp
+    fn amp_ramp(&self) -> (u64, u64, i64, i64);
+
+    /// Replaces the ramp with one from `amp` at `ramp_start` to `target_amp` at `ramp_end`
+    fn set_amp_ramp(&mut self, amp: u64, target_amp: u64, ramp_start: i64, ramp_end: i64);
+
+    /// The amplification coefficient at `now`, linearly interpolated during a ramp
+    fn current_amp(&self, now: i64) -> u64 {
+        let (amp, target_amp, ramp_start, ramp_end) = self.amp_ramp();
+        if now >= ramp_end {
+            return target_amp;
+        }
+        if now <= ramp_start {
+            return amp;
+        }
+
+        // amp + (target_amp - amp) * elapsed / duration，两个方向分开算，避免有符号运算
+        let elapsed = (now - ramp_start) as u128;
+        let duration = (ramp_end - ramp_start) as u128;
+        if target_amp >= amp {
+            amp + ((target_amp - amp) as u128 * elapsed / duration) as u64
+        } else {
+            amp - ((amp - target_amp) as u128 * elapsed / duration) as u64
+        }
+    }
+}
+
+impl AmpRamp for Pool {
+    fn curve_type(&self) -> CurveType {
+        self.curve_type
+    }
+
+    fn amp_ramp(&self) -> (u64, u64, i64, i64) {
+        (self.amp, self.target_amp, self.amp_ramp_start, self.amp_ramp_end)
+    }
+
+    fn set_amp_ramp(&mut self, amp: u64, target_amp: u64, ramp_start: i64, ramp_end: i64) {
+        self.amp = amp;
+        self.target_amp = target_amp;
+        self.amp_ramp_start = ramp_start;
+        self.amp_ramp_end = ramp_end;
+    }
+}
+
+impl AmpRamp for BasketPool {
+    fn curve_type(&self) -> CurveType {
+        self.curve_type
+    }
+
+    fn amp_ramp(&self) -> (u64, u64, i64, i64) {
+        (self.amp, self.target_amp, self.amp_ramp_start, self.amp_ramp_end)
+    }
+
+    fn set_amp_ramp(&mut self, amp: u64, target_amp: u64, ramp_start: i64, ramp_end: i64) {
+        self.amp = amp;
+        self.target_amp = target_amp;
+        self.amp_ramp_start = ramp_start;
+        self.amp_ramp_end = ramp_end;
+    }
+}
+
+#[account]
+#[derive(Default)]
+pub struct ClmmPool {
+    /// Primary key of the AMM
+    pub amm: Pubkey,
+    /// Mint of token A
+    pub mint_a: Pubkey,
+    /// Mint of token B
+    pub mint_b: Pubkey,
+    /// Positions can only be bounded by ticks that are multiples of the spacing
+    pub tick_spacing: u16,
+    /// Square root of the price of token A in token B, as a Q64.64 fixed-point number
+    pub sqrt_price: u128,
+    /// Index of the tick the current price is in
+    pub tick: i32,
+    /// Total liquidity of the positions whose range contains the current price
+    pub liquidity: u128,
+    /// Token A fees earned per unit of liquidity over the life of the pool, as a wrapping Q64.64 number
+    pub fee_growth_global_a: u128,
+    /// Token B fees earned per unit of liquidity over the life of the pool, as a wrapping Q64.64 number
+    pub fee_growth_global_b: u128,
+    /// Protocol fees in token A held by the pool vault but not owed to the positions
+    pub protocol_fees_a: u64,
+    /// Protocol fees in token B held by the pool vault but not owed to the positions
+    pub protocol_fees_b: u64,
+    /// Blocks swaps and adding liquidity in this pool
+    pub paused: bool,
+    /// Time-weighted sum of the price of token B in token A (A per B), as a wrapping Q64.64 number
+    pub price_a_per_b_cumulative: u128,
+    /// Time-weighted sum of the price of token A in token B (B per A), as a wrapping Q64.64 number
+    pub price_b_per_a_cumulative: u128,
+    /// Unix timestamp at which the price accumulators were last updated
+    pub last_price_update: i64,
+    /// Set by `initialize_clmm_oracle`, every swap must then pass the oracle
+    pub has_oracle: bool,
+}
+
+impl ClmmPool {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 2 + 16 + 4 + 16 + 16 + 16 + 8 + 8 + 1 + 16 + 16 + 8 + 1;
+
+    /// Adds the current price held since the last update, weighted by the elapsed seconds
+    pub fn update_price_cumulative(&mut self, now: i64) {
+        if now <= self.last_price_update {
+            return;
+        }
+        // 价格只在swap时改变，所以在swap改变sqrt_price之前累加，和Pool一样累加器允许溢出回绕
+        // B per A = sqrt_price^2，A per B = 1 / sqrt_price^2，都是Q64.64；sqrt_price不超过MAX_SQRT_PRICE，U256不会溢出
+        let elapsed = U256::from((now - self.last_price_update) as u64);
+        let price_squared = U256::from(self.sqrt_price) * U256::from(self.sqrt_price);
+        let b_per_a = (price_squared >> 64) * elapsed;
+        let a_per_b = (U256::one() << 192) / price_squared * elapsed;
+        self.price_a_per_b_cumulative = self.price_a_per_b_cumulative.wrapping_add(a_per_b.low_u128());
+        self.price_b_per_a_cumulative = self.price_b_per_a_cumulative.wrapping_add(b_per_a.low_u128());
+        self.last_price_update = now;
+    }
+
+    /// The price accumulators at their last update
+    pub fn observation(&self) -> Observation {
+        Observation {
+            timestamp: self.last_price_update,
+            price_a_per_b_cumulative: self.price_a_per_b_cumulative,
+            price_b_per_a_cumulative: self.price_b_per_a_cumulative,
+        }
+    }
+
+    /// Fees earned per unit of liquidity between `tick_lower` and `tick_upper`, in token A and token B
+    pub fn fee_growth_inside(
+        &self,
+        lower: &Tick,
+        tick_lower: i32,
+        upper: &Tick,
+        tick_upper: i32,
+    ) -> (u128, u128) {
+        // 每个tick记录的是"外侧"的增长：当前价格在tick之上时外侧是下方，否则是上方（Uniswap v3的约定）
+        // 区间内的增长 = 全部的增长 - 下界以下的增长 - 上界以上的增长，都是回绕运算
+        let inside = |global: u128, lower_outside: u128, upper_outside: u128| {
+            let below = if self.tick >= tick_lower {
+                lower_outside
+            } else {
+                global.wrapping_sub(lower_outside)
+            };
+            let above = if self.tick < tick_upper {
+                upper_outside
+            } else {
+                global.wrapping_sub(upper_outside)
+            };
+            global.wrapping_sub(below).wrapping_sub(above)
+        };
+        (
+            inside(self.fee_growth_global_a, lower.fee_growth_outside_a, upper.fee_growth_outside_a),
+            inside(self.fee_growth_global_b, lower.fee_growth_outside_b, upper.fee_growth_outside_b),
+        )
+    }
+}
+
+/// A tick that bounds at least one position of a concentrated liquidity pool
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Tick {
+    /// Liquidity added to the pool when the price crosses the tick upwards, removed when crossing downwards
+    pub liquidity_net: i128,
+    /// Total liquidity of the positions bounded by the tick, 0 when the tick is not initialized
+    pub liquidity_gross: u128,
+    /// Token A fee growth on the other side of the tick from the current price
+    pub fee_growth_outside_a: u128,
+    /// Token B fee growth on the other side of the tick from the current price
+    pub fee_growth_outside_b: u128,
+}
+
+impl Tick {
+    pub const LEN: usize = 16 + 16 + 16 + 16;
+
+    /// Adds `liquidity_delta` of a position whose lower (or upper) bound is this tick
+    pub fn update(
+        &mut self,
+        tick_index: i32,
+        pool: &ClmmPool,
+        liquidity_delta: i128,
+        upper: bool,
+    ) -> Result<()> {
+        // 第一次被用到时，约定tick以下的增长全部发生在"外侧"（当前价格在tick之上时）
+        if self.liquidity_gross == 0 && tick_index <= pool.tick {
+            self.fee_growth_outside_a = pool.fee_growth_global_a;
+            self.fee_growth_outside_b = pool.fee_growth_global_b;
+        }
+
+        self.liquidity_gross = tick_math::add_liquidity_delta(self.liquidity_gross, liquidity_delta)?;
+        // 价格向上穿过下界时流动性增加，穿过上界时减少
+        let liquidity_net = if upper {
+            self.liquidity_net.checked_sub(liquidity_delta)
+        } else {
+            self.liquidity_net.checked_add(liquidity_delta)
+        };
+        self.liquidity_net = liquidity_net.ok_or_else(|| error!(TutorialError::MathOverflow))?;
+        Ok(())
+    }
+
+    /// Resets a tick no position is bounded by anymore, once its fee growth has been read
+    pub fn clear_if_unused(&mut self) {
+        if self.liquidity_gross == 0 {
+            *self = Tick::default();
+        }
+    }
+
+    /// Flips the outside fee growth when the price crosses the tick, returning `liquidity_net`
+    pub fn cross(&mut self, fee_growth_global_a: u128, fee_growth_global_b: u128) -> i128 {
+        self.fee_growth_outside_a = fee_growth_global_a.wrapping_sub(self.fee_growth_outside_a);
+        self.fee_growth_outside_b = fee_growth_global_b.wrapping_sub(self.fee_growth_outside_b);
+        self.liquidity_net
+    }
+}
+
+#[account]
+#[derive(Default)]
+pub struct TickArray {
+    /// The concentrated liquidity pool the ticks belong to
+    pub pool: Pubkey,
+    /// Index of the first tick, a multiple of `TICK_ARRAY_SIZE * tick_spacing`
+    pub start_tick: i32,
+    /// `TICK_ARRAY_SIZE` ticks spaced by the pool tick spacing
+    pub ticks: Vec<Tick>,
+}
+
+impl TickArray {
+    pub const LEN: usize = 8 + 32 + 4 + 4 + Tick::LEN * TICK_ARRAY_SIZE;
+
+    /// Start tick of the array holding `tick` in a pool with the given spacing
+    pub fn start_tick_for(tick: i32, tick_spacing: u16) -> i32 {
+        let span = TICK_ARRAY_SIZE as i32 * tick_spacing as i32;
+        tick.div_euclid(span) * span
+    }
+
+    fn offset(&self, tick: i32, tick_spacing: u16) -> Result<usize> {
+        let spacing = tick_spacing as i32;
+        if tick < self.start_tick
+            || tick % spacing != 0
+            || (tick - self.start_tick) / spacing >= TICK_ARRAY_SIZE as i32
+        {
+            return err!(TutorialError::InvalidTickArray);
+        }
+        Ok(((tick - self.start_tick) / spacing) as usize)
+    }
+
+    /// The tick at index `tick`, which must lie in this array
+    pub fn tick_mut(&mut self, tick: i32, tick_spacing: u16) -> Result<&mut Tick> {
+        let offset = self.offset(tick, tick_spacing)?;
+        Ok(&mut self.ticks[offset])
+    }
+
+    /// The next initialized tick the price reaches from `current_tick` in the swap direction,
+    /// or the last tick of the array with `false` when none is initialized
+    pub fn next_initialized_tick(
+        &self,
+        current_tick: i32,
+        tick_spacing: u16,
+        swap_a: bool,
+    ) -> Result<(i32, bool)> {
+        let spacing = tick_spacing as i32;
+        // 价格下降时从current_tick所在的tick开始往下找，上升时从下一个tick开始往上找
+        let first = current_tick.div_euclid(spacing) * spacing + if swap_a { 0 } else { spacing };
+        let first_offset = self.offset(first, tick_spacing)?;
+
+        let tick_at = |offset: usize| self.start_tick + offset as i32 * spacing;
+        if swap_a {
+            for offset in (0..=first_offset).rev() {
+                if self.ticks[offset].liquidity_gross > 0 {
+                    return Ok((tick_at(offset), true));
+                }
+            }
+            Ok((self.start_tick, false))
+        } else {
+            for offset in first_offset..TICK_ARRAY_SIZE {
+                if self.ticks[offset].liquidity_gross > 0 {
+                    return Ok((tick_at(offset), true));
+                }
+            }
+            Ok((tick_at(TICK_ARRAY_SIZE - 1), false))
+        }
+    }
+}
+
+#[account]
+#[derive(Default)]
+pub struct Position {
+    /// The concentrated liquidity pool of the position
+    pub pool: Pubkey,
+    /// Account allowed to change the position and collect its fees
+    pub owner: Pubkey,
+    /// Lower bound of the price range, inclusive
+    pub tick_lower: i32,
+    /// Upper bound of the price range, exclusive
+    pub tick_upper: i32,
+    /// Liquidity the position provides while the price is in its range
+    pub liquidity: u128,
+    /// Token A fee growth inside the range when the fees were last settled
+    pub fee_growth_inside_a: u128,
+    /// Token B fee growth inside the range when the fees were last settled
+    pub fee_growth_inside_b: u128,
+    /// Settled token A fees not collected yet
+    pub fees_owed_a: u64,
+    /// Settled token B fees not collected yet
+    pub fees_owed_b: u64,
+}
+
+impl Position {
+    pub const LEN: usize = 8 + 32 + 32 + 4 + 4 + 16 + 16 + 16 + 8 + 8;
+
+    /// Settles the fees earned since the last update, then applies `liquidity_delta`
+    pub fn update(
+        &mut self,
+        liquidity_delta: i128,
+        fee_growth_inside_a: u128,
+        fee_growth_inside_b: u128,
+    ) -> Result<()> {
+        let fees_a = tick_math::fees_earned(
+            self.liquidity,
+            fee_growth_inside_a.wrapping_sub(self.fee_growth_inside_a),
+        )?;
+        let fees_b = tick_math::fees_earned(
+            self.liquidity,
+            fee_growth_inside_b.wrapping_sub(self.fee_growth_inside_b),
+        )?;
+        self.fees_owed_a = self.fees_owed_a.checked_add(fees_a).ok_or_else(|| error!(TutorialError::MathOverflow))?;
+        self.fees_owed_b = self.fees_owed_b.checked_add(fees_b).ok_or_else(|| error!(TutorialError::MathOverflow))?;
+        self.fee_growth_inside_a = fee_growth_inside_a;
+        self.fee_growth_inside_b = fee_growth_inside_b;
+
+        self.liquidity = tick_math::add_liquidity_delta(self.liquidity, liquidity_delta)?;
+        Ok(())
+    }
+}
+
+/// The price accumulators of a pool at one point in time
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Observation {
+    /// Unix timestamp of the observation
+    pub timestamp: i64,
+    /// `price_a_per_b_cumulative` of the pool at `timestamp`
+    pub price_a_per_b_cumulative: u128,
+    /// `price_b_per_a_cumulative` of the pool at `timestamp`
+    pub price_b_per_a_cumulative: u128,
+}
+
+impl Observation {
+    pub const LEN: usize = 8 + 16 + 16;
+}
+
+#[account]
+#[derive(Default)]
+pub struct Oracle {
+    /// The pool whose prices are observed, a `Pool` or a `ClmmPool`
+    pub pool: Pubkey,
+    /// Maximum number of observations kept, the oldest is overwritten once full
+    pub capacity: u16,
+    /// Index of the latest observation in `observations`
+    pub index: u16,
+    /// Ring buffer of observations, in chronological order starting after `index` once full
+    pub observations: Vec<Observation>,
+}
+
+impl Oracle {
+    /// Account size of an oracle keeping `capacity` observations
+    pub fn space(capacity: usize) -> usize {
+        8 + 32 + 2 + 2 + 4 + Observation::LEN * capacity
+    }
+
+    /// Records `observation`, the accumulators of the pool at their last update, at most one per second
+    pub fn write(&mut self, observation: Observation) {
+        if let Some(latest) = self.observations.get(self.index as usize) {
+            if latest.timestamp >= observation.timestamp {
+                return;
+            }
+        }
+
+        if self.observations.len() < self.capacity as usize {
+            self.observations.push(observation);
+            self.index = (self.observations.len() - 1) as u16;
+        } else {
+            self.index = (self.index + 1) % self.capacity;
+            self.observations[self.index as usize] = observation;
+        }
+    }
+
+    /// The accumulators at `target`, interpolated between the observations around it,
+    /// with `current` the accumulators of the pool brought up to now
+    pub fn cumulative_at(&self, target: i64, current: &Observation) -> Result<(u128, u128)> {
+        let points = || self.observations.iter().chain(std::iter::once(current));
+        let before = points()
+            .filter(|observation| observation.timestamp <= target)
+            .max_by_key(|observation| observation.timestamp)
+            .ok_or_else(|| error!(TutorialError::OracleWindowTooLong))?;
+        let after = match points()
+            .filter(|observation| observation.timestamp > target)
+            .min_by_key(|observation| observation.timestamp)
+        {
+            Some(after) if before.timestamp < target => after,
+            _ => return Ok((before.price_a_per_b_cumulative, before.price_b_per_a_cumulative)),
+        };
+
+        // 两次更新之间价格不变，累加器随时间线性增长，按时间比例插值
+        let elapsed = U256::from((target - before.timestamp) as u64);
+        let duration = U256::from((after.timestamp - before.timestamp) as u64);
+        let interpolate = |before: u128, after: u128| -> Result<u128> {
+            let delta = U256::from(after.wrapping_sub(before)) * elapsed / duration;
+            Ok(before.wrapping_add(math::u256_to_u128(delta)?))
+        };
+        Ok((
+            interpolate(before.price_a_per_b_cumulative, after.price_a_per_b_cumulative)?,
+            interpolate(before.price_b_per_a_cumulative, after.price_b_per_a_cumulative)?,
+        ))
+    }
+}
@@ -0,0 +1,339 @@
+use anchor_lang::prelude::*;
+use fixed::types::I64F64;
+
+use crate::{
+    constants::{MAX_SQRT_PRICE, MAX_TICK, MIN_SQRT_PRICE, MIN_TICK},
+    errors::TutorialError,
+    math::{self, U256},
+};
+
+// 集中流动性（Uniswap v3的写法）：
+// 价格 = 代币B / 代币A，tick i 对应的价格是 1.0001^i
+// 池子里保存的是价格的平方根 sqrt_price，用Q64.64定点数表示（sqrt_price / 2^64 才是真实值）
+// 在一个价格区间内，流动性L满足：
+//   代币A的数量 = L * (1 / sqrt_p - 1 / sqrt_upper)
+//   代币B的数量 = L * (sqrt_p - sqrt_lower)
+// 所有取整都对池子有利：用户付的向上取整，用户拿走的向下取整
+
+// 2^128 / sqrt(1.0001)^(2^i)，Q128.128定点数
+const SQRT_PRICE_FACTORS: [u128; 19] = [
+    0xfffcb933bd6fad37aa2d162d1a594001,
+    0xfff97272373d413259a46990580e2139,
+    0xfff2e50f5f656932ef12357cf3c7fdcb,
+    0xffe5caca7e10e4e61c3624eaa0941ccf,
+    0xffcb9843d60f6159c9db58835c926643,
+    0xff973b41fa98c081472e6896dfb254bf,
+    0xff2ea16466c96a3843ec78b326b52860,
+    0xfe5dee046a99a2a811c461f1969c3052,
+    0xfcbe86c7900a88aedcffc83b479aa3a3,
+    0xf987a7253ac413176f2b074cf7815e53,
+    0xf3392b0822b70005940c7a398e4b70f2,
+    0xe7159475a2c29b7443b29c7fa6e889d8,
+    0xd097f3bdfd2022b8845ad8f792aa5825,
+    0xa9f746462d870fdf8a65dc1f90e061e4,
+    0x70d869a156d2a1b890bb3df62baf32f6,
+    0x31be135f97d08fd981231505542fcfa5,
+    0x9aa508b5b7a84e1c677de54f3e99bc8,
+    0x5d6af8dedb81196699c329225ee604,
+    0x2216e584f5fa1ea926041bedfe97,
+];
+
+// log2(1.0001)，I64F64
+const LOG2_TICK_BASE: I64F64 = I64F64::from_bits(0x97451d28210c5);
+
+// tick对应的sqrt_price：把|tick|拆成二进制位，每一位乘上对应的 1 / sqrt(1.0001)^(2^i)
+// 正的tick取倒数，最后从Q128.128转成Q64.64，向上取整
+pub fn sqrt_price_at_tick(tick: i32) -> Result<u128> {
+    if !(MIN_TICK..=MAX_TICK).contains(&tick) {
+        return err!(TutorialError::InvalidTickRange);
+    }
+
+    let abs_tick = tick.unsigned_abs();
+    let mut ratio = U256::one() << 128;
+    for (bit, &factor) in SQRT_PRICE_FACTORS.iter().enumerate() {
+        if abs_tick & (1 << bit) != 0 {
+            ratio = (ratio * U256::from(factor)) >> 128;
+        }
+    }
+    if tick > 0 {
+        ratio = U256::MAX / ratio;
+    }
+
+    let (sqrt_price, remainder) = ratio.div_mod(U256::one() << 64);
+    let sqrt_price = if remainder.is_zero() {
+        sqrt_price
+    } else {
+        sqrt_price + 1
+    };
+    math::u256_to_u128(sqrt_price)
+}
+
+// sqrt_price所在的tick：满足 sqrt_price_at_tick(tick) <= sqrt_price 的最大tick
+// 先用log2估算 tick = 2 * log2(sqrt_price) / log2(1.0001)，误差不超过1，再用sqrt_price_at_tick校正
+pub fn tick_at_sqrt_price(sqrt_price: u128) -> Result<i32> {
+    if !(MIN_SQRT_PRICE..=MAX_SQRT_PRICE).contains(&sqrt_price) {
+        return err!(TutorialError::InvalidSqrtPrice);
+    }
+
+    // log2只接受u64，取sqrt_price最高的64位
+    let shift = (128 - sqrt_price.leading_zeros()).saturating_sub(64);
+    let log2_sqrt_price =
+        math::log2((sqrt_price >> shift) as u64) + I64F64::from_num(shift) - I64F64::from_num(64);
+    let estimate = math::checked_div(log2_sqrt_price * 2, LOG2_TICK_BASE)?.floor();
+
+    let mut tick = estimate.to_num::<i32>().clamp(MIN_TICK, MAX_TICK);
+    while tick > MIN_TICK && sqrt_price_at_tick(tick)? > sqrt_price {
+        tick -= 1;
+    }
+    while tick < MAX_TICK && sqrt_price_at_tick(tick + 1)? <= sqrt_price {
+        tick += 1;
+    }
+    Ok(tick)
+}
+
+fn div_round(numerator: U256, denominator: U256, round_up: bool) -> Result<U256> {
+    if denominator.is_zero() {
+        return err!(TutorialError::DivisionByZero);
+    }
+    let (quotient, remainder) = numerator.div_mod(denominator);
+    if round_up && !remainder.is_zero() {
+        Ok(quotient + 1)
+    } else {
+        Ok(quotient)
+    }
+}
+
+// 价格从sqrt_price_lower变到sqrt_price_upper时代币A的变化量：
+// L * (sqrt_upper - sqrt_lower) / (sqrt_upper * sqrt_lower)
+pub fn amount_a_delta(
+    sqrt_price_lower: u128,
+    sqrt_price_upper: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<u128> {
+    let numerator = (U256::from(liquidity) << 64)
+        .checked_mul(U256::from(sqrt_price_upper - sqrt_price_lower))
+        .ok_or_else(|| error!(TutorialError::MathOverflow))?;
+    let denominator = U256::from(sqrt_price_upper) * U256::from(sqrt_price_lower);
+    math::u256_to_u128(div_round(numerator, denominator, round_up)?)
+}
+
+// 价格从sqrt_price_lower变到sqrt_price_upper时代币B的变化量：L * (sqrt_upper - sqrt_lower)
+pub fn amount_b_delta(
+    sqrt_price_lower: u128,
+    sqrt_price_upper: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<u128> {
+    let numerator = U256::from(liquidity) * U256::from(sqrt_price_upper - sqrt_price_lower);
+    math::u256_to_u128(div_round(numerator, U256::one() << 64, round_up)?)
+}
+
+fn to_u64(amount: u128) -> Result<u64> {
+    u64::try_from(amount).map_err(|_| error!(TutorialError::MathOverflow))
+}
+
+// 在[sqrt_price_lower, sqrt_price_upper)区间内提供liquidity需要的代币A、B
+// 当前价格低于区间时只需要代币A，高于区间时只需要代币B
+pub fn amounts_for_liquidity(
+    sqrt_price: u128,
+    sqrt_price_lower: u128,
+    sqrt_price_upper: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<(u64, u64)> {
+    let (amount_a, amount_b) = if sqrt_price <= sqrt_price_lower {
+        (amount_a_delta(sqrt_price_lower, sqrt_price_upper, liquidity, round_up)?, 0)
+    } else if sqrt_price >= sqrt_price_upper {
+        (0, amount_b_delta(sqrt_price_lower, sqrt_price_upper, liquidity, round_up)?)
+    } else {
+        (
+            amount_a_delta(sqrt_price, sqrt_price_upper, liquidity, round_up)?,
+            amount_b_delta(sqrt_price_lower, sqrt_price, liquidity, round_up)?,
+        )
+    };
+    Ok((to_u64(amount_a)?, to_u64(amount_b)?))
+}
+
+// 最多存入amount_a、amount_b时能提供的最大流动性，向下取整
+pub fn liquidity_for_amounts(
+    sqrt_price: u128,
+    sqrt_price_lower: u128,
+    sqrt_price_upper: u128,
+    amount_a: u64,
+    amount_b: u64,
+) -> Result<u128> {
+    // L = amount_a * sqrt_from * sqrt_upper / (sqrt_upper - sqrt_from)
+    let liquidity_a = |sqrt_price_from: u128| -> Result<u128> {
+        let product = (U256::from(sqrt_price_from) * U256::from(sqrt_price_upper)) >> 64;
+        let numerator = U256::from(amount_a) * product;
+        math::u256_to_u128(div_round(numerator, U256::from(sqrt_price_upper - sqrt_price_from), false)?)
+    };
+    // L = amount_b / (sqrt_to - sqrt_lower)
+    let liquidity_b = |sqrt_price_to: u128| -> Result<u128> {
+        let numerator = U256::from(amount_b) << 64;
+        math::u256_to_u128(div_round(numerator, U256::from(sqrt_price_to - sqrt_price_lower), false)?)
+    };
+
+    if sqrt_price <= sqrt_price_lower {
+        liquidity_a(sqrt_price_lower)
+    } else if sqrt_price >= sqrt_price_upper {
+        liquidity_b(sqrt_price_upper)
+    } else {
+        Ok(liquidity_a(sqrt_price)?.min(liquidity_b(sqrt_price)?))
+    }
+}
+
+// 在流动性L下存入amount个输入代币之后的sqrt_price
+// 输入A（价格下降）：sqrt_p' = L / (L / sqrt_p + amount)，向上取整
+// 输入B（价格上升）：sqrt_p' = sqrt_p + amount / L，向下取整
+pub fn next_sqrt_price_from_input(
+    sqrt_price: u128,
+    liquidity: u128,
+    amount: u64,
+    swap_a: bool,
+) -> Result<u128> {
+    let liquidity_x64 = U256::from(liquidity) << 64;
+    if swap_a {
+        let denominator = liquidity_x64 / U256::from(sqrt_price) + U256::from(amount);
+        math::u256_to_u128(div_round(liquidity_x64, denominator, true)?)
+    } else {
+        let delta = div_round(U256::from(amount) << 64, U256::from(liquidity), false)?;
+        math::u256_to_u128(U256::from(sqrt_price) + delta)
+    }
+}
+
+// 一次交换在一个tick区间内的结果
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapStep {
+    pub sqrt_price_next: u128,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee_amount: u64,
+}
+
+// 从sqrt_price向sqrt_price_target交换，最多用掉amount_remaining个输入代币（含手续费）
+// 输入足够时价格到达target，否则价格停在中间，输入全部用完
+pub fn compute_swap_step(
+    sqrt_price: u128,
+    sqrt_price_target: u128,
+    liquidity: u128,
+    amount_remaining: u64,
+    fee: u16,
+    swap_a: bool,
+) -> Result<SwapStep> {
+    let amount_less_fee = math::mul_div(amount_remaining, 10000 - fee as u64, 10000)?;
+
+    // 输入代币的变化量向上取整，输出代币的向下取整
+    let amount_in_for = |sqrt_price_next: u128| {
+        if swap_a {
+            amount_a_delta(sqrt_price_next, sqrt_price, liquidity, true)
+        } else {
+            amount_b_delta(sqrt_price, sqrt_price_next, liquidity, true)
+        }
+    };
+    let amount_in_to_target = amount_in_for(sqrt_price_target)?;
+
+    let sqrt_price_next = if amount_less_fee as u128 >= amount_in_to_target {
+        sqrt_price_target
+    } else {
+        next_sqrt_price_from_input(sqrt_price, liquidity, amount_less_fee, swap_a)?
+    };
+    let reached_target = sqrt_price_next == sqrt_price_target;
+
+    let amount_in = if reached_target {
+        to_u64(amount_in_to_target)?
+    } else {
+        to_u64(amount_in_for(sqrt_price_next)?)?
+    };
+    let amount_out = to_u64(if swap_a {
+        amount_b_delta(sqrt_price_next, sqrt_price, liquidity, false)?
+    } else {
+        amount_a_delta(sqrt_price, sqrt_price_next, liquidity, false)?
+    })?;
+
+    // 到达target时按实际输入收手续费，否则剩下的输入全部是手续费（只差取整的几个单位）
+    let fee_amount = if reached_target {
+        math::mul_div_ceil(amount_in, fee as u64, 10000 - fee as u64)?
+    } else {
+        amount_remaining
+            .checked_sub(amount_in)
+            .ok_or_else(|| error!(TutorialError::MathOverflow))?
+    };
+
+    Ok(SwapStep {
+        sqrt_price_next,
+        amount_in,
+        amount_out,
+        fee_amount,
+    })
+}
+
+// 手续费按流动性分摊：每单位流动性赚到的手续费，Q64.64
+pub fn fee_growth(fee_amount: u64, liquidity: u128) -> Result<u128> {
+    if liquidity == 0 {
+        return Ok(0);
+    }
+    math::u256_to_u128(div_round(U256::from(fee_amount) << 64, U256::from(liquidity), false)?)
+}
+
+// 流动性为liquidity的仓位在fee_growth增长了growth_delta期间赚到的手续费，向下取整
+pub fn fees_earned(liquidity: u128, growth_delta: u128) -> Result<u64> {
+    let fees = (U256::from(liquidity) * U256::from(growth_delta)) >> 64;
+    math::u256_to_u64(fees)
+}
+
+// 流动性加上一个有符号的变化量，结果不能为负
+pub fn add_liquidity_delta(liquidity: u128, delta: i128) -> Result<u128> {
+    if delta >= 0 {
+        liquidity.checked_add(delta as u128)
+    } else {
+        liquidity.checked_sub(delta.unsigned_abs())
+    }
+    .ok_or_else(|| error!(TutorialError::MathOverflow))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sqrt_price_at_tick_bounds() {
+        assert_eq!(sqrt_price_at_tick(0).unwrap(), 1 << 64);
+        assert_eq!(sqrt_price_at_tick(MIN_TICK).unwrap(), MIN_SQRT_PRICE);
+        assert_eq!(sqrt_price_at_tick(MAX_TICK).unwrap(), MAX_SQRT_PRICE);
+        assert_eq!(sqrt_price_at_tick(MIN_TICK - 1), Err(TutorialError::InvalidTickRange.into()));
+        assert_eq!(sqrt_price_at_tick(MAX_TICK + 1), Err(TutorialError::InvalidTickRange.into()));
+    }
+
+    #[test]
+    fn sqrt_price_at_tick_is_increasing() {
+        for start in [MIN_TICK, -50, MAX_TICK - 100] {
+            for tick in start..start + 100 {
+                assert!(sqrt_price_at_tick(tick + 1).unwrap() > sqrt_price_at_tick(tick).unwrap(), "tick {tick}");
+            }
+        }
+    }
+
+    #[test]
+    fn tick_at_sqrt_price_round_trip() {
+        assert_eq!(tick_at_sqrt_price(MIN_SQRT_PRICE).unwrap(), MIN_TICK);
+        assert_eq!(tick_at_sqrt_price(MAX_SQRT_PRICE).unwrap(), MAX_TICK);
+        assert_eq!(tick_at_sqrt_price(MIN_SQRT_PRICE - 1), Err(TutorialError::InvalidSqrtPrice.into()));
+        assert_eq!(tick_at_sqrt_price(MAX_SQRT_PRICE + 1), Err(TutorialError::InvalidSqrtPrice.into()));
+
+        for tick in (MIN_TICK..=MIN_TICK + 100)
+            .chain(-1000..=1000)
+            .chain([-200_000, -12_345, 54_321, 300_000])
+            .chain(MAX_TICK - 100..=MAX_TICK)
+        {
+            let sqrt_price = sqrt_price_at_tick(tick).unwrap();
+            assert_eq!(tick_at_sqrt_price(sqrt_price).unwrap(), tick);
+            // 到下一个tick之前的价格都属于这个tick
+            if tick < MAX_TICK {
+                let next = sqrt_price_at_tick(tick + 1).unwrap();
+                assert_eq!(tick_at_sqrt_price(next - 1).unwrap(), tick);
+            }
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::get_associated_token_address_with_program_id,
+    token_2022::spl_token_2022::{
+        self,
+        extension::{
+            transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions,
+        },
+    },
+    token_interface::{Mint, Token2022, TokenAccount},
+};
+
+use crate::{
+    errors::TutorialError,
+    state::{BasketPool, ClmmPool, Deadline, Position, TickArray},
+    tick_math,
+};
+
+// 交易在mempool里可能等很久才被打包，那时价格可能已经变了
+// deadline可以是unix时间戳或者slot，超过之后交易直接失败；None表示不检查
+// 时间戳由验证者投票决定，精度只有秒级；slot更精确，但换算成时间要估计出块速度
+pub fn check_deadline(deadline: Option<Deadline>) -> Result<()> {
+    let clock = Clock::get()?;
+    let expired = match deadline {
+        Some(Deadline::Timestamp { unix_timestamp }) => clock.unix_timestamp > unix_timestamp,
+        Some(Deadline::Slot { slot }) => clock.slot > slot,
+        None => false,
+    };
+    if expired {
+        return err!(TutorialError::DeadlineExceeded);
+    }
+
+    Ok(())
+}
+
+// Token-2022的transfer-fee扩展：转出amount时，接收方实际只收到amount - fee
+// 经典Token程序的mint、或者没有这个扩展的mint，手续费为0
+pub fn get_transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    if *mint.owner != Token2022::id() {
+        return Ok(0);
+    }
+
+    let mint_data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+    let fee = match mint.get_extension::<TransferFeeConfig>() {
+        Ok(transfer_fee_config) => transfer_fee_config
+            .calculate_epoch_fee(Clock::get()?.epoch, amount)
+            .ok_or_else(|| error!(TutorialError::MathOverflow))?,
+        Err(_) => 0,
+    };
+
+    Ok(fee)
+}
+
+// get_transfer_fee的反向：要让接收方正好收到post_fee_amount，转账时需要额外加上的手续费
+pub fn get_transfer_inverse_fee(mint: &AccountInfo, post_fee_amount: u64) -> Result<u64> {
+    if *mint.owner != Token2022::id() {
+        return Ok(0);
+    }
+
+    let mint_data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+    let fee = match mint.get_extension::<TransferFeeConfig>() {
+        Ok(transfer_fee_config) => transfer_fee_config
+            .calculate_inverse_epoch_fee(Clock::get()?.epoch, post_fee_amount)
+            .ok_or_else(|| error!(TutorialError::MathOverflow))?,
+        Err(_) => 0,
+    };
+
+    Ok(fee)
+}
+
+// 篮子池子存取款时每种代币在remaining_accounts里占用的账户数量：
+// [mint, vault, user_account, token_program]，按pool.mints的顺序排列
+pub const BASKET_TOKEN_ACCOUNTS: usize = 4;
+
+// 篮子池子里一种代币的账户
+pub struct BasketTokenAccounts<'info> {
+    pub mint: &'info AccountInfo<'info>,
+    pub vault: &'info AccountInfo<'info>,
+    pub user_account: &'info AccountInfo<'info>,
+    pub token_program: &'info AccountInfo<'info>,
+    pub decimals: u8,
+    // 这种代币属于LP的储备：vault的余额减去累积的协议手续费
+    pub reserve: u64,
+    // 用户代币账户的余额
+    pub balance: u64,
+}
+
+// 检查并加载篮子池子存取款用的remaining_accounts
+// vault必须是pool_authority的ATA，user_account必须属于user，token程序必须是mint的owner
+pub fn load_basket_tokens<'info>(
+    pool: &BasketPool,
+    pool_authority: &Pubkey,
+    user: &Pubkey,
+    accounts: &'info [AccountInfo<'info>],
+) -> Result<Vec<BasketTokenAccounts<'info>>> {
+    if accounts.len() != pool.mints.len() * BASKET_TOKEN_ACCOUNTS {
+        return err!(TutorialError::InvalidBasketAccounts);
+    }
+
+    let mut tokens = Vec::with_capacity(pool.mints.len());
+    for (index, (mint_key, accounts)) in pool.mints.iter().zip(accounts.chunks(BASKET_TOKEN_ACCOUNTS)).enumerate() {
+        let (mint, vault, user_account, token_program) =
+            (&accounts[0], &accounts[1], &accounts[2], &accounts[3]);
+        if mint.key() != *mint_key || token_program.key() != *mint.owner {
+            return err!(TutorialError::InvalidBasketAccounts);
+        }
+        let decimals = InterfaceAccount::<Mint>::try_from(mint)?.decimals;
+
+        if vault.key()
+            != get_associated_token_address_with_program_id(pool_authority, mint_key, token_program.key)
+        {
+            return err!(TutorialError::InvalidBasketAccounts);
+        }
+        let reserve = pool.reserve(index, InterfaceAccount::<TokenAccount>::try_from(vault)?.amount)?;
+
+        let user_token_account = InterfaceAccount::<TokenAccount>::try_from(user_account)?;
+        if user_token_account.owner != *user || user_token_account.mint != *mint_key {
+            return err!(TutorialError::InvalidBasketAccounts);
+        }
+
+        tokens.push(BasketTokenAccounts {
+            mint,
+            vault,
+            user_account,
+            token_program,
+            decimals,
+            reserve,
+            balance: user_token_account.amount,
+        });
+    }
+
+    Ok(tokens)
+}
+
+// 篮子池子交换时只需要读所有vault的储备（StableSwap的D和每种代币的储备都有关），协议手续费不计入
+// remaining_accounts按pool.mints的顺序放每种代币的vault
+pub fn load_basket_reserves<'info>(
+    pool: &BasketPool,
+    pool_authority: &Pubkey,
+    vaults: &'info [AccountInfo<'info>],
+) -> Result<Vec<u64>> {
+    if vaults.len() != pool.mints.len() {
+        return err!(TutorialError::InvalidBasketAccounts);
+    }
+
+    let mut reserves = Vec::with_capacity(vaults.len());
+    for (index, (mint, vault)) in pool.mints.iter().zip(vaults).enumerate() {
+        // vault的owner就是它所属的token程序，ATA地址和token程序有关
+        if vault.key() != get_associated_token_address_with_program_id(pool_authority, mint, vault.owner) {
+            return err!(TutorialError::InvalidBasketAccounts);
+        }
+        reserves.push(pool.reserve(index, InterfaceAccount::<TokenAccount>::try_from(vault)?.amount)?);
+    }
+
+    Ok(reserves)
+}
+
+// 改变集中流动性仓位的流动性（liquidity_delta为0时只结算手续费）：
+// 先更新上下界的tick，再按区间内的手续费增长结算仓位的手续费，最后更新池子当前的流动性
+// 上下界可能在同一个tick array里，这时两个账户是同一个：只改tick_array_lower，最后复制给tick_array_upper，
+// 否则两份数据写回同一个账户时后写的会覆盖先写的
+pub fn update_position(
+    pool: &mut ClmmPool,
+    position: &mut Position,
+    tick_array_lower: &mut TickArray,
+    tick_array_upper: &mut TickArray,
+    same_tick_array: bool,
+    liquidity_delta: i128,
+) -> Result<()> {
+    let (tick_lower, tick_upper) = (position.tick_lower, position.tick_upper);
+    let tick_spacing = pool.tick_spacing;
+
+    let mut lower = *tick_array_lower.tick_mut(tick_lower, tick_spacing)?;
+    let upper_array = if same_tick_array { &mut *tick_array_lower } else { &mut *tick_array_upper };
+    let mut upper = *upper_array.tick_mut(tick_upper, tick_spacing)?;
+
+    if liquidity_delta != 0 {
+        lower.update(tick_lower, pool, liquidity_delta, false)?;
+        upper.update(tick_upper, pool, liquidity_delta, true)?;
+    }
+
+    let (fee_growth_inside_a, fee_growth_inside_b) =
+        pool.fee_growth_inside(&lower, tick_lower, &upper, tick_upper);
+    position.update(liquidity_delta, fee_growth_inside_a, fee_growth_inside_b)?;
+
+    lower.clear_if_unused();
+    upper.clear_if_unused();
+    *tick_array_lower.tick_mut(tick_lower, tick_spacing)? = lower;
+    let upper_array = if same_tick_array { &mut *tick_array_lower } else { &mut *tick_array_upper };
+    *upper_array.tick_mut(tick_upper, tick_spacing)? = upper;
+    if same_tick_array {
+        tick_array_upper.ticks.clone_from(&tick_array_lower.ticks);
+    }
+
+    // 当前价格在仓位的区间内时，池子的流动性跟着变
+    if tick_lower <= pool.tick && pool.tick < tick_upper {
+        pool.liquidity = tick_math::add_liquidity_delta(pool.liquidity, liquidity_delta)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,134 @@
+import * as anchor from "@coral-xyz/anchor";
+import type { Program } from "@coral-xyz/anchor";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { expect } from "chai";
+import { MyAmmProject } from "../target/types/my_amm_project";
+import { type TestValues, createTestValues, expectRevert } from "./utils";
+
+describe("Admin transfer", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.MyAmmProject as Program<MyAmmProject>;
+
+  let values: TestValues;
+  let newAdmin: Keypair;
+
+  beforeEach(async () => {
+    values = createTestValues();
+    newAdmin = Keypair.generate();
+
+    await program.methods
+      .createAmm(values.id, values.fee, new anchor.BN(values.feeDelay))
+      .accounts({
+        amm: values.ammPda,
+        admin: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+  });
+
+  it("Success: Propose and accept a new admin", async () => {
+    await program.methods
+      .proposeAdmin(newAdmin.publicKey)
+      .accounts({
+        amm: values.ammPda,
+        admin: values.admin.publicKey,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+    let ammAccount = await program.account.amm.fetch(values.ammPda);
+    // 新admin接受之前，admin不变
+    expect(ammAccount.admin.toString()).to.equal(
+      values.admin.publicKey.toString()
+    );
+    expect(ammAccount.pendingAdmin.toString()).to.equal(
+      newAdmin.publicKey.toString()
+    );
+
+    await program.methods
+      .acceptAdmin()
+      .accounts({
+        amm: values.ammPda,
+        pendingAdmin: newAdmin.publicKey,
+      } as any)
+      .signers([newAdmin])
+      .rpc();
+
+    ammAccount = await program.account.amm.fetch(values.ammPda);
+    expect(ammAccount.admin.toString()).to.equal(newAdmin.publicKey.toString());
+    expect(ammAccount.pendingAdmin.toString()).to.equal(
+      PublicKey.default.toString()
+    );
+  });
+
+  it("Failure: Accept without being the pending admin", async () => {
+    const attacker = Keypair.generate();
+
+    await program.methods
+      .proposeAdmin(newAdmin.publicKey)
+      .accounts({
+        amm: values.ammPda,
+        admin: values.admin.publicKey,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+    await expectRevert(
+      program.methods
+        .acceptAdmin()
+        .accounts({
+          amm: values.ammPda,
+          pendingAdmin: attacker.publicKey,
+        } as any)
+        .signers([attacker])
+        .rpc()
+    );
+  });
+
+  it("Failure: Accept after the transfer was cancelled", async () => {
+    await program.methods
+      .proposeAdmin(newAdmin.publicKey)
+      .accounts({
+        amm: values.ammPda,
+        admin: values.admin.publicKey,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+    await program.methods
+      .cancelAdminTransfer()
+      .accounts({
+        amm: values.ammPda,
+        admin: values.admin.publicKey,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+    await expectRevert(
+      program.methods
+        .acceptAdmin()
+        .accounts({
+          amm: values.ammPda,
+          pendingAdmin: newAdmin.publicKey,
+        } as any)
+        .signers([newAdmin])
+        .rpc()
+    );
+  });
+
+  it("Failure: Propose from a non-admin", async () => {
+    await expectRevert(
+      program.methods
+        .proposeAdmin(newAdmin.publicKey)
+        .accounts({
+          amm: values.ammPda,
+          admin: newAdmin.publicKey,
+        } as any)
+        .signers([newAdmin])
+        .rpc()
+    );
+  });
+});
@@ -0,0 +1,518 @@
+import * as anchor from "@coral-xyz/anchor";
+import type { Program } from "@coral-xyz/anchor";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import {
+  createMint,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+  transfer,
+} from "@solana/spl-token";
+import { expect } from "chai";
+import { BN } from "bn.js";
+import { MyAmmProject } from "../target/types/my_amm_project";
+import {
+  type TestValues,
+  createTestValues,
+  expectRevert,
+} from "./utils";
+
+describe("Basket Pool", () => {
+  const provider = anchor.AnchorProvider.env();
+  const connection = provider.connection;
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.MyAmmProject as Program<MyAmmProject>;
+  const programId = program.programId;
+
+  let values: TestValues;
+  // 三种代币按pubkey升序排列，和pool.mints的顺序一致
+  let mints: PublicKey[];
+  let basketId: PublicKey;
+  let poolPda: PublicKey;
+  let poolAuthority: PublicKey;
+  let mintLiquidity: PublicKey;
+
+  const amp = 100;
+  const initialAmount = 1000 * 10 ** 6;
+
+  const vault = (mint: PublicKey) =>
+    getAssociatedTokenAddressSync(mint, poolAuthority, true);
+  const userAccount = (mint: PublicKey) =>
+    getAssociatedTokenAddressSync(mint, values.admin.publicKey, true);
+
+  // 存取款时每种代币在remaining_accounts里的账户：[mint, vault, user_account, token_program]
+  const liquidityAccounts = () =>
+    mints.flatMap((mint) => [
+      { pubkey: mint, isWritable: false, isSigner: false },
+      { pubkey: vault(mint), isWritable: true, isSigner: false },
+      { pubkey: userAccount(mint), isWritable: true, isSigner: false },
+      {
+        pubkey: anchor.utils.token.TOKEN_PROGRAM_ID,
+        isWritable: false,
+        isSigner: false,
+      },
+    ]);
+
+  // 交换时remaining_accounts按顺序放每种代币的vault
+  const vaultAccounts = () =>
+    mints.map((mint) => ({
+      pubkey: vault(mint),
+      isWritable: true,
+      isSigner: false,
+    }));
+
+  const createBasketPool = (curveType: any, ampValue: number) =>
+    program.methods
+      .createBasketPool(basketId, mints.length, curveType, new anchor.BN(ampValue))
+      .accounts({
+        amm: values.ammPda,
+        pool: poolPda,
+        poolAuthority,
+        mintLiquidity,
+        payer: provider.wallet.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .remainingAccounts(
+        mints.flatMap((mint) => [
+          { pubkey: mint, isWritable: false, isSigner: false },
+          { pubkey: vault(mint), isWritable: true, isSigner: false },
+          {
+            pubkey: anchor.utils.token.TOKEN_PROGRAM_ID,
+            isWritable: false,
+            isSigner: false,
+          },
+        ])
+      )
+      .rpc();
+
+  const deposit = (amounts: number[], minLiquidityOut = 0) =>
+    program.methods
+      .depositBasketLiquidity(
+        amounts.map((amount) => new anchor.BN(amount)),
+        new anchor.BN(minLiquidityOut),
+        null
+      )
+      .accounts({
+        amm: values.ammPda,
+        pool: poolPda,
+        poolAuthority,
+        mintLiquidity,
+        depositorAccountLiquidity: userAccount(mintLiquidity),
+        depositor: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .remainingAccounts(liquidityAccounts())
+      .signers([values.admin])
+      .rpc();
+
+  const swap = (
+    inputIndex: number,
+    outputIndex: number,
+    inputAmount: number,
+    minOutputAmount = 0
+  ) =>
+    program.methods
+      .swapBasket(
+        inputIndex,
+        outputIndex,
+        new anchor.BN(inputAmount),
+        new anchor.BN(minOutputAmount),
+        null
+      )
+      .accounts({
+        amm: values.ammPda,
+        pool: poolPda,
+        poolAuthority,
+        trader: values.admin.publicKey,
+        mintIn: mints[inputIndex],
+        mintOut: mints[outputIndex],
+        traderAccountIn: userAccount(mints[inputIndex]),
+        traderAccountOut: userAccount(mints[outputIndex]),
+        payer: provider.wallet.publicKey,
+        tokenProgramIn: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramOut: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .remainingAccounts(vaultAccounts())
+      .signers([values.admin])
+      .rpc();
+
+  const balance = async (account: PublicKey) =>
+    Number((await getAccount(connection, account)).amount);
+
+  beforeEach(async () => {
+    // Setup: 生成测试数据
+    values = createTestValues();
+
+    // Setup: 创建AMM
+    await program.methods
+      .createAmm(values.id, values.fee, new anchor.BN(values.feeDelay))
+      .accounts({
+        amm: values.ammPda,
+        admin: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+
+    // Setup: 创建三种代币并给admin铸造
+    const signature = await connection.requestAirdrop(values.admin.publicKey, 10 ** 10);
+    const latestBlockhash = await connection.getLatestBlockhash();
+    await connection.confirmTransaction({
+      signature,
+      blockhash: latestBlockhash.blockhash,
+      lastValidBlockHeight: latestBlockhash.lastValidBlockHeight,
+    });
+
+    const keypairs = [Keypair.generate(), Keypair.generate(), Keypair.generate()];
+    for (const mint of keypairs) {
+      await createMint(
+        connection,
+        values.admin,
+        values.admin.publicKey,
+        values.admin.publicKey,
+        6,
+        mint
+      );
+      await getOrCreateAssociatedTokenAccount(
+        connection,
+        values.admin,
+        mint.publicKey,
+        values.admin.publicKey,
+        true
+      );
+      await mintTo(
+        connection,
+        values.admin,
+        mint.publicKey,
+        getAssociatedTokenAddressSync(mint.publicKey, values.admin.publicKey, true),
+        values.admin.publicKey,
+        10000 * 10 ** 6
+      );
+    }
+    mints = keypairs
+      .map((mint) => mint.publicKey)
+      .sort((x, y) => new BN(x.toBytes()).cmp(new BN(y.toBytes())));
+
+    // Setup: 篮子池子的PDA用id代替mint
+    basketId = Keypair.generate().publicKey;
+    poolPda = PublicKey.findProgramAddressSync(
+      [values.ammPda.toBuffer(), basketId.toBuffer()],
+      programId
+    )[0];
+    poolAuthority = PublicKey.findProgramAddressSync(
+      [values.ammPda.toBuffer(), basketId.toBuffer(), Buffer.from("authority")],
+      programId
+    )[0];
+    mintLiquidity = PublicKey.findProgramAddressSync(
+      [values.ammPda.toBuffer(), basketId.toBuffer(), Buffer.from("liquidity")],
+      programId
+    )[0];
+  });
+
+  it("Success: Create a StableSwap basket pool with three tokens", async () => {
+    await createBasketPool({ stableSwap: {} }, amp);
+
+    const pool = await program.account.basketPool.fetch(poolPda);
+    expect(pool.amm.toString()).to.equal(values.ammPda.toString());
+    expect(pool.id.toString()).to.equal(basketId.toString());
+    expect(pool.curveType).to.deep.equal({ stableSwap: {} });
+    expect(pool.amp.toNumber()).to.equal(amp);
+    expect(pool.mints.map((mint) => mint.toString())).to.deep.equal(
+      mints.map((mint) => mint.toString())
+    );
+
+    // 每种代币的vault都已经创建
+    for (const mint of mints) {
+      expect(await balance(vault(mint))).to.equal(0);
+    }
+  });
+
+  it("Failure: Mints out of order", async () => {
+    mints = [mints[1], mints[0], mints[2]];
+    await expectRevert(createBasketPool({ stableSwap: {} }, amp));
+  });
+
+  it("Failure: Too few tokens", async () => {
+    mints = mints.slice(0, 2);
+    await expectRevert(createBasketPool({ stableSwap: {} }, amp));
+  });
+
+  it("Failure: Weighted basket pools are not supported", async () => {
+    await expectRevert(createBasketPool({ weighted: {} }, 0));
+  });
+
+  it("Success: First deposit mints D minus the minimum liquidity", async () => {
+    await createBasketPool({ stableSwap: {} }, amp);
+    await deposit([initialAmount, initialAmount, initialAmount]);
+
+    // 平衡时 D = 所有代币储备之和
+    expect(await balance(userAccount(mintLiquidity))).to.equal(3 * initialAmount - 100);
+    for (const mint of mints) {
+      expect(await balance(vault(mint))).to.equal(initialAmount);
+    }
+  });
+
+  it("Success: Tokens donated before the first deposit become part of the seed", async () => {
+    await createBasketPool({ stableSwap: {} }, amp);
+
+    // 第一次存款之前有人直接往一个vault里转了代币，其他储备仍然为0
+    await transfer(
+      connection,
+      values.admin,
+      userAccount(mints[0]),
+      vault(mints[0]),
+      values.admin,
+      1
+    );
+    await deposit([initialAmount, initialAmount, initialAmount]);
+
+    // 捐赠的代币算进第一次存款，池子没有被卡住
+    expect(await balance(vault(mints[0]))).to.equal(initialAmount + 1);
+    expect(await balance(userAccount(mintLiquidity))).to.be.closeTo(3 * initialAmount - 100, 1);
+
+    // 之后的存款照常按比例铸造
+    const lpBefore = await balance(userAccount(mintLiquidity));
+    await deposit([initialAmount, initialAmount, initialAmount]);
+    expect(await balance(userAccount(mintLiquidity))).to.be.greaterThan(lpBefore);
+  });
+
+  it("Success: Later deposits are proportional to the reserves", async () => {
+    await createBasketPool({ constantProduct: {} }, 0);
+    await deposit([initialAmount, 2 * initialAmount, 4 * initialAmount]);
+    const lpBefore = await balance(userAccount(mintLiquidity));
+
+    // 第二种代币按比例只需要20，限制了这次存款
+    const before = await Promise.all(mints.map((mint) => balance(userAccount(mint))));
+    await deposit([100 * 10 ** 6, 20 * 10 ** 6, 100 * 10 ** 6]);
+    const after = await Promise.all(mints.map((mint) => balance(userAccount(mint))));
+
+    const spent = before.map((amount, i) => amount - after[i]);
+    expect(spent[0]).to.be.closeTo(10 * 10 ** 6, 1);
+    expect(spent[1]).to.be.closeTo(20 * 10 ** 6, 1);
+    expect(spent[2]).to.be.closeTo(40 * 10 ** 6, 1);
+
+    // LP按1%的比例铸造
+    const lpMinted = (await balance(userAccount(mintLiquidity))) - lpBefore;
+    expect(lpMinted).to.be.closeTo((lpBefore + 100) / 100, 1);
+  });
+
+  it("Failure: Deposit below the minimum liquidity", async () => {
+    await createBasketPool({ stableSwap: {} }, amp);
+    await expectRevert(
+      deposit([initialAmount, initialAmount, initialAmount], 3 * initialAmount)
+    );
+  });
+
+  it("Success: Swap between any two tokens of a stable basket", async () => {
+    await createBasketPool({ stableSwap: {} }, amp);
+    await deposit([initialAmount, initialAmount, initialAmount]);
+
+    const inputAmount = 100 * 10 ** 6;
+    const beforeIn = await balance(userAccount(mints[0]));
+    const beforeOut = await balance(userAccount(mints[2]));
+    const beforeOther = await balance(vault(mints[1]));
+
+    await swap(0, 2, inputAmount);
+
+    expect(beforeIn - (await balance(userAccount(mints[0])))).to.equal(inputAmount);
+    const output = (await balance(userAccount(mints[2]))) - beforeOut;
+
+    // 锚定价格附近滑点很小，输出接近扣掉手续费的输入
+    const taxedInput = (inputAmount * (10000 - values.fee)) / 10000;
+    expect(output).to.be.lessThanOrEqual(taxedInput);
+    expect(output).to.be.greaterThan(taxedInput * 0.99);
+
+    // 第三种代币不参与交换
+    expect(await balance(vault(mints[1]))).to.equal(beforeOther);
+  });
+
+  it("Success: Constant product basket swaps like a pair", async () => {
+    await createBasketPool({ constantProduct: {} }, 0);
+    await deposit([initialAmount, 2 * initialAmount, 4 * initialAmount]);
+
+    const inputAmount = 100 * 10 ** 6;
+    const beforeOut = await balance(userAccount(mints[1]));
+    await swap(2, 1, inputAmount);
+    const output = (await balance(userAccount(mints[1]))) - beforeOut;
+
+    const taxedInput = (inputAmount * (10000 - values.fee)) / 10000;
+    const expected = Math.floor(
+      (taxedInput * 2 * initialAmount) / (4 * initialAmount + taxedInput)
+    );
+    expect(output).to.be.closeTo(expected, 1);
+  });
+
+  it("Failure: Swap a token with itself", async () => {
+    await createBasketPool({ stableSwap: {} }, amp);
+    await deposit([initialAmount, initialAmount, initialAmount]);
+
+    await expectRevert(swap(1, 1, 10 * 10 ** 6));
+  });
+
+  it("Failure: Swap output below the minimum", async () => {
+    await createBasketPool({ stableSwap: {} }, amp);
+    await deposit([initialAmount, initialAmount, initialAmount]);
+
+    await expectRevert(swap(0, 1, 10 * 10 ** 6, 10 * 10 ** 6));
+  });
+
+  it("Success: Withdraw returns every token pro rata", async () => {
+    await createBasketPool({ stableSwap: {} }, amp);
+    await deposit([initialAmount, initialAmount, initialAmount]);
+
+    const lp = await balance(userAccount(mintLiquidity));
+    const before = await Promise.all(mints.map((mint) => balance(userAccount(mint))));
+
+    await program.methods
+      .withdrawBasketLiquidity(
+        new anchor.BN(lp),
+        mints.map(() => new anchor.BN(0)),
+        null
+      )
+      .accounts({
+        pool: poolPda,
+        poolAuthority,
+        mintLiquidity,
+        depositorAccountLiquidity: userAccount(mintLiquidity),
+        depositor: values.admin.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+      } as any)
+      .remainingAccounts(liquidityAccounts())
+      .signers([values.admin])
+      .rpc();
+
+    expect(await balance(userAccount(mintLiquidity))).to.equal(0);
+    for (let i = 0; i < mints.length; i++) {
+      const received = (await balance(userAccount(mints[i]))) - before[i];
+      // 锁定的最小流动性对应的代币留在池子里
+      expect(received).to.be.closeTo(initialAmount, 100);
+      expect(received).to.be.lessThan(initialAmount);
+    }
+  });
+
+  it("Failure: Withdraw below the minimum amounts", async () => {
+    await createBasketPool({ stableSwap: {} }, amp);
+    await deposit([initialAmount, initialAmount, initialAmount]);
+
+    const lp = await balance(userAccount(mintLiquidity));
+    await expectRevert(
+      program.methods
+        .withdrawBasketLiquidity(
+          new anchor.BN(lp),
+          mints.map(() => new anchor.BN(initialAmount)),
+          null
+        )
+        .accounts({
+          pool: poolPda,
+          poolAuthority,
+          mintLiquidity,
+          depositorAccountLiquidity: userAccount(mintLiquidity),
+          depositor: values.admin.publicKey,
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        } as any)
+        .remainingAccounts(liquidityAccounts())
+        .signers([values.admin])
+        .rpc()
+    );
+  });
+
+  it("Success: Protocol fees stay out of the reserves until collected", async () => {
+    await createBasketPool({ stableSwap: {} }, amp);
+    await deposit([initialAmount, initialAmount, initialAmount]);
+
+    // 协议拿一半手续费，treasury用admin，它已经有每种代币的账户
+    await program.methods
+      .setProtocolFee(5000, values.admin.publicKey)
+      .accounts({
+        amm: values.ammPda,
+        admin: values.admin.publicKey,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+    const inputAmount = 100 * 10 ** 6;
+    await swap(0, 2, inputAmount);
+
+    const feeAmount = (inputAmount * values.fee) / 10000;
+    let pool = await program.account.basketPool.fetch(poolPda);
+    expect(pool.protocolFees.map((fee) => fee.toNumber())).to.deep.equal([feeAmount / 2, 0, 0]);
+
+    const before = await balance(userAccount(mints[0]));
+    await program.methods
+      .collectBasketProtocolFees()
+      .accounts({
+        amm: values.ammPda,
+        pool: poolPda,
+        poolAuthority,
+        treasury: values.admin.publicKey,
+        admin: values.admin.publicKey,
+      } as any)
+      .remainingAccounts(liquidityAccounts())
+      .signers([values.admin])
+      .rpc();
+
+    expect((await balance(userAccount(mints[0]))) - before).to.equal(feeAmount / 2);
+    expect(await balance(vault(mints[0]))).to.equal(initialAmount + inputAmount - feeAmount / 2);
+    pool = await program.account.basketPool.fetch(poolPda);
+    expect(pool.protocolFees.map((fee) => fee.toNumber())).to.deep.equal([0, 0, 0]);
+  });
+
+  it("Failure: Swap and deposit in a paused basket pool", async () => {
+    await createBasketPool({ stableSwap: {} }, amp);
+    await deposit([initialAmount, initialAmount, initialAmount]);
+
+    await program.methods
+      .setBasketPoolPause(true)
+      .accounts({
+        amm: values.ammPda,
+        pool: poolPda,
+        admin: values.admin.publicKey,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+    await expectRevert(swap(0, 1, 10 * 10 ** 6));
+    await expectRevert(deposit([initialAmount, initialAmount, initialAmount]));
+  });
+
+  it("Success: Ramp and stop the amp of a stable basket pool", async () => {
+    await createBasketPool({ stableSwap: {} }, amp);
+    const day = 86400;
+    const rampEnd = (await connection.getBlockTime(await connection.getSlot())) + 10 * day;
+
+    await program.methods
+      .rampBasketAmp(new anchor.BN(1000), new anchor.BN(rampEnd))
+      .accounts({
+        amm: values.ammPda,
+        pool: poolPda,
+        admin: values.admin.publicKey,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+    let pool = await program.account.basketPool.fetch(poolPda);
+    expect(pool.targetAmp.toNumber()).to.equal(1000);
+    expect(pool.ampRampEnd.toNumber()).to.equal(rampEnd);
+
+    await program.methods
+      .stopRampBasketAmp()
+      .accounts({
+        amm: values.ammPda,
+        pool: poolPda,
+        admin: values.admin.publicKey,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+    pool = await program.account.basketPool.fetch(poolPda);
+    expect(pool.targetAmp.toNumber()).to.equal(pool.amp.toNumber());
+    expect(pool.amp.toNumber()).to.be.lessThan(200);
+  });
+});
@@ -0,0 +1,545 @@
+import * as anchor from "@coral-xyz/anchor";
+import type { Program } from "@coral-xyz/anchor";
+import { PublicKey } from "@solana/web3.js";
+import {
+  getAccount,
+  getAssociatedTokenAddressSync,
+} from "@solana/spl-token";
+import { expect } from "chai";
+import { BN } from "bn.js";
+import { MyAmmProject } from "../target/types/my_amm_project";
+import {
+  type TestValues,
+  createTestValues,
+  expectRevert,
+  mintingTokens,
+} from "./utils";
+
+describe("Concentrated Liquidity Pool", () => {
+  const provider = anchor.AnchorProvider.env();
+  const connection = provider.connection;
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.MyAmmProject as Program<MyAmmProject>;
+  const programId = program.programId;
+
+  let values: TestValues;
+  let poolPda: PublicKey;
+  let poolAuthority: PublicKey;
+  let poolAccountA: PublicKey;
+  let poolAccountB: PublicKey;
+  let userAccountA: PublicKey;
+  let userAccountB: PublicKey;
+
+  // tick_spacing = 10，每个tick array有32个tick，覆盖320个tick
+  const tickSpacing = 10;
+  const tickArraySpan = 32 * tickSpacing;
+  // 价格1对应的sqrt_price（Q64.64）
+  const sqrtPriceOne = new BN(1).shln(64);
+  const amount = 10 * 10 ** 6;
+
+  const i32Buffer = (value: number) => {
+    const buffer = Buffer.alloc(4);
+    buffer.writeInt32LE(value);
+    return buffer;
+  };
+
+  const tickArrayPda = (startTick: number) =>
+    PublicKey.findProgramAddressSync(
+      [poolPda.toBuffer(), Buffer.from("tick_array"), i32Buffer(startTick)],
+      programId
+    )[0];
+
+  const startTickFor = (tick: number) =>
+    Math.floor(tick / tickArraySpan) * tickArraySpan;
+
+  const positionPda = (tickLower: number, tickUpper: number) =>
+    PublicKey.findProgramAddressSync(
+      [
+        poolPda.toBuffer(),
+        Buffer.from("position"),
+        values.admin.publicKey.toBuffer(),
+        i32Buffer(tickLower),
+        i32Buffer(tickUpper),
+      ],
+      programId
+    )[0];
+
+  const createClmmPool = (spacing: number, sqrtPrice: BN) =>
+    program.methods
+      .createClmmPool(spacing, sqrtPrice)
+      .accounts({
+        amm: values.ammPda,
+        pool: poolPda,
+        poolAuthority,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA,
+        poolAccountB,
+        payer: provider.wallet.publicKey,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+
+  const initializeTickArray = (startTick: number) =>
+    program.methods
+      .initializeTickArray(startTick)
+      .accounts({
+        pool: poolPda,
+        tickArray: tickArrayPda(startTick),
+        payer: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+
+  const openPosition = (tickLower: number, tickUpper: number) =>
+    program.methods
+      .openPosition(tickLower, tickUpper)
+      .accounts({
+        pool: poolPda,
+        position: positionPda(tickLower, tickUpper),
+        owner: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+  const positionAccounts = (tickLower: number, tickUpper: number) => ({
+    pool: poolPda,
+    poolAuthority,
+    position: positionPda(tickLower, tickUpper),
+    tickArrayLower: tickArrayPda(startTickFor(tickLower)),
+    tickArrayUpper: tickArrayPda(startTickFor(tickUpper)),
+    mintA: values.mint_a.publicKey,
+    mintB: values.mint_b.publicKey,
+    poolAccountA,
+    poolAccountB,
+    ownerAccountA: userAccountA,
+    ownerAccountB: userAccountB,
+    owner: values.admin.publicKey,
+    tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+    tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+  });
+
+  const increaseLiquidity = (
+    tickLower: number,
+    tickUpper: number,
+    amountA: number,
+    amountB: number,
+    minLiquidityOut = new BN(0)
+  ) =>
+    program.methods
+      .increaseLiquidity(new BN(amountA), new BN(amountB), minLiquidityOut, null)
+      .accounts({
+        amm: values.ammPda,
+        ...positionAccounts(tickLower, tickUpper),
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+  const decreaseLiquidity = (
+    tickLower: number,
+    tickUpper: number,
+    liquidity: BN,
+    minAmountA = 0,
+    minAmountB = 0
+  ) =>
+    program.methods
+      .decreaseLiquidity(liquidity, new BN(minAmountA), new BN(minAmountB), null)
+      .accounts({
+        ...positionAccounts(tickLower, tickUpper),
+        payer: provider.wallet.publicKey,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+  const collectFees = (tickLower: number, tickUpper: number) =>
+    program.methods
+      .collectFees()
+      .accounts({
+        ...positionAccounts(tickLower, tickUpper),
+        payer: provider.wallet.publicKey,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+  // 交换会经过的tick array按方向依次放在remaining_accounts里
+  const swap = (
+    swapA: boolean,
+    inputAmount: number,
+    startTicks: number[],
+    minOutputAmount = 0,
+    oracle: PublicKey | null = null
+  ) =>
+    program.methods
+      .swapClmm(swapA, new BN(inputAmount), new BN(minOutputAmount), null)
+      .accounts({
+        amm: values.ammPda,
+        pool: poolPda,
+        oracle,
+        poolAuthority,
+        trader: values.admin.publicKey,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA,
+        poolAccountB,
+        traderAccountA: userAccountA,
+        traderAccountB: userAccountB,
+        payer: provider.wallet.publicKey,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .remainingAccounts(
+        startTicks.map((startTick) => ({
+          pubkey: tickArrayPda(startTick),
+          isWritable: true,
+          isSigner: false,
+        }))
+      )
+      .signers([values.admin])
+      .rpc();
+
+  const balance = async (account: PublicKey) =>
+    Number((await getAccount(connection, account)).amount);
+
+  const setClmmPoolPause = (paused: boolean) =>
+    program.methods
+      .setClmmPoolPause(paused)
+      .accounts({
+        amm: values.ammPda,
+        pool: poolPda,
+        admin: values.admin.publicKey,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+  const toPrice = (value: BN) => Number(value.toString()) / 2 ** 64;
+  const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
+  beforeEach(async () => {
+    // Setup: 生成测试数据
+    values = createTestValues();
+
+    // Setup: 创建AMM
+    await program.methods
+      .createAmm(values.id, values.fee, new anchor.BN(values.feeDelay))
+      .accounts({
+        amm: values.ammPda,
+        admin: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+
+    // Setup: 创建代币并给admin铸造
+    await mintingTokens({
+      connection,
+      creator: values.admin,
+      holder: values.admin,
+      mint_a: values.mint_a,
+      mint_b: values.mint_b,
+    });
+
+    // Setup: 集中流动性池子的PDA多一个tick_spacing种子，authority由池子地址派生
+    const spacingBuffer = Buffer.alloc(2);
+    spacingBuffer.writeUInt16LE(tickSpacing);
+    poolPda = PublicKey.findProgramAddressSync(
+      [
+        values.ammPda.toBuffer(),
+        values.mint_a.publicKey.toBuffer(),
+        values.mint_b.publicKey.toBuffer(),
+        spacingBuffer,
+      ],
+      programId
+    )[0];
+    poolAuthority = PublicKey.findProgramAddressSync(
+      [poolPda.toBuffer(), Buffer.from("authority")],
+      programId
+    )[0];
+    poolAccountA = getAssociatedTokenAddressSync(values.mint_a.publicKey, poolAuthority, true);
+    poolAccountB = getAssociatedTokenAddressSync(values.mint_b.publicKey, poolAuthority, true);
+    userAccountA = getAssociatedTokenAddressSync(values.mint_a.publicKey, values.admin.publicKey, true);
+    userAccountB = getAssociatedTokenAddressSync(values.mint_b.publicKey, values.admin.publicKey, true);
+  });
+
+  // 价格为1的池子，[-100, 100)区间的仓位跨两个tick array
+  const setupPosition = async () => {
+    await createClmmPool(tickSpacing, sqrtPriceOne);
+    await initializeTickArray(-tickArraySpan);
+    await initializeTickArray(0);
+    await openPosition(-100, 100);
+    await increaseLiquidity(-100, 100, amount, amount);
+  };
+
+  it("Success: Create a pool at price 1", async () => {
+    await createClmmPool(tickSpacing, sqrtPriceOne);
+
+    const pool = await program.account.clmmPool.fetch(poolPda);
+    expect(pool.amm.toString()).to.equal(values.ammPda.toString());
+    expect(pool.tickSpacing).to.equal(tickSpacing);
+    expect(pool.sqrtPrice.toString()).to.equal(sqrtPriceOne.toString());
+    expect(pool.tick).to.equal(0);
+    expect(pool.liquidity.toNumber()).to.equal(0);
+  });
+
+  it("Failure: Zero tick spacing", async () => {
+    await expectRevert(createClmmPool(0, sqrtPriceOne));
+  });
+
+  it("Failure: Tick array not aligned to its span", async () => {
+    await createClmmPool(tickSpacing, sqrtPriceOne);
+    await expectRevert(initializeTickArray(tickSpacing));
+  });
+
+  it("Failure: Position bounds not multiples of the tick spacing", async () => {
+    await createClmmPool(tickSpacing, sqrtPriceOne);
+    await expectRevert(openPosition(-105, 100));
+  });
+
+  it("Success: In-range position takes both tokens", async () => {
+    await setupPosition();
+
+    // 价格在区间中间，两种代币按1:1存入
+    expect(await balance(poolAccountA)).to.be.closeTo(amount, 1);
+    expect(await balance(poolAccountB)).to.be.closeTo(amount, amount / 1000);
+
+    const pool = await program.account.clmmPool.fetch(poolPda);
+    const position = await program.account.position.fetch(positionPda(-100, 100));
+    expect(position.liquidity.gtn(0)).to.be.true;
+    expect(pool.liquidity.toString()).to.equal(position.liquidity.toString());
+  });
+
+  it("Success: Position above the price takes only token A", async () => {
+    await createClmmPool(tickSpacing, sqrtPriceOne);
+    await initializeTickArray(0);
+    await openPosition(100, 200);
+    await increaseLiquidity(100, 200, amount, amount);
+
+    expect(await balance(poolAccountA)).to.be.closeTo(amount, 1);
+    expect(await balance(poolAccountB)).to.equal(0);
+
+    // 价格不在区间内，池子的活跃流动性不变
+    const pool = await program.account.clmmPool.fetch(poolPda);
+    expect(pool.liquidity.toNumber()).to.equal(0);
+  });
+
+  it("Failure: Liquidity below the minimum", async () => {
+    await createClmmPool(tickSpacing, sqrtPriceOne);
+    await initializeTickArray(-tickArraySpan);
+    await initializeTickArray(0);
+    await openPosition(-100, 100);
+    await expectRevert(
+      increaseLiquidity(-100, 100, amount, amount, new BN(10).pow(new BN(15)))
+    );
+  });
+
+  it("Success: Swap inside the range", async () => {
+    await setupPosition();
+
+    const inputAmount = 10 ** 6;
+    const beforeA = await balance(userAccountA);
+    const beforeB = await balance(userAccountB);
+    await swap(true, inputAmount, [0, -tickArraySpan]);
+
+    expect(beforeA - (await balance(userAccountA))).to.equal(inputAmount);
+    const output = (await balance(userAccountB)) - beforeB;
+
+    // 区间内的流动性很深，输出接近扣掉手续费的输入
+    const taxedInput = (inputAmount * (10000 - values.fee)) / 10000;
+    expect(output).to.be.lessThan(taxedInput);
+    expect(output).to.be.greaterThan(taxedInput * 0.99);
+
+    // 卖出A，价格下降
+    const pool = await program.account.clmmPool.fetch(poolPda);
+    expect(pool.tick).to.be.lessThan(0);
+    expect(pool.tick).to.be.greaterThanOrEqual(-100);
+  });
+
+  it("Failure: Swap output below the minimum", async () => {
+    await setupPosition();
+    await expectRevert(swap(true, 10 ** 6, [0, -tickArraySpan], 10 ** 6));
+  });
+
+  it("Failure: Swap past the liquidity without the next tick array", async () => {
+    await setupPosition();
+
+    // 价格穿过下界之后没有流动性，需要的tick array没有传入
+    await expectRevert(swap(true, 2 * amount, [0, -tickArraySpan]));
+  });
+
+  it("Success: Collect the swap fees of a position", async () => {
+    await setupPosition();
+
+    const inputAmount = 10 ** 6;
+    await swap(false, inputAmount, [0]);
+
+    const beforeA = await balance(userAccountA);
+    const beforeB = await balance(userAccountB);
+    await collectFees(-100, 100);
+
+    // 唯一的仓位拿到全部手续费（只差取整）
+    const feeAmount = (inputAmount * values.fee) / 10000;
+    expect(await balance(userAccountA)).to.equal(beforeA);
+    expect((await balance(userAccountB)) - beforeB).to.be.closeTo(feeAmount, 2);
+
+    const position = await program.account.position.fetch(positionPda(-100, 100));
+    expect(position.feesOwedA.toNumber()).to.equal(0);
+    expect(position.feesOwedB.toNumber()).to.equal(0);
+  });
+
+  it("Success: Remove all liquidity and close the position", async () => {
+    await setupPosition();
+
+    const position = await program.account.position.fetch(positionPda(-100, 100));
+    const beforeA = await balance(userAccountA);
+    const beforeB = await balance(userAccountB);
+    await decreaseLiquidity(-100, 100, position.liquidity);
+
+    // 向下取整，最多少几个单位
+    expect((await balance(userAccountA)) - beforeA).to.be.closeTo(amount, 2);
+    expect((await balance(userAccountB)) - beforeB).to.be.closeTo(amount, amount / 1000);
+    const pool = await program.account.clmmPool.fetch(poolPda);
+    expect(pool.liquidity.toNumber()).to.equal(0);
+
+    await program.methods
+      .closePosition()
+      .accounts({
+        position: positionPda(-100, 100),
+        owner: values.admin.publicKey,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+    expect(await connection.getAccountInfo(positionPda(-100, 100))).to.be.null;
+  });
+
+  it("Failure: Remove more liquidity than the position has", async () => {
+    await setupPosition();
+
+    const position = await program.account.position.fetch(positionPda(-100, 100));
+    await expectRevert(decreaseLiquidity(-100, 100, position.liquidity.addn(1)));
+  });
+
+  it("Failure: Close a position that still has liquidity", async () => {
+    await setupPosition();
+    await expectRevert(
+      program.methods
+        .closePosition()
+        .accounts({
+          position: positionPda(-100, 100),
+          owner: values.admin.publicKey,
+        } as any)
+        .signers([values.admin])
+        .rpc()
+    );
+  });
+
+  it("Failure: Swap and add liquidity in a paused pool", async () => {
+    await setupPosition();
+    await swap(false, 10 ** 6, [0]);
+    await setClmmPoolPause(true);
+
+    await expectRevert(swap(true, 10 ** 6, [0, -tickArraySpan]));
+    await expectRevert(increaseLiquidity(-100, 100, amount, amount));
+
+    // 领取手续费不受暂停影响
+    const beforeB = await balance(userAccountB);
+    await collectFees(-100, 100);
+    expect(await balance(userAccountB)).to.be.greaterThan(beforeB);
+  });
+
+  it("Success: Protocol fees are kept out of the position fees", async () => {
+    await setupPosition();
+    await program.methods
+      .setProtocolFee(5000, values.admin.publicKey)
+      .accounts({
+        amm: values.ammPda,
+        admin: values.admin.publicKey,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+    const inputAmount = 10 ** 6;
+    await swap(false, inputAmount, [0]);
+
+    // 一半手续费归协议，仓位只拿到另一半
+    const feeAmount = (inputAmount * values.fee) / 10000;
+    const pool = await program.account.clmmPool.fetch(poolPda);
+    expect(pool.protocolFeesA.toNumber()).to.equal(0);
+    expect(pool.protocolFeesB.toNumber()).to.equal(feeAmount / 2);
+
+    const beforeB = await balance(userAccountB);
+    await collectFees(-100, 100);
+    expect((await balance(userAccountB)) - beforeB).to.be.closeTo(feeAmount / 2, 2);
+
+    const vaultBefore = await balance(poolAccountB);
+    await program.methods
+      .collectClmmProtocolFees()
+      .accounts({
+        amm: values.ammPda,
+        pool: poolPda,
+        poolAuthority,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA,
+        poolAccountB,
+        treasury: values.admin.publicKey,
+        treasuryAccountA: userAccountA,
+        treasuryAccountB: userAccountB,
+        admin: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+    expect(vaultBefore - (await balance(poolAccountB))).to.equal(feeAmount / 2);
+    expect((await program.account.clmmPool.fetch(poolPda)).protocolFeesB.toNumber()).to.equal(0);
+  });
+
+  it("Success: TWAP of the pool price recorded by swaps", async () => {
+    await setupPosition();
+    const oraclePda = PublicKey.findProgramAddressSync(
+      [poolPda.toBuffer(), Buffer.from("oracle")],
+      programId
+    )[0];
+    await program.methods
+      .initializeClmmOracle(10)
+      .accounts({
+        pool: poolPda,
+        oracle: oraclePda,
+        payer: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+    await sleep(2000);
+
+    // swap之前按旧价格累计，记录一个观测点
+    await swap(false, 10 ** 6, [0], 0, oraclePda);
+    const pool = await program.account.clmmPool.fetch(poolPda);
+    const oracle = await program.account.oracle.fetch(oraclePda);
+    expect(oracle.observations.length).to.equal(2);
+    expect(pool.priceBPerACumulative.gtn(0)).to.be.true;
+    await sleep(3000);
+
+    // 价格从1开始，买入少量A之后略高于1
+    const twap = await program.methods
+      .consultClmmTwap(4)
+      .accounts({ pool: poolPda, oracle: oraclePda } as any)
+      .view();
+    expect(toPrice(twap.priceBPerA)).to.be.greaterThanOrEqual(1 - 1e-9);
+    expect(toPrice(twap.priceBPerA)).to.be.lessThan(1.01);
+    expect(toPrice(twap.priceAPerB) * toPrice(twap.priceBPerA)).to.be.closeTo(1, 0.01);
+  });
+});
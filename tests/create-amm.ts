@@ -18,7 +18,7 @@ describe("Create AMM", () => {
 
   it("Success create AMM", async () => {
     await program.methods
-      .createAmm(values.id, values.fee)
+      .createAmm(values.id, values.fee, new anchor.BN(values.feeDelay))
       .accounts({
         amm: values.ammPda,
         admin: values.admin.publicKey,
@@ -32,6 +32,7 @@ describe("Create AMM", () => {
       values.admin.publicKey.toString()
     );
     expect(ammAccount.fee).to.equal(values.fee);
+    expect(ammAccount.feeDelay.toNumber()).to.equal(values.feeDelay);
     expect(ammAccount.id.toString()).to.equal(values.id.toString());
   });
 
@@ -40,7 +41,39 @@ describe("Create AMM", () => {
 
     await expectRevert(
       program.methods
-        .createAmm(values.id, values.fee)
+        .createAmm(values.id, values.fee, new anchor.BN(values.feeDelay))
+        .accounts({
+          amm: values.ammPda,
+          admin: values.admin.publicKey,
+          payer: provider.wallet.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        } as any)
+        .rpc()
+    );
+  });
+
+  it("Invalid fee delay", async () => {
+    values.feeDelay = -1;
+
+    await expectRevert(
+      program.methods
+        .createAmm(values.id, values.fee, new anchor.BN(values.feeDelay))
+        .accounts({
+          amm: values.ammPda,
+          admin: values.admin.publicKey,
+          payer: provider.wallet.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        } as any)
+        .rpc()
+    );
+  });
+
+  it("Fee delay above the maximum", async () => {
+    values.feeDelay = 30 * 24 * 60 * 60 + 1; // 超过30天
+
+    await expectRevert(
+      program.methods
+        .createAmm(values.id, values.fee, new anchor.BN(values.feeDelay))
         .accounts({
           amm: values.ammPda,
           admin: values.admin.publicKey,
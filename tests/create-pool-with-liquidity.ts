@@ -0,0 +1,235 @@
+import * as anchor from "@coral-xyz/anchor";
+import type { Program } from "@coral-xyz/anchor";
+import {
+  getAccount,
+  getAssociatedTokenAddressSync,
+} from "@solana/spl-token";
+import { expect } from "chai";
+import { MyAmmProject } from "../target/types/my_amm_project";
+import {
+  type TestValues,
+  createTestValues,
+  expectRevert,
+  mintingTokens,
+} from "./utils";
+
+describe("Create Pool With Liquidity", () => {
+  const provider = anchor.AnchorProvider.env();
+  const connection = provider.connection;
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.MyAmmProject as Program<MyAmmProject>;
+
+  let values: TestValues;
+
+  // 三种曲线的一步创建指令用同一组账户
+  const createAccounts = () =>
+    ({
+      amm: values.ammPda,
+      pool: values.poolPda,
+      poolAuthority: values.poolAuthority,
+      mintLiquidity: values.mintLiquidity,
+      mintA: values.mint_a.publicKey,
+      mintB: values.mint_b.publicKey,
+      poolAccountA: values.poolAccountA,
+      poolAccountB: values.poolAccountB,
+      depositorAccountLiquidity: getAssociatedTokenAddressSync(
+        values.mintLiquidity,
+        values.admin.publicKey,
+        true
+      ),
+      depositorAccountA: getAssociatedTokenAddressSync(
+        values.mint_a.publicKey,
+        values.admin.publicKey,
+        true
+      ),
+      depositorAccountB: getAssociatedTokenAddressSync(
+        values.mint_b.publicKey,
+        values.admin.publicKey,
+        true
+      ),
+      depositor: values.admin.publicKey,
+      payer: provider.wallet.publicKey,
+      tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+      tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+      tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+      associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    }) as any;
+
+  const createPoolWithLiquidity = (amountA: number, amountB: number) =>
+    program.methods
+      .createPoolWithLiquidity(new anchor.BN(amountA), new anchor.BN(amountB))
+      .accounts(createAccounts())
+      .signers([values.admin])
+      .rpc();
+
+  beforeEach(async () => {
+    // Setup: 生成测试数据
+    values = createTestValues();
+
+    // Setup: 创建AMM
+    await program.methods
+      .createAmm(values.id, values.fee, new anchor.BN(values.feeDelay))
+      .accounts({
+        amm: values.ammPda,
+        admin: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+
+    // Setup: 创建代币并给admin铸造
+    await mintingTokens({
+      connection,
+      creator: values.admin,
+      holder: values.admin,
+      mint_a: values.mint_a,
+      mint_b: values.mint_b,
+      mintedAmount: 10000,
+      decimals: 6,
+    });
+  });
+
+  it("Success: Pool is created and seeded in one instruction", async () => {
+    const amountA = 100 * 10 ** 6;
+    const amountB = 200 * 10 ** 6;
+
+    await createPoolWithLiquidity(amountA, amountB);
+
+    const poolAccount = await program.account.pool.fetch(values.poolPda);
+    expect(poolAccount.amm.toString()).to.equal(values.ammPda.toString());
+    expect(poolAccount.mintA.toString()).to.equal(
+      values.mint_a.publicKey.toString()
+    );
+    expect(poolAccount.mintB.toString()).to.equal(
+      values.mint_b.publicKey.toString()
+    );
+
+    // 池子一创建就有创建者指定的价格
+    const poolAccountA = await getAccount(connection, values.poolAccountA);
+    const poolAccountB = await getAccount(connection, values.poolAccountB);
+    expect(Number(poolAccountA.amount)).to.equal(amountA);
+    expect(Number(poolAccountB.amount)).to.equal(amountB);
+
+    // LP代币数量：sqrt(amountA * amountB) - MINIMUM_LIQUIDITY
+    const liquidityAccount = await getAccount(
+      connection,
+      getAssociatedTokenAddressSync(
+        values.mintLiquidity,
+        values.admin.publicKey,
+        true
+      )
+    );
+    expect(Number(liquidityAccount.amount)).to.be.approximately(
+      Math.sqrt(amountA * amountB) - 100,
+      1
+    );
+  });
+
+  it("Failure: Pool already created by someone else", async () => {
+    // 攻击者抢先创建了同一个池子
+    await program.methods
+      .createPool()
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        mintLiquidity: values.mintLiquidity,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        payer: provider.wallet.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+
+    // init失败，创建者不会按别人设定的比例存入
+    await expectRevert(createPoolWithLiquidity(100 * 10 ** 6, 200 * 10 ** 6));
+  });
+
+  it("Failure: Initial deposit too small (below MINIMUM_LIQUIDITY)", async () => {
+    await expectRevert(createPoolWithLiquidity(1, 1));
+  });
+
+  it("Success: Stable pool is created and seeded in one instruction", async () => {
+    const amount = 100 * 10 ** 6;
+
+    await program.methods
+      .createStablePoolWithLiquidity(
+        new anchor.BN(100),
+        new anchor.BN(amount),
+        new anchor.BN(amount)
+      )
+      .accounts(createAccounts())
+      .signers([values.admin])
+      .rpc();
+
+    const poolAccount = await program.account.pool.fetch(values.poolPda);
+    expect(poolAccount.curveType).to.deep.equal({ stableSwap: {} });
+    expect(poolAccount.amp.toNumber()).to.equal(100);
+
+    // 余额相等时StableSwap的D = 2 * amount，LP = D - MINIMUM_LIQUIDITY
+    const liquidityAccount = await getAccount(
+      connection,
+      getAssociatedTokenAddressSync(
+        values.mintLiquidity,
+        values.admin.publicKey,
+        true
+      )
+    );
+    expect(Number(liquidityAccount.amount)).to.be.approximately(
+      2 * amount - 100,
+      1
+    );
+  });
+
+  it("Success: Weighted pool is created and seeded in one instruction", async () => {
+    await program.methods
+      .createWeightedPoolWithLiquidity(
+        8000,
+        new anchor.BN(400 * 10 ** 6),
+        new anchor.BN(100 * 10 ** 6)
+      )
+      .accounts(createAccounts())
+      .signers([values.admin])
+      .rpc();
+
+    const poolAccount = await program.account.pool.fetch(values.poolPda);
+    expect(poolAccount.curveType).to.deep.equal({ weighted: {} });
+    expect(poolAccount.weightA).to.equal(8000);
+    expect(poolAccount.weightB).to.equal(2000);
+  });
+
+  it("Failure: Invalid amp for a stable pool", async () => {
+    await expectRevert(
+      program.methods
+        .createStablePoolWithLiquidity(
+          new anchor.BN(0),
+          new anchor.BN(100 * 10 ** 6),
+          new anchor.BN(100 * 10 ** 6)
+        )
+        .accounts(createAccounts())
+        .signers([values.admin])
+        .rpc()
+    );
+  });
+
+  it("Failure: AMM is paused", async () => {
+    await program.methods
+      .setAmmPause(true)
+      .accounts({
+        amm: values.ammPda,
+        admin: values.admin.publicKey,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+    await expectRevert(createPoolWithLiquidity(100 * 10 ** 6, 200 * 10 ** 6));
+  });
+});
@@ -12,6 +12,7 @@ import {
   type TestValues,
   createTestValues,
   expectRevert,
+  getPoolAddresses,
   mintingTokens,
 } from "./utils";
 
@@ -30,7 +31,7 @@ describe("Create Pool", () => {
 
     // setup2 创建amm
     await program.methods
-      .createAmm(values.id, values.fee)
+      .createAmm(values.id, values.fee, new anchor.BN(values.feeDelay))
       .accounts({
         amm: values.ammPda,
         admin: values.admin.publicKey,
@@ -64,6 +65,8 @@ describe("Create Pool", () => {
         poolAccountB: values.poolAccountB,
         payer: provider.wallet.publicKey,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -77,6 +80,10 @@ describe("Create Pool", () => {
     expect(poolAccount.mintB.toString()).to.equal(
       values.mint_b.publicKey.toString()
     );
+    // 默认是恒定乘积曲线
+    expect(poolAccount.curveType).to.deep.equal({ constantProduct: {} });
+    expect(poolAccount.amp.toNumber()).to.equal(0);
+    expect(poolAccount.weightA).to.equal(0);
   });
 
   it("Invalid mints", async () => {
@@ -116,4 +123,69 @@ describe("Create Pool", () => {
         .rpc()
     );
   });
+
+  it("Failure: Mints in non-canonical order (mint_a > mint_b)", async () => {
+    // createTestValues保证mint_a < mint_b，这里反过来建B/A池子
+    const reversed = getPoolAddresses(
+      values.ammPda,
+      values.mint_b.publicKey,
+      values.mint_a.publicKey
+    );
+
+    await expectRevert(
+      program.methods
+        .createPool()
+        .accounts({
+          amm: values.ammPda,
+          pool: reversed.poolPda,
+          poolAuthority: reversed.poolAuthority,
+          mintLiquidity: reversed.mintLiquidity,
+          mintA: values.mint_b.publicKey,
+          mintB: values.mint_a.publicKey,
+          poolAccountA: reversed.poolAccountA,
+          poolAccountB: reversed.poolAccountB,
+          payer: provider.wallet.publicKey,
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+          associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        } as any)
+        .rpc()
+    );
+  });
+
+  it("Failure: AMM is paused", async () => {
+    await program.methods
+      .setAmmPause(true)
+      .accounts({
+        amm: values.ammPda,
+        admin: values.admin.publicKey,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+    // 和create_pool_with_liquidity一样，暂停的AMM不能创建新池子
+    await expectRevert(
+      program.methods
+        .createPool()
+        .accounts({
+          amm: values.ammPda,
+          pool: values.poolPda,
+          poolAuthority: values.poolAuthority,
+          mintLiquidity: values.mintLiquidity,
+          mintA: values.mint_a.publicKey,
+          mintB: values.mint_b.publicKey,
+          poolAccountA: values.poolAccountA,
+          poolAccountB: values.poolAccountB,
+          payer: provider.wallet.publicKey,
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+          associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        } as any)
+        .rpc()
+    );
+  });
 });
@@ -31,7 +31,7 @@ describe("Deposit Liquidity", () => {
 
     // Setup: 创建AMM
     await program.methods
-      .createAmm(values.id, values.fee)
+      .createAmm(values.id, values.fee, new anchor.BN(values.feeDelay))
       .accounts({
         amm: values.ammPda,
         admin: values.admin.publicKey,
@@ -65,6 +65,8 @@ describe("Deposit Liquidity", () => {
         poolAccountB: values.poolAccountB,
         payer: provider.wallet.publicKey,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -95,8 +97,16 @@ describe("Deposit Liquidity", () => {
 
     // 执行存款
     await program.methods
-      .depositLiquidity(new anchor.BN(amountA), new anchor.BN(amountB))
+      .depositLiquidity(
+        new anchor.BN(amountA),
+        new anchor.BN(amountB),
+        new anchor.BN(0),
+        null,
+        null,
+        null
+      )
       .accounts({
+        amm: values.ammPda,
         pool: values.poolPda,
         poolAuthority: values.poolAuthority,
         mintLiquidity: values.mintLiquidity,
@@ -114,6 +124,8 @@ describe("Deposit Liquidity", () => {
         depositor: values.admin.publicKey,
         payer: provider.wallet.publicKey,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -174,9 +186,14 @@ describe("Deposit Liquidity", () => {
     await program.methods
       .depositLiquidity(
         new anchor.BN(initialAmountA),
-        new anchor.BN(initialAmountB)
+        new anchor.BN(initialAmountB),
+        new anchor.BN(0),
+        null,
+        null,
+        null
       )
       .accounts({
+        amm: values.ammPda,
         pool: values.poolPda,
         poolAuthority: values.poolAuthority,
         mintLiquidity: values.mintLiquidity,
@@ -202,6 +219,8 @@ describe("Deposit Liquidity", () => {
         depositor: values.admin.publicKey,
         payer: provider.wallet.publicKey,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -228,9 +247,14 @@ describe("Deposit Liquidity", () => {
     await program.methods
       .depositLiquidity(
         new anchor.BN(secondAmountA),
-        new anchor.BN(secondAmountB)
+        new anchor.BN(secondAmountB),
+        new anchor.BN(0),
+        null,
+        null,
+        null
       )
       .accounts({
+        amm: values.ammPda,
         pool: values.poolPda,
         poolAuthority: values.poolAuthority,
         mintLiquidity: values.mintLiquidity,
@@ -256,6 +280,8 @@ describe("Deposit Liquidity", () => {
         depositor: values.admin.publicKey,
         payer: provider.wallet.publicKey,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -316,9 +342,14 @@ describe("Deposit Liquidity", () => {
     await program.methods
       .depositLiquidity(
         new anchor.BN(100 * 10 ** 6),
-        new anchor.BN(200 * 10 ** 6)
+        new anchor.BN(200 * 10 ** 6),
+        new anchor.BN(0),
+        null,
+        null,
+        null
       )
       .accounts({
+        amm: values.ammPda,
         pool: values.poolPda,
         poolAuthority: values.poolAuthority,
         mintLiquidity: values.mintLiquidity,
@@ -344,6 +375,8 @@ describe("Deposit Liquidity", () => {
         depositor: values.admin.publicKey,
         payer: provider.wallet.publicKey,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -368,9 +401,14 @@ describe("Deposit Liquidity", () => {
     await program.methods
       .depositLiquidity(
         new anchor.BN(100 * 10 ** 6),
-        new anchor.BN(100 * 10 ** 6)
+        new anchor.BN(100 * 10 ** 6),
+        new anchor.BN(0),
+        null,
+        null,
+        null
       )
       .accounts({
+        amm: values.ammPda,
         pool: values.poolPda,
         poolAuthority: values.poolAuthority,
         mintLiquidity: values.mintLiquidity,
@@ -388,6 +426,8 @@ describe("Deposit Liquidity", () => {
         depositor: newUser.publicKey,
         payer: provider.wallet.publicKey,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -425,9 +465,63 @@ describe("Deposit Liquidity", () => {
       program.methods
         .depositLiquidity(
           new anchor.BN(tinyAmountA),
-          new anchor.BN(tinyAmountB)
+          new anchor.BN(tinyAmountB),
+          new anchor.BN(0),
+          null,
+          null,
+          null
+        )
+        .accounts({
+          amm: values.ammPda,
+          pool: values.poolPda,
+          poolAuthority: values.poolAuthority,
+          mintLiquidity: values.mintLiquidity,
+          mintA: values.mint_a.publicKey,
+          mintB: values.mint_b.publicKey,
+          poolAccountA: values.poolAccountA,
+          poolAccountB: values.poolAccountB,
+          depositorAccountLiquidity: getAssociatedTokenAddressSync(
+            values.mintLiquidity,
+            values.admin.publicKey,
+            true
+          ),
+          depositorAccountA: getAssociatedTokenAddressSync(
+            values.mint_a.publicKey,
+            values.admin.publicKey,
+            true
+          ),
+          depositorAccountB: getAssociatedTokenAddressSync(
+            values.mint_b.publicKey,
+            values.admin.publicKey,
+            true
+          ),
+          depositor: values.admin.publicKey,
+          payer: provider.wallet.publicKey,
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+          associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        } as any)
+        .signers([values.admin])
+        .rpc()
+    );
+  });
+
+  it("Failure: Minted liquidity below min_liquidity_out", async () => {
+    // sqrt(100 * 200) - MINIMUM_LIQUIDITY ≈ 141.42个LP，但要求至少200个
+    await expectRevert(
+      program.methods
+        .depositLiquidity(
+          new anchor.BN(100 * 10 ** 6),
+          new anchor.BN(200 * 10 ** 6),
+          new anchor.BN(200 * 10 ** 6),
+          null,
+          null,
+          null
         )
         .accounts({
+          amm: values.ammPda,
           pool: values.poolPda,
           poolAuthority: values.poolAuthority,
           mintLiquidity: values.mintLiquidity,
@@ -453,6 +547,102 @@ describe("Deposit Liquidity", () => {
           depositor: values.admin.publicKey,
           payer: provider.wallet.publicKey,
           tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+          associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        } as any)
+        .signers([values.admin])
+        .rpc()
+    );
+  });
+
+  it("Failure: Re-ratioed deposit above max_amount_a", async () => {
+    // 初始比例 1:2
+    await program.methods
+      .depositLiquidity(
+        new anchor.BN(100 * 10 ** 6),
+        new anchor.BN(200 * 10 ** 6),
+        new anchor.BN(0),
+        null,
+        null,
+        null
+      )
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        mintLiquidity: values.mintLiquidity,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        depositorAccountLiquidity: getAssociatedTokenAddressSync(
+          values.mintLiquidity,
+          values.admin.publicKey,
+          true
+        ),
+        depositorAccountA: getAssociatedTokenAddressSync(
+          values.mint_a.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        depositorAccountB: getAssociatedTokenAddressSync(
+          values.mint_b.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        depositor: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+    // 想存10个A和100个B，按池子比例会调整成50个A和100个B，超过了max_amount_a
+    await expectRevert(
+      program.methods
+        .depositLiquidity(
+          new anchor.BN(10 * 10 ** 6),
+          new anchor.BN(100 * 10 ** 6),
+          new anchor.BN(0),
+          new anchor.BN(10 * 10 ** 6),
+          null,
+          null
+        )
+        .accounts({
+          amm: values.ammPda,
+          pool: values.poolPda,
+          poolAuthority: values.poolAuthority,
+          mintLiquidity: values.mintLiquidity,
+          mintA: values.mint_a.publicKey,
+          mintB: values.mint_b.publicKey,
+          poolAccountA: values.poolAccountA,
+          poolAccountB: values.poolAccountB,
+          depositorAccountLiquidity: getAssociatedTokenAddressSync(
+            values.mintLiquidity,
+            values.admin.publicKey,
+            true
+          ),
+          depositorAccountA: getAssociatedTokenAddressSync(
+            values.mint_a.publicKey,
+            values.admin.publicKey,
+            true
+          ),
+          depositorAccountB: getAssociatedTokenAddressSync(
+            values.mint_b.publicKey,
+            values.admin.publicKey,
+            true
+          ),
+          depositor: values.admin.publicKey,
+          payer: provider.wallet.publicKey,
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: anchor.web3.SystemProgram.programId,
         } as any)
@@ -467,8 +657,16 @@ describe("Deposit Liquidity", () => {
     const amountB = 1000 * 10 ** 6; // 1000 token B
 
     await program.methods
-      .depositLiquidity(new anchor.BN(amountA), new anchor.BN(amountB))
+      .depositLiquidity(
+        new anchor.BN(amountA),
+        new anchor.BN(amountB),
+        new anchor.BN(0),
+        null,
+        null,
+        null
+      )
       .accounts({
+        amm: values.ammPda,
         pool: values.poolPda,
         poolAuthority: values.poolAuthority,
         mintLiquidity: values.mintLiquidity,
@@ -494,6 +692,8 @@ describe("Deposit Liquidity", () => {
         depositor: values.admin.publicKey,
         payer: provider.wallet.publicKey,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -520,8 +720,16 @@ describe("Deposit Liquidity", () => {
 
     // 验证后续存款仍然维持比例
     await program.methods
-      .depositLiquidity(new anchor.BN(amountA), new anchor.BN(amountB))
+      .depositLiquidity(
+        new anchor.BN(amountA),
+        new anchor.BN(amountB),
+        new anchor.BN(0),
+        null,
+        null,
+        null
+      )
       .accounts({
+        amm: values.ammPda,
         pool: values.poolPda,
         poolAuthority: values.poolAuthority,
         mintLiquidity: values.mintLiquidity,
@@ -547,6 +755,8 @@ describe("Deposit Liquidity", () => {
         depositor: values.admin.publicKey,
         payer: provider.wallet.publicKey,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
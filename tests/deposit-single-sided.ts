@@ -0,0 +1,222 @@
+import * as anchor from "@coral-xyz/anchor";
+import type { Program } from "@coral-xyz/anchor";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+import { expect } from "chai";
+import { MyAmmProject } from "../target/types/my_amm_project";
+import {
+  type TestValues,
+  createTestValues,
+  expectRevert,
+  mintingTokens,
+} from "./utils";
+
+describe("Single-sided deposit", () => {
+  const provider = anchor.AnchorProvider.env();
+  const connection = provider.connection;
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.MyAmmProject as Program<MyAmmProject>;
+
+  let values: TestValues;
+
+  const initialAmountA = 1000 * 10 ** 6;
+  const initialAmountB = 2000 * 10 ** 6;
+
+  const adminAccount = (mint: anchor.web3.PublicKey) =>
+    getAssociatedTokenAddressSync(mint, values.admin.publicKey, true);
+
+  const balance = async (account: anchor.web3.PublicKey) =>
+    Number((await connection.getTokenAccountBalance(account)).value.amount);
+
+  const deposit = () =>
+    program.methods
+      .depositLiquidity(
+        new anchor.BN(initialAmountA),
+        new anchor.BN(initialAmountB),
+        new anchor.BN(0),
+        null,
+        null,
+        null
+      )
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        mintLiquidity: values.mintLiquidity,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        depositorAccountLiquidity: adminAccount(values.mintLiquidity),
+        depositorAccountA: adminAccount(values.mint_a.publicKey),
+        depositorAccountB: adminAccount(values.mint_b.publicKey),
+        depositor: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+  const depositSingleSided = (
+    depositA: boolean,
+    amountIn: number,
+    minLiquidityOut: number
+  ) =>
+    program.methods
+      .depositSingleSided(
+        depositA,
+        new anchor.BN(amountIn),
+        new anchor.BN(minLiquidityOut),
+        null
+      )
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        mintLiquidity: values.mintLiquidity,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        depositorAccountLiquidity: adminAccount(values.mintLiquidity),
+        depositorAccountA: adminAccount(values.mint_a.publicKey),
+        depositorAccountB: adminAccount(values.mint_b.publicKey),
+        depositor: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+  beforeEach(async () => {
+    // Setup: 生成测试数据
+    values = createTestValues();
+
+    // Setup: 创建AMM
+    await program.methods
+      .createAmm(values.id, values.fee, new anchor.BN(values.feeDelay))
+      .accounts({
+        amm: values.ammPda,
+        admin: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+
+    // Setup: 创建代币并给admin铸造
+    await mintingTokens({
+      connection,
+      creator: values.admin,
+      holder: values.admin,
+      mint_a: values.mint_a,
+      mint_b: values.mint_b,
+      mintedAmount: 10000, // 10000个代币用于测试
+      decimals: 6,
+    });
+
+    // Setup: 创建Pool
+    await program.methods
+      .createPool()
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        mintLiquidity: values.mintLiquidity,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        payer: provider.wallet.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+  });
+
+  it("Success: Deposit only token A", async () => {
+    await deposit();
+    const liquidityBefore = await balance(adminAccount(values.mintLiquidity));
+    const balanceABefore = await balance(adminAccount(values.mint_a.publicKey));
+    const balanceBBefore = await balance(adminAccount(values.mint_b.publicKey));
+
+    const amountIn = 100 * 10 ** 6;
+    await depositSingleSided(true, amountIn, 0);
+
+    // 只转入了A，B的余额不变，换出的B留在池子里一起存入
+    expect(await balance(adminAccount(values.mint_a.publicKey))).to.equal(
+      balanceABefore - amountIn
+    );
+    expect(await balance(adminAccount(values.mint_b.publicKey))).to.equal(
+      balanceBBefore
+    );
+    expect(await balance(values.poolAccountA)).to.equal(initialAmountA + amountIn);
+    expect(await balance(values.poolAccountB)).to.equal(initialAmountB);
+
+    // 最优swap约50.06个A换成约90.80个B，LP = sqrt(49.94 * 90.80)
+    const minted = (await balance(adminAccount(values.mintLiquidity))) - liquidityBefore;
+    expect(minted).to.be.closeTo(67_337_567, 1000);
+  });
+
+  it("Success: Deposit only token B", async () => {
+    await deposit();
+    const liquidityBefore = await balance(adminAccount(values.mintLiquidity));
+    const balanceABefore = await balance(adminAccount(values.mint_a.publicKey));
+
+    const amountIn = 200 * 10 ** 6;
+    await depositSingleSided(false, amountIn, 0);
+
+    expect(await balance(adminAccount(values.mint_a.publicKey))).to.equal(
+      balanceABefore
+    );
+    expect(await balance(values.poolAccountB)).to.equal(initialAmountB + amountIn);
+    expect(
+      (await balance(adminAccount(values.mintLiquidity))) - liquidityBefore
+    ).to.be.greaterThan(0);
+  });
+
+  it("Success: Emits a Swapped event for the internal swap", async () => {
+    await deposit();
+
+    // 内部swap也会移动价格，索引器要能看到
+    let event: any;
+    const listener = program.addEventListener("swapped", (e) => {
+      event = e;
+    });
+
+    const amountIn = 100 * 10 ** 6;
+    await depositSingleSided(true, amountIn, 0);
+
+    // 等待事件推送
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    await program.removeEventListener(listener);
+
+    expect(event.pool.toString()).to.equal(values.poolPda.toString());
+    expect(event.trader.toString()).to.equal(values.admin.publicKey.toString());
+    expect(event.swapA).to.equal(true);
+    expect(event.inputAmount.toNumber()).to.be.closeTo(50_060_000, 100_000);
+    expect(event.outputAmount.toNumber()).to.be.closeTo(90_800_000, 100_000);
+    // 储备是swap之后、存款之前的
+    expect(event.reserveA.toNumber()).to.equal(initialAmountA + event.inputAmount.toNumber());
+    expect(event.reserveB.toNumber()).to.equal(initialAmountB - event.outputAmount.toNumber());
+  });
+
+  it("Failure: Minted liquidity below the minimum", async () => {
+    await deposit();
+    await expectRevert(depositSingleSided(true, 100 * 10 ** 6, 68 * 10 ** 6));
+  });
+
+  it("Failure: Empty pool has no price", async () => {
+    await expectRevert(depositSingleSided(true, 100 * 10 ** 6, 0));
+  });
+});
@@ -0,0 +1,243 @@
+import * as anchor from "@coral-xyz/anchor";
+import type { Program } from "@coral-xyz/anchor";
+import { SYSVAR_INSTRUCTIONS_PUBKEY, Transaction } from "@solana/web3.js";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+import { expect } from "chai";
+import { MyAmmProject } from "../target/types/my_amm_project";
+import {
+  type TestValues,
+  createTestValues,
+  expectRevert,
+  mintingTokens,
+} from "./utils";
+
+describe("Flash loan", () => {
+  const provider = anchor.AnchorProvider.env();
+  const connection = provider.connection;
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.MyAmmProject as Program<MyAmmProject>;
+
+  let values: TestValues;
+
+  const initialAmountA = 1000 * 10 ** 6;
+  const initialAmountB = 2000 * 10 ** 6;
+
+  const adminAccount = (mint: anchor.web3.PublicKey) =>
+    getAssociatedTokenAddressSync(mint, values.admin.publicKey, true);
+
+  const flashBorrow = (amountA: number, amountB: number) =>
+    program.methods
+      .flashBorrow(new anchor.BN(amountA), new anchor.BN(amountB))
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        borrower: values.admin.publicKey,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        borrowerAccountA: adminAccount(values.mint_a.publicKey),
+        borrowerAccountB: adminAccount(values.mint_b.publicKey),
+        payer: provider.wallet.publicKey,
+        instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any);
+
+  const flashRepay = () =>
+    program.methods.flashRepay().accounts({
+      pool: values.poolPda,
+      poolAuthority: values.poolAuthority,
+      borrower: values.admin.publicKey,
+      mintA: values.mint_a.publicKey,
+      mintB: values.mint_b.publicKey,
+      poolAccountA: values.poolAccountA,
+      poolAccountB: values.poolAccountB,
+      borrowerAccountA: adminAccount(values.mint_a.publicKey),
+      borrowerAccountB: adminAccount(values.mint_b.publicKey),
+      tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+      tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+    } as any);
+
+  const swap = () =>
+    program.methods
+      .swapExactTokensForTokens(true, new anchor.BN(10 * 10 ** 6), new anchor.BN(0), null)
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        trader: values.admin.publicKey,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        traderAccountA: adminAccount(values.mint_a.publicKey),
+        traderAccountB: adminAccount(values.mint_b.publicKey),
+        payer: provider.wallet.publicKey,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any);
+
+  // 借款和还款必须放在同一笔交易里
+  const sendTransaction = async (
+    ...instructions: anchor.web3.TransactionInstruction[]
+  ) => {
+    const transaction = new Transaction().add(...instructions);
+    return provider.sendAndConfirm(transaction, [values.admin]);
+  };
+
+  beforeEach(async () => {
+    // Setup: 生成测试数据
+    values = createTestValues();
+
+    // Setup: 创建AMM
+    await program.methods
+      .createAmm(values.id, values.fee, new anchor.BN(values.feeDelay))
+      .accounts({
+        amm: values.ammPda,
+        admin: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+
+    // Setup: 创建代币并给admin铸造
+    await mintingTokens({
+      connection,
+      creator: values.admin,
+      holder: values.admin,
+      mint_a: values.mint_a,
+      mint_b: values.mint_b,
+      mintedAmount: 10000, // 10000个代币用于测试
+      decimals: 6,
+    });
+
+    // Setup: 创建Pool
+    await program.methods
+      .createPool()
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        mintLiquidity: values.mintLiquidity,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        payer: provider.wallet.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+
+    // Setup: 存入初始流动性
+    await program.methods
+      .depositLiquidity(
+        new anchor.BN(initialAmountA),
+        new anchor.BN(initialAmountB),
+        new anchor.BN(0),
+        null,
+        null,
+        null
+      )
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        mintLiquidity: values.mintLiquidity,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        depositorAccountLiquidity: adminAccount(values.mintLiquidity),
+        depositorAccountA: adminAccount(values.mint_a.publicKey),
+        depositorAccountB: adminAccount(values.mint_b.publicKey),
+        depositor: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+  });
+
+  it("Success: Borrow and repay in the same transaction", async () => {
+    const borrowA = 100 * 10 ** 6;
+    const borrowB = 50 * 10 ** 6;
+
+    await sendTransaction(
+      await flashBorrow(borrowA, borrowB).instruction(),
+      await flashRepay().instruction()
+    );
+
+    // 手续费5%，全部留在池子里
+    const poolA = await connection.getTokenAccountBalance(values.poolAccountA);
+    const poolB = await connection.getTokenAccountBalance(values.poolAccountB);
+    expect(Number(poolA.value.amount)).to.equal(initialAmountA + borrowA * 0.05);
+    expect(Number(poolB.value.amount)).to.equal(initialAmountB + borrowB * 0.05);
+
+    const pool = await program.account.pool.fetch(values.poolPda);
+    expect(pool.flashLoanActive).to.be.false;
+    expect(pool.flashOwedA.toNumber()).to.equal(0);
+    expect(pool.flashOwedB.toNumber()).to.equal(0);
+  });
+
+  it("Failure: Borrow without a repay instruction", async () => {
+    await expectRevert(
+      sendTransaction(await flashBorrow(100 * 10 ** 6, 0).instruction())
+    );
+  });
+
+  it("Failure: Repay placed before the borrow", async () => {
+    await expectRevert(
+      sendTransaction(
+        await flashRepay().instruction(),
+        await flashBorrow(100 * 10 ** 6, 0).instruction()
+      )
+    );
+  });
+
+  it("Failure: Repay without an active loan", async () => {
+    await expectRevert(sendTransaction(await flashRepay().instruction()));
+  });
+
+  it("Failure: Swap on the pool while the loan is active", async () => {
+    await expectRevert(
+      sendTransaction(
+        await flashBorrow(100 * 10 ** 6, 0).instruction(),
+        await swap().instruction(),
+        await flashRepay().instruction()
+      )
+    );
+  });
+
+  it("Failure: Borrow more than the reserves", async () => {
+    await expectRevert(
+      sendTransaction(
+        await flashBorrow(initialAmountA + 1, 0).instruction(),
+        await flashRepay().instruction()
+      )
+    );
+  });
+
+  it("Failure: Borrow nothing", async () => {
+    await expectRevert(
+      sendTransaction(
+        await flashBorrow(0, 0).instruction(),
+        await flashRepay().instruction()
+      )
+    );
+  });
+});
@@ -0,0 +1,285 @@
+import * as anchor from "@coral-xyz/anchor";
+import type { Program } from "@coral-xyz/anchor";
+import { PublicKey } from "@solana/web3.js";
+import { getAssociatedTokenAddressSync, transfer } from "@solana/spl-token";
+import { expect } from "chai";
+import { MyAmmProject } from "../target/types/my_amm_project";
+import {
+  type TestValues,
+  createTestValues,
+  expectRevert,
+  mintingTokens,
+} from "./utils";
+
+describe("TWAP Oracle", () => {
+  const provider = anchor.AnchorProvider.env();
+  const connection = provider.connection;
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.MyAmmProject as Program<MyAmmProject>;
+
+  let values: TestValues;
+  let oraclePda: PublicKey;
+
+  const initialAmountA = 1000 * 10 ** 6;
+  const initialAmountB = 2000 * 10 ** 6;
+
+  // TWAP是Q64.64定点数
+  const toPrice = (value: anchor.BN) => Number(value.toString()) / 2 ** 64;
+  const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
+  const initializeOracle = (capacity: number) =>
+    program.methods
+      .initializeOracle(capacity)
+      .accounts({
+        pool: values.poolPda,
+        oracle: oraclePda,
+        payer: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+
+  const deposit = () =>
+    program.methods
+      .depositLiquidity(
+        new anchor.BN(initialAmountA),
+        new anchor.BN(initialAmountB),
+        new anchor.BN(0),
+        null,
+        null,
+        null
+      )
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        oracle: oraclePda,
+        poolAuthority: values.poolAuthority,
+        mintLiquidity: values.mintLiquidity,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        depositorAccountLiquidity: getAssociatedTokenAddressSync(
+          values.mintLiquidity,
+          values.admin.publicKey,
+          true
+        ),
+        depositorAccountA: getAssociatedTokenAddressSync(
+          values.mint_a.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        depositorAccountB: getAssociatedTokenAddressSync(
+          values.mint_b.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        depositor: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+  const swap = (inputAmount: number, oracle: PublicKey | null = oraclePda) =>
+    program.methods
+      .swapExactTokensForTokens(true, new anchor.BN(inputAmount), new anchor.BN(0), null)
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        oracle,
+        poolAuthority: values.poolAuthority,
+        trader: values.admin.publicKey,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        traderAccountA: getAssociatedTokenAddressSync(
+          values.mint_a.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        traderAccountB: getAssociatedTokenAddressSync(
+          values.mint_b.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        payer: provider.wallet.publicKey,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+  // consult_twap只读取账户，用view()模拟执行并解析return data
+  const consultTwap = (window: number) =>
+    program.methods
+      .consultTwap(window)
+      .accounts({
+        pool: values.poolPda,
+        oracle: oraclePda,
+      } as any)
+      .view();
+
+  beforeEach(async () => {
+    // Setup: 生成测试数据
+    values = createTestValues();
+
+    // Setup: 创建AMM
+    await program.methods
+      .createAmm(values.id, values.fee, new anchor.BN(values.feeDelay))
+      .accounts({
+        amm: values.ammPda,
+        admin: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+
+    // Setup: 创建代币并给admin铸造
+    await mintingTokens({
+      connection,
+      creator: values.admin,
+      holder: values.admin,
+      mint_a: values.mint_a,
+      mint_b: values.mint_b,
+      mintedAmount: 10000, // 10000个代币用于测试
+      decimals: 6,
+    });
+
+    // Setup: 创建Pool
+    await program.methods
+      .createPool()
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        mintLiquidity: values.mintLiquidity,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        payer: provider.wallet.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+
+    oraclePda = PublicKey.findProgramAddressSync(
+      [values.poolPda.toBuffer(), Buffer.from("oracle")],
+      program.programId
+    )[0];
+  });
+
+  it("Success: Initialize an oracle with the first observation", async () => {
+    await initializeOracle(10);
+
+    const oracle = await program.account.oracle.fetch(oraclePda);
+    const pool = await program.account.pool.fetch(values.poolPda);
+    expect(oracle.pool.toString()).to.equal(values.poolPda.toString());
+    expect(oracle.capacity).to.equal(10);
+    expect(oracle.observations.length).to.equal(1);
+    expect(oracle.observations[0].timestamp.toNumber()).to.equal(
+      pool.lastPriceUpdate.toNumber()
+    );
+  });
+
+  it("Failure: Zero capacity", async () => {
+    await expectRevert(initializeOracle(0));
+  });
+
+  it("Failure: Capacity above the maximum", async () => {
+    await expectRevert(initializeOracle(201));
+  });
+
+  it("Success: Swaps accumulate prices and record observations", async () => {
+    await initializeOracle(10);
+    await deposit();
+    await sleep(2000);
+    await swap(10 * 10 ** 6);
+
+    // 存款之后价格是 B/A = 2，累加器按经过的秒数累计
+    const pool = await program.account.pool.fetch(values.poolPda);
+    expect(pool.priceBPerACumulative.gtn(0)).to.be.true;
+    expect(pool.priceAPerBCumulative.gtn(0)).to.be.true;
+
+    const oracle = await program.account.oracle.fetch(oraclePda);
+    const latest = oracle.observations[oracle.index];
+    expect(latest.timestamp.toNumber()).to.equal(pool.lastPriceUpdate.toNumber());
+    expect(latest.priceBPerACumulative.toString()).to.equal(
+      pool.priceBPerACumulative.toString()
+    );
+  });
+
+  it("Failure: Swap without the oracle once it is initialized", async () => {
+    await initializeOracle(10);
+    await deposit();
+
+    // 跳过观测点的交易会让窗口起点的插值失真，所以池子有oracle之后必须传入
+    await expectRevert(swap(10 * 10 ** 6, null));
+    expect((await program.account.pool.fetch(values.poolPda)).hasOracle).to.be.true;
+  });
+
+  it("Success: TWAP of a constant price equals the spot price", async () => {
+    await initializeOracle(10);
+    await deposit();
+    await sleep(4000);
+
+    const twap = await consultTwap(2);
+    expect(toPrice(twap.priceBPerA)).to.be.closeTo(2, 1e-9);
+    expect(toPrice(twap.priceAPerB)).to.be.closeTo(0.5, 1e-9);
+  });
+
+  it("Success: Tokens sent straight to a vault do not move the TWAP", async () => {
+    await initializeOracle(10);
+    await deposit();
+
+    // 直接转给vault的代币不经过池子的指令，价格仍然按保存的储备计算
+    await transfer(
+      connection,
+      values.admin,
+      getAssociatedTokenAddressSync(values.mint_a.publicKey, values.admin.publicKey, true),
+      values.poolAccountA,
+      values.admin,
+      initialAmountA
+    );
+    await sleep(4000);
+
+    const twap = await consultTwap(2);
+    expect(toPrice(twap.priceBPerA)).to.be.closeTo(2, 1e-9);
+  });
+
+  it("Success: A large swap barely moves the TWAP", async () => {
+    await initializeOracle(10);
+    await deposit();
+    await sleep(5000);
+
+    // 卖出和储备一样多的A，现货价格从2跌到0.5左右
+    await swap(initialAmountA);
+    const twap = await consultTwap(4);
+
+    expect(toPrice(twap.priceBPerA)).to.be.greaterThan(1.4);
+    expect(toPrice(twap.priceBPerA)).to.be.lessThanOrEqual(2);
+  });
+
+  it("Failure: Window longer than the recorded history", async () => {
+    await initializeOracle(10);
+    await deposit();
+    await expectRevert(consultTwap(1000));
+  });
+
+  it("Failure: Zero window", async () => {
+    await initializeOracle(10);
+    await deposit();
+    await expectRevert(consultTwap(0));
+  });
+});
@@ -0,0 +1,317 @@
+import * as anchor from "@coral-xyz/anchor";
+import type { Program } from "@coral-xyz/anchor";
+import { Keypair } from "@solana/web3.js";
+import {
+  getAccount,
+  getAssociatedTokenAddressSync,
+} from "@solana/spl-token";
+import { expect } from "chai";
+import { MyAmmProject } from "../target/types/my_amm_project";
+import {
+  type TestValues,
+  createTestValues,
+  expectRevert,
+  mintingTokens,
+} from "./utils";
+
+describe("Pause", () => {
+  const provider = anchor.AnchorProvider.env();
+  const connection = provider.connection;
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.MyAmmProject as Program<MyAmmProject>;
+
+  let values: TestValues;
+
+  beforeEach(async () => {
+    // Setup: 生成测试数据
+    values = createTestValues();
+
+    // Setup: 创建AMM
+    await program.methods
+      .createAmm(values.id, values.fee, new anchor.BN(values.feeDelay))
+      .accounts({
+        amm: values.ammPda,
+        admin: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+
+    // Setup: 创建代币并给admin铸造
+    await mintingTokens({
+      connection,
+      creator: values.admin,
+      holder: values.admin,
+      mint_a: values.mint_a,
+      mint_b: values.mint_b,
+      mintedAmount: 10000, // 10000个代币用于测试
+      decimals: 6,
+    });
+
+    // Setup: 创建Pool
+    await program.methods
+      .createPool()
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        mintLiquidity: values.mintLiquidity,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        payer: provider.wallet.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+
+    // Setup: 添加初始流动性 (1000个A, 2000个B)
+    const initialAmountA = 1000 * 10 ** 6;
+    const initialAmountB = 2000 * 10 ** 6;
+    
+    await program.methods
+      .depositLiquidity(
+        new anchor.BN(initialAmountA),
+        new anchor.BN(initialAmountB),
+        new anchor.BN(0),
+        null,
+        null,
+        null
+      )
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        mintLiquidity: values.mintLiquidity,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        depositorAccountLiquidity: getAssociatedTokenAddressSync(
+          values.mintLiquidity,
+          values.admin.publicKey,
+          true
+        ),
+        depositorAccountA: getAssociatedTokenAddressSync(
+          values.mint_a.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        depositorAccountB: getAssociatedTokenAddressSync(
+          values.mint_b.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        depositor: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+  });
+
+  const swapAForB = async (inputAmount: number) => {
+    await program.methods
+      .swapExactTokensForTokens(true, new anchor.BN(inputAmount), new anchor.BN(1), null)
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        trader: values.admin.publicKey,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        traderAccountA: getAssociatedTokenAddressSync(
+          values.mint_a.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        traderAccountB: getAssociatedTokenAddressSync(
+          values.mint_b.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        payer: provider.wallet.publicKey,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+  };
+
+  const depositLiquidity = async (amountA: number, amountB: number) => {
+    await program.methods
+      .depositLiquidity(
+        new anchor.BN(amountA),
+        new anchor.BN(amountB),
+        new anchor.BN(0),
+        null,
+        null,
+        null
+      )
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        mintLiquidity: values.mintLiquidity,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        depositorAccountLiquidity: getAssociatedTokenAddressSync(
+          values.mintLiquidity,
+          values.admin.publicKey,
+          true
+        ),
+        depositorAccountA: getAssociatedTokenAddressSync(
+          values.mint_a.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        depositorAccountB: getAssociatedTokenAddressSync(
+          values.mint_b.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        depositor: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+  };
+
+  const setPoolPause = async (paused: boolean) => {
+    await program.methods
+      .setPoolPause(paused)
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        admin: values.admin.publicKey,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+  };
+
+  it("Success: Paused pool rejects swaps and deposits but allows withdrawals", async () => {
+    await setPoolPause(true);
+
+    const poolAccount = await program.account.pool.fetch(values.poolPda);
+    expect(poolAccount.paused).to.equal(true);
+
+    await expectRevert(swapAForB(10 * 10 ** 6));
+    await expectRevert(depositLiquidity(10 * 10 ** 6, 20 * 10 ** 6));
+
+    // withdraw不受暂停影响，LP随时可以撤出
+    const lpTokenAddress = getAssociatedTokenAddressSync(
+      values.mintLiquidity,
+      values.admin.publicKey,
+      true
+    );
+    const beforeLPBalance = (await getAccount(connection, lpTokenAddress)).amount;
+    const withdrawAmount = beforeLPBalance / BigInt(2);
+
+    await program.methods
+      .withdrawLiquidity(
+        new anchor.BN(withdrawAmount.toString()),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        null
+      )
+      .accounts({
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        mintLiquidity: values.mintLiquidity,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        depositorAccountA: getAssociatedTokenAddressSync(
+          values.mint_a.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        depositorAccountB: getAssociatedTokenAddressSync(
+          values.mint_b.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        depositorAccountLiquidity: lpTokenAddress,
+        depositor: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+    const afterLPBalance = (await getAccount(connection, lpTokenAddress)).amount;
+    expect(afterLPBalance).to.equal(beforeLPBalance - withdrawAmount);
+  });
+
+  it("Success: Unpaused pool accepts swaps again", async () => {
+    await setPoolPause(true);
+    await setPoolPause(false);
+
+    await swapAForB(10 * 10 ** 6);
+  });
+
+  it("Success: Paused AMM rejects swaps in all of its pools", async () => {
+    await program.methods
+      .setAmmPause(true)
+      .accounts({
+        amm: values.ammPda,
+        admin: values.admin.publicKey,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+    await expectRevert(swapAForB(10 * 10 ** 6));
+    await expectRevert(depositLiquidity(10 * 10 ** 6, 20 * 10 ** 6));
+  });
+
+  it("Failure: Non-admin cannot pause", async () => {
+    const attacker = Keypair.generate();
+
+    await expectRevert(
+      program.methods
+        .setPoolPause(true)
+        .accounts({
+          amm: values.ammPda,
+          pool: values.poolPda,
+          admin: attacker.publicKey,
+        } as any)
+        .signers([attacker])
+        .rpc()
+    );
+    await expectRevert(
+      program.methods
+        .setAmmPause(true)
+        .accounts({
+          amm: values.ammPda,
+          admin: attacker.publicKey,
+        } as any)
+        .signers([attacker])
+        .rpc()
+    );
+  });
+});
@@ -0,0 +1,287 @@
+import * as anchor from "@coral-xyz/anchor";
+import type { Program } from "@coral-xyz/anchor";
+import { Keypair } from "@solana/web3.js";
+import {
+  getAccount,
+  getAssociatedTokenAddressSync,
+} from "@solana/spl-token";
+import { expect } from "chai";
+import { MyAmmProject } from "../target/types/my_amm_project";
+import {
+  type TestValues,
+  createTestValues,
+  expectRevert,
+  mintingTokens,
+} from "./utils";
+
+describe("Protocol Fees", () => {
+  const provider = anchor.AnchorProvider.env();
+  const connection = provider.connection;
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.MyAmmProject as Program<MyAmmProject>;
+
+  let values: TestValues;
+
+  beforeEach(async () => {
+    // Setup: 生成测试数据
+    values = createTestValues();
+
+    // Setup: 创建AMM
+    await program.methods
+      .createAmm(values.id, values.fee, new anchor.BN(values.feeDelay))
+      .accounts({
+        amm: values.ammPda,
+        admin: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+
+    // Setup: 创建代币并给admin铸造
+    await mintingTokens({
+      connection,
+      creator: values.admin,
+      holder: values.admin,
+      mint_a: values.mint_a,
+      mint_b: values.mint_b,
+      mintedAmount: 10000, // 10000个代币用于测试
+      decimals: 6,
+    });
+
+    // Setup: 创建Pool
+    await program.methods
+      .createPool()
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        mintLiquidity: values.mintLiquidity,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        payer: provider.wallet.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+
+    // Setup: 添加初始流动性 (1000个A, 2000个B)
+    const initialAmountA = 1000 * 10 ** 6;
+    const initialAmountB = 2000 * 10 ** 6;
+    
+    await program.methods
+      .depositLiquidity(
+        new anchor.BN(initialAmountA),
+        new anchor.BN(initialAmountB),
+        new anchor.BN(0),
+        null,
+        null,
+        null
+      )
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        mintLiquidity: values.mintLiquidity,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        depositorAccountLiquidity: getAssociatedTokenAddressSync(
+          values.mintLiquidity,
+          values.admin.publicKey,
+          true
+        ),
+        depositorAccountA: getAssociatedTokenAddressSync(
+          values.mint_a.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        depositorAccountB: getAssociatedTokenAddressSync(
+          values.mint_b.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        depositor: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+  });
+
+  const swapAForB = async (inputAmount: number) => {
+    await program.methods
+      .swapExactTokensForTokens(true, new anchor.BN(inputAmount), new anchor.BN(1), null)
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        trader: values.admin.publicKey,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        traderAccountA: getAssociatedTokenAddressSync(
+          values.mint_a.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        traderAccountB: getAssociatedTokenAddressSync(
+          values.mint_b.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        payer: provider.wallet.publicKey,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+  };
+
+  it("Success: Protocol share of the fee accrues and is collected by the treasury", async () => {
+    const treasury = Keypair.generate();
+    const protocolFee = 5000; // 手续费的50%归协议
+
+    await program.methods
+      .setProtocolFee(protocolFee, treasury.publicKey)
+      .accounts({
+        amm: values.ammPda,
+        admin: values.admin.publicKey,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+    const inputAmount = 100 * 10 ** 6;
+    await swapAForB(inputAmount);
+
+    // fee = 5%，手续费 = 5个A，其中一半归协议
+    const feeAmount = (inputAmount * values.fee) / 10000;
+    const expectedProtocolFees = Math.floor((feeAmount * protocolFee) / 10000);
+    let poolAccount = await program.account.pool.fetch(values.poolPda);
+    expect(poolAccount.protocolFeesA.toNumber()).to.equal(expectedProtocolFees);
+    expect(poolAccount.protocolFeesB.toNumber()).to.equal(0);
+
+    const treasuryAccountA = getAssociatedTokenAddressSync(
+      values.mint_a.publicKey,
+      treasury.publicKey,
+      true
+    );
+    const treasuryAccountB = getAssociatedTokenAddressSync(
+      values.mint_b.publicKey,
+      treasury.publicKey,
+      true
+    );
+    const beforePoolA = (await getAccount(connection, values.poolAccountA)).amount;
+
+    await program.methods
+      .collectProtocolFees()
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        treasury: treasury.publicKey,
+        treasuryAccountA,
+        treasuryAccountB,
+        admin: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+    // treasury收到协议手续费，池子的vault减少相同数量
+    expect(Number((await getAccount(connection, treasuryAccountA)).amount)).to.equal(
+      expectedProtocolFees
+    );
+    const afterPoolA = (await getAccount(connection, values.poolAccountA)).amount;
+    expect(Number(beforePoolA - afterPoolA)).to.equal(expectedProtocolFees);
+
+    poolAccount = await program.account.pool.fetch(values.poolPda);
+    expect(poolAccount.protocolFeesA.toNumber()).to.equal(0);
+  });
+
+  it("Success: Without a protocol fee the whole fee stays with the LPs", async () => {
+    await swapAForB(100 * 10 ** 6);
+
+    const poolAccount = await program.account.pool.fetch(values.poolPda);
+    expect(poolAccount.protocolFeesA.toNumber()).to.equal(0);
+    expect(poolAccount.protocolFeesB.toNumber()).to.equal(0);
+  });
+
+  it("Failure: Invalid protocol fee", async () => {
+    await expectRevert(
+      program.methods
+        .setProtocolFee(10001, values.admin.publicKey)
+        .accounts({
+          amm: values.ammPda,
+          admin: values.admin.publicKey,
+        } as any)
+        .signers([values.admin])
+        .rpc()
+    );
+  });
+
+  it("Failure: Non-admin cannot collect protocol fees", async () => {
+    const attacker = Keypair.generate();
+    const signature = await connection.requestAirdrop(attacker.publicKey, 10 ** 10);
+    const latestBlockhash = await connection.getLatestBlockhash();
+    await connection.confirmTransaction({
+      signature,
+      blockhash: latestBlockhash.blockhash,
+      lastValidBlockHeight: latestBlockhash.lastValidBlockHeight,
+    });
+
+    await expectRevert(
+      program.methods
+        .collectProtocolFees()
+        .accounts({
+          amm: values.ammPda,
+          pool: values.poolPda,
+          poolAuthority: values.poolAuthority,
+          mintA: values.mint_a.publicKey,
+          mintB: values.mint_b.publicKey,
+          poolAccountA: values.poolAccountA,
+          poolAccountB: values.poolAccountB,
+          treasury: values.admin.publicKey,
+          treasuryAccountA: getAssociatedTokenAddressSync(
+            values.mint_a.publicKey,
+            values.admin.publicKey,
+            true
+          ),
+          treasuryAccountB: getAssociatedTokenAddressSync(
+            values.mint_b.publicKey,
+            values.admin.publicKey,
+            true
+          ),
+          admin: attacker.publicKey,
+          payer: provider.wallet.publicKey,
+          tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+          associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        } as any)
+        .signers([attacker])
+        .rpc()
+    );
+  });
+});
@@ -0,0 +1,280 @@
+import * as anchor from "@coral-xyz/anchor";
+import type { Program } from "@coral-xyz/anchor";
+import {
+  getAccount,
+  getAssociatedTokenAddressSync,
+} from "@solana/spl-token";
+import { expect } from "chai";
+import { MyAmmProject } from "../target/types/my_amm_project";
+import {
+  type TestValues,
+  createTestValues,
+  mintingTokens,
+} from "./utils";
+
+describe("Quote", () => {
+  const provider = anchor.AnchorProvider.env();
+  const connection = provider.connection;
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.MyAmmProject as Program<MyAmmProject>;
+
+  let values: TestValues;
+
+  beforeEach(async () => {
+    // Setup: 生成测试数据
+    values = createTestValues();
+
+    // Setup: 创建AMM
+    await program.methods
+      .createAmm(values.id, values.fee, new anchor.BN(values.feeDelay))
+      .accounts({
+        amm: values.ammPda,
+        admin: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+
+    // Setup: 创建代币并给admin铸造
+    await mintingTokens({
+      connection,
+      creator: values.admin,
+      holder: values.admin,
+      mint_a: values.mint_a,
+      mint_b: values.mint_b,
+      mintedAmount: 10000, // 10000个代币用于测试
+      decimals: 6,
+    });
+
+    // Setup: 创建Pool
+    await program.methods
+      .createPool()
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        mintLiquidity: values.mintLiquidity,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        payer: provider.wallet.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+
+    // Setup: 添加初始流动性 (1000个A, 2000个B)
+    const initialAmountA = 1000 * 10 ** 6;
+    const initialAmountB = 2000 * 10 ** 6;
+    
+    await program.methods
+      .depositLiquidity(
+        new anchor.BN(initialAmountA),
+        new anchor.BN(initialAmountB),
+        new anchor.BN(0),
+        null,
+        null,
+        null
+      )
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        mintLiquidity: values.mintLiquidity,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        depositorAccountLiquidity: getAssociatedTokenAddressSync(
+          values.mintLiquidity,
+          values.admin.publicKey,
+          true
+        ),
+        depositorAccountA: getAssociatedTokenAddressSync(
+          values.mint_a.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        depositorAccountB: getAssociatedTokenAddressSync(
+          values.mint_b.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        depositor: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+  });
+
+  // 报价指令只读取账户，用view()模拟执行并解析return data
+  const quoteAccounts = () => ({
+    amm: values.ammPda,
+    pool: values.poolPda,
+    poolAuthority: values.poolAuthority,
+    mintLiquidity: values.mintLiquidity,
+    mintA: values.mint_a.publicKey,
+    mintB: values.mint_b.publicKey,
+    poolAccountA: values.poolAccountA,
+    poolAccountB: values.poolAccountB,
+    tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+    tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+  });
+
+  it("Success: quote_swap matches the actual swap", async () => {
+    const inputAmount = 100 * 10 ** 6;
+
+    const preview = await program.methods
+      .quoteSwap(true, new anchor.BN(inputAmount))
+      .accounts(quoteAccounts() as any)
+      .view();
+
+    // fee = 5%, taxed_input = 95, output = 95 * 2000 / 1095
+    const expectedTaxedInput = inputAmount * (10000 - values.fee) / 10000;
+    const expectedOutput = Math.floor(
+      (expectedTaxedInput * 2000 * 10 ** 6) / (1000 * 10 ** 6 + expectedTaxedInput)
+    );
+    expect(preview.inputAmount.toNumber()).to.equal(inputAmount);
+    expect(preview.outputAmount.toNumber()).to.equal(expectedOutput);
+    expect(preview.feeAmount.toNumber()).to.equal(inputAmount - expectedTaxedInput);
+
+    // 现价下95个A能换190个B，实际只换到约173.5个，价格冲击约8.7%
+    const spotOutput = expectedTaxedInput * 2;
+    expect(preview.priceImpact).to.equal(
+      Math.floor(((spotOutput - expectedOutput) * 10000) / spotOutput)
+    );
+
+    const traderTokenA = getAssociatedTokenAddressSync(
+      values.mint_a.publicKey,
+      values.admin.publicKey,
+      true
+    );
+    const traderTokenB = getAssociatedTokenAddressSync(
+      values.mint_b.publicKey,
+      values.admin.publicKey,
+      true
+    );
+    const beforeTraderB = (await getAccount(connection, traderTokenB)).amount;
+
+    // 用报价的输出作为min_output_amount，应该刚好成交
+    await program.methods
+      .swapExactTokensForTokens(
+        true,
+        new anchor.BN(inputAmount),
+        preview.outputAmount,
+        null
+      )
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        trader: values.admin.publicKey,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        traderAccountA: traderTokenA,
+        traderAccountB: traderTokenB,
+        payer: provider.wallet.publicKey,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+    const afterTraderB = (await getAccount(connection, traderTokenB)).amount;
+    expect(Number(afterTraderB - beforeTraderB)).to.equal(preview.outputAmount.toNumber());
+  });
+
+  it("Success: quote_deposit adjusts amounts to the pool ratio", async () => {
+    // 池子比例1:2，想存100个A和100个B，只能存50个A和100个B
+    const preview = await program.methods
+      .quoteDeposit(
+        new anchor.BN(100 * 10 ** 6),
+        new anchor.BN(100 * 10 ** 6),
+        null,
+        null
+      )
+      .accounts(quoteAccounts() as any)
+      .view();
+
+    expect(preview.amountA.toNumber()).to.equal(50 * 10 ** 6);
+    expect(preview.amountB.toNumber()).to.equal(100 * 10 ** 6);
+    // sqrt(50 * 100) * 10^6
+    expect(preview.liquidity.toNumber()).to.equal(Math.floor(Math.sqrt(5000) * 10 ** 6));
+  });
+
+  it("Success: quote_withdraw matches the actual withdrawal", async () => {
+    const lpTokenAddress = getAssociatedTokenAddressSync(
+      values.mintLiquidity,
+      values.admin.publicKey,
+      true
+    );
+    const lpBalance = (await getAccount(connection, lpTokenAddress)).amount;
+    const withdrawAmount = lpBalance / BigInt(2);
+
+    const preview = await program.methods
+      .quoteWithdraw(new anchor.BN(withdrawAmount.toString()))
+      .accounts(quoteAccounts() as any)
+      .view();
+
+    const userTokenA = getAssociatedTokenAddressSync(
+      values.mint_a.publicKey,
+      values.admin.publicKey,
+      true
+    );
+    const userTokenB = getAssociatedTokenAddressSync(
+      values.mint_b.publicKey,
+      values.admin.publicKey,
+      true
+    );
+    const beforeUserA = (await getAccount(connection, userTokenA)).amount;
+    const beforeUserB = (await getAccount(connection, userTokenB)).amount;
+
+    await program.methods
+      .withdrawLiquidity(
+        new anchor.BN(withdrawAmount.toString()),
+        preview.amountA,
+        preview.amountB,
+        null
+      )
+      .accounts({
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        mintLiquidity: values.mintLiquidity,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        depositorAccountA: userTokenA,
+        depositorAccountB: userTokenB,
+        depositorAccountLiquidity: lpTokenAddress,
+        depositor: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+    const afterUserA = (await getAccount(connection, userTokenA)).amount;
+    const afterUserB = (await getAccount(connection, userTokenB)).amount;
+    expect(Number(afterUserA - beforeUserA)).to.equal(preview.amountA.toNumber());
+    expect(Number(afterUserB - beforeUserB)).to.equal(preview.amountB.toNumber());
+  });
+});
@@ -0,0 +1,133 @@
+import * as anchor from "@coral-xyz/anchor";
+import type { Program } from "@coral-xyz/anchor";
+import { Keypair } from "@solana/web3.js";
+import { expect } from "chai";
+import { MyAmmProject } from "../target/types/my_amm_project";
+import {
+  type TestValues,
+  createTestValues,
+  expectRevert,
+  mintingTokens,
+} from "./utils";
+
+describe("Ramp Amp", () => {
+  const provider = anchor.AnchorProvider.env();
+  const connection = provider.connection;
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.MyAmmProject as Program<MyAmmProject>;
+
+  let values: TestValues;
+  const amp = 100;
+  const day = 86400;
+
+  beforeEach(async () => {
+    // Setup: 生成测试数据
+    values = createTestValues();
+
+    // Setup: 创建AMM
+    await program.methods
+      .createAmm(values.id, values.fee, new anchor.BN(values.feeDelay))
+      .accounts({
+        amm: values.ammPda,
+        admin: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+
+    // Setup: 创建代币并给admin铸造
+    await mintingTokens({
+      connection,
+      creator: values.admin,
+      holder: values.admin,
+      mint_a: values.mint_a,
+      mint_b: values.mint_b,
+      mintedAmount: 10000, // 10000个代币用于测试
+      decimals: 6,
+    });
+
+    // Setup: 创建StableSwap Pool
+    await program.methods
+      .createStablePool(new anchor.BN(amp))
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        mintLiquidity: values.mintLiquidity,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        payer: provider.wallet.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+  });
+
+  const now = async () => connection.getBlockTime(await connection.getSlot());
+
+  const rampAmp = (targetAmp: number, rampEnd: number, admin: Keypair = values.admin) =>
+    program.methods
+      .rampAmp(new anchor.BN(targetAmp), new anchor.BN(rampEnd))
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        admin: admin.publicKey,
+      } as any)
+      .signers([admin])
+      .rpc();
+
+  it("Success: Start a ramp", async () => {
+    const rampEnd = (await now()) + 2 * day;
+    await rampAmp(500, rampEnd);
+
+    const pool = await program.account.pool.fetch(values.poolPda);
+    expect(pool.amp.toNumber()).to.equal(amp);
+    expect(pool.targetAmp.toNumber()).to.equal(500);
+    expect(pool.ampRampEnd.toNumber()).to.equal(rampEnd);
+    expect(pool.ampRampStart.toNumber()).to.be.lessThan(rampEnd - day);
+  });
+
+  it("Success: Stop a ramp freezes the current amp", async () => {
+    await rampAmp(1000, (await now()) + 10 * day);
+
+    await program.methods
+      .stopRampAmp()
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        admin: values.admin.publicKey,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+    // 刚开始调整就停止，amp还在100附近
+    const pool = await program.account.pool.fetch(values.poolPda);
+    expect(pool.targetAmp.toNumber()).to.equal(pool.amp.toNumber());
+    expect(pool.amp.toNumber()).to.be.lessThan(110);
+  });
+
+  it("Fail: Ramp shorter than the minimum duration", async () => {
+    await expectRevert(rampAmp(200, (await now()) + day / 2));
+  });
+
+  it("Fail: Ramp changes amp by more than 10x", async () => {
+    await expectRevert(rampAmp(1001, (await now()) + 2 * day));
+    await expectRevert(rampAmp(9, (await now()) + 2 * day));
+  });
+
+  it("Fail: New ramp right after the previous one started", async () => {
+    await rampAmp(200, (await now()) + 2 * day);
+    await expectRevert(rampAmp(300, (await now()) + 3 * day));
+  });
+
+  it("Fail: Ramp by a non-admin", async () => {
+    const user = Keypair.generate();
+    await expectRevert(rampAmp(200, (await now()) + 2 * day, user));
+  });
+});
@@ -0,0 +1,109 @@
+import * as anchor from "@coral-xyz/anchor";
+import type { Program } from "@coral-xyz/anchor";
+import { Keypair } from "@solana/web3.js";
+import { expect } from "chai";
+import { MyAmmProject } from "../target/types/my_amm_project";
+import { type TestValues, createTestValues, expectRevert } from "./utils";
+
+describe("Set fee", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.MyAmmProject as Program<MyAmmProject>;
+
+  let values: TestValues;
+
+  const createAmm = async () => {
+    await program.methods
+      .createAmm(values.id, values.fee, new anchor.BN(values.feeDelay))
+      .accounts({
+        amm: values.ammPda,
+        admin: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+  };
+
+  beforeEach(() => {
+    values = createTestValues();
+  });
+
+  it("Success: New fee is pending until the delay has passed", async () => {
+    values.feeDelay = 24 * 60 * 60; // 1天
+    await createAmm();
+
+    await program.methods
+      .setFee(100)
+      .accounts({
+        amm: values.ammPda,
+        admin: values.admin.publicKey,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+    const ammAccount = await program.account.amm.fetch(values.ammPda);
+    // 生效中的手续费不变，新手续费处于pending状态
+    expect(ammAccount.fee).to.equal(values.fee);
+    expect(ammAccount.pendingFee).to.equal(100);
+    expect(ammAccount.pendingFeeActivation.toNumber()).to.be.greaterThan(0);
+  });
+
+  it("Success: New fee is effective immediately without delay", async () => {
+    await createAmm();
+
+    await program.methods
+      .setFee(100)
+      .accounts({
+        amm: values.ammPda,
+        admin: values.admin.publicKey,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+    // 再提一次手续费，上一次的pending_fee已经生效，会被写入fee
+    await program.methods
+      .setFee(200)
+      .accounts({
+        amm: values.ammPda,
+        admin: values.admin.publicKey,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+    const ammAccount = await program.account.amm.fetch(values.ammPda);
+    expect(ammAccount.fee).to.equal(100);
+    expect(ammAccount.pendingFee).to.equal(200);
+  });
+
+  it("Failure: Invalid fee", async () => {
+    await createAmm();
+
+    await expectRevert(
+      program.methods
+        .setFee(10000)
+        .accounts({
+          amm: values.ammPda,
+          admin: values.admin.publicKey,
+        } as any)
+        .signers([values.admin])
+        .rpc()
+    );
+  });
+
+  it("Failure: Signer is not the admin", async () => {
+    await createAmm();
+    const attacker = Keypair.generate();
+
+    await expectRevert(
+      program.methods
+        .setFee(100)
+        .accounts({
+          amm: values.ammPda,
+          admin: attacker.publicKey,
+        } as any)
+        .signers([attacker])
+        .rpc()
+    );
+  });
+});
@@ -0,0 +1,318 @@
+import * as anchor from "@coral-xyz/anchor";
+import type { Program } from "@coral-xyz/anchor";
+import {
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getMint,
+} from "@solana/spl-token";
+import { expect } from "chai";
+import { MyAmmProject } from "../target/types/my_amm_project";
+import {
+  type TestValues,
+  createTestValues,
+  expectRevert,
+  mintingTokens,
+} from "./utils";
+
+describe("StableSwap Pool", () => {
+  const provider = anchor.AnchorProvider.env();
+  const connection = provider.connection;
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.MyAmmProject as Program<MyAmmProject>;
+
+  let values: TestValues;
+  const amp = 100;
+
+  beforeEach(async () => {
+    // Setup: 生成测试数据
+    values = createTestValues();
+
+    // Setup: 创建AMM
+    await program.methods
+      .createAmm(values.id, values.fee, new anchor.BN(values.feeDelay))
+      .accounts({
+        amm: values.ammPda,
+        admin: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+
+    // Setup: 创建代币并给admin铸造
+    await mintingTokens({
+      connection,
+      creator: values.admin,
+      holder: values.admin,
+      mint_a: values.mint_a,
+      mint_b: values.mint_b,
+      mintedAmount: 10000, // 10000个代币用于测试
+      decimals: 6,
+    });
+
+    // Setup: 创建StableSwap Pool
+    await program.methods
+      .createStablePool(new anchor.BN(amp))
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        mintLiquidity: values.mintLiquidity,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        payer: provider.wallet.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+
+    // Setup: 添加初始流动性 (1000个A, 1000个B)
+    const initialAmountA = 1000 * 10 ** 6;
+    const initialAmountB = 1000 * 10 ** 6;
+    
+    await program.methods
+      .depositLiquidity(
+        new anchor.BN(initialAmountA),
+        new anchor.BN(initialAmountB),
+        new anchor.BN(0),
+        null,
+        null,
+        null
+      )
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        mintLiquidity: values.mintLiquidity,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        depositorAccountLiquidity: getAssociatedTokenAddressSync(
+          values.mintLiquidity,
+          values.admin.publicKey,
+          true
+        ),
+        depositorAccountA: getAssociatedTokenAddressSync(
+          values.mint_a.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        depositorAccountB: getAssociatedTokenAddressSync(
+          values.mint_b.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        depositor: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+  });
+
+  const traderAccounts = () => ({
+    amm: values.ammPda,
+    pool: values.poolPda,
+    poolAuthority: values.poolAuthority,
+    trader: values.admin.publicKey,
+    mintA: values.mint_a.publicKey,
+    mintB: values.mint_b.publicKey,
+    poolAccountA: values.poolAccountA,
+    poolAccountB: values.poolAccountB,
+    traderAccountA: getAssociatedTokenAddressSync(
+      values.mint_a.publicKey,
+      values.admin.publicKey,
+      true
+    ),
+    traderAccountB: getAssociatedTokenAddressSync(
+      values.mint_b.publicKey,
+      values.admin.publicKey,
+      true
+    ),
+    payer: provider.wallet.publicKey,
+    tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+    tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+    associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+    systemProgram: anchor.web3.SystemProgram.programId,
+  });
+
+  it("Success: Pool stores the curve type and amp", async () => {
+    const pool = await program.account.pool.fetch(values.poolPda);
+    expect(pool.curveType).to.deep.equal({ stableSwap: {} });
+    expect(pool.amp.toNumber()).to.equal(amp);
+
+    // 第一次存款的LP数量是不变量D（1:1时D = x + y），减去锁定的最小流动性
+    const lpBalance = (
+      await getAccount(
+        connection,
+        getAssociatedTokenAddressSync(values.mintLiquidity, values.admin.publicKey, true)
+      )
+    ).amount;
+    expect(Number(lpBalance)).to.equal(2000 * 10 ** 6 - 100);
+  });
+
+  it("Success: Swap near the peg has much lower slippage than x*y=k", async () => {
+    const inputAmount = 100 * 10 ** 6; // 储备的10%
+    const accounts = traderAccounts();
+    const beforeTraderB = (await getAccount(connection, accounts.traderAccountB)).amount;
+
+    await program.methods
+      .swapExactTokensForTokens(true, new anchor.BN(inputAmount), new anchor.BN(0), null)
+      .accounts(accounts as any)
+      .signers([values.admin])
+      .rpc();
+
+    const afterTraderB = (await getAccount(connection, accounts.traderAccountB)).amount;
+    const output = Number(afterTraderB - beforeTraderB);
+
+    // 恒定乘积：95 * 1000 / 1095 ≈ 86.76，StableSwap(amp=100)接近95
+    const taxedInput = inputAmount * (10000 - values.fee) / 10000;
+    const constantProductOutput = Math.floor(
+      (taxedInput * 1000 * 10 ** 6) / (1000 * 10 ** 6 + taxedInput)
+    );
+    expect(output).to.be.greaterThan(constantProductOutput);
+    expect(output).to.be.lessThanOrEqual(taxedInput);
+    expect(output).to.be.greaterThan(taxedInput * 0.99);
+  });
+
+  it("Success: Swap for an exact output", async () => {
+    const outputAmount = 50 * 10 ** 6;
+    const accounts = traderAccounts();
+    const beforeTraderA = (await getAccount(connection, accounts.traderAccountA)).amount;
+    const beforeTraderB = (await getAccount(connection, accounts.traderAccountB)).amount;
+
+    await program.methods
+      .swapTokensForExactTokens(
+        false,
+        new anchor.BN(outputAmount),
+        new anchor.BN(60 * 10 ** 6),
+        null
+      )
+      .accounts(accounts as any)
+      .signers([values.admin])
+      .rpc();
+
+    const afterTraderA = (await getAccount(connection, accounts.traderAccountA)).amount;
+    const afterTraderB = (await getAccount(connection, accounts.traderAccountB)).amount;
+    expect(Number(afterTraderA - beforeTraderA)).to.equal(outputAmount);
+    // 输入接近 50 / 0.95 ≈ 52.6
+    const input = Number(beforeTraderB - afterTraderB);
+    expect(input).to.be.greaterThan(outputAmount / 0.95);
+    expect(input).to.be.lessThan(outputAmount / 0.95 * 1.01);
+  });
+
+  it("Success: Deposit after swaps mints LP in proportion to the pool share", async () => {
+    // 先swap让储备偏离1:1
+    await program.methods
+      .swapExactTokensForTokens(true, new anchor.BN(300 * 10 ** 6), new anchor.BN(0), null)
+      .accounts(traderAccounts() as any)
+      .signers([values.admin])
+      .rpc();
+
+    const lpAccount = getAssociatedTokenAddressSync(
+      values.mintLiquidity,
+      values.admin.publicKey,
+      true
+    );
+    const reserveA = Number((await getAccount(connection, values.poolAccountA)).amount);
+    const supply = Number((await getMint(connection, values.mintLiquidity)).supply) + 100;
+    const lpBefore = (await getAccount(connection, lpAccount)).amount;
+    const accounts = traderAccounts();
+    const beforeTraderA = (await getAccount(connection, accounts.traderAccountA)).amount;
+
+    await program.methods
+      .depositLiquidity(
+        new anchor.BN(100 * 10 ** 6),
+        new anchor.BN(100 * 10 ** 6),
+        new anchor.BN(0),
+        null,
+        null,
+        null
+      )
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        mintLiquidity: values.mintLiquidity,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        depositorAccountLiquidity: lpAccount,
+        depositorAccountA: accounts.traderAccountA,
+        depositorAccountB: accounts.traderAccountB,
+        depositor: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+    // 非首次存款按LP总量和份额铸造，不再是D的增量本身
+    const depositedA = Number(beforeTraderA - (await getAccount(connection, accounts.traderAccountA)).amount);
+    const minted = Number((await getAccount(connection, lpAccount)).amount - lpBefore);
+    const expected = (depositedA * supply) / reserveA;
+    expect(minted).to.be.lessThanOrEqual(Math.ceil(expected));
+    expect(minted).to.be.greaterThan(expected * 0.999);
+  });
+
+  it("Fail: Amp outside the allowed range", async () => {
+    for (const invalidAmp of [0, 1_000_001]) {
+      const other = createTestValues();
+      await program.methods
+        .createAmm(other.id, other.fee, new anchor.BN(other.feeDelay))
+        .accounts({
+          amm: other.ammPda,
+          admin: other.admin.publicKey,
+          payer: provider.wallet.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        } as any)
+        .rpc();
+      await mintingTokens({
+        connection,
+        creator: other.admin,
+        holder: other.admin,
+        mint_a: other.mint_a,
+        mint_b: other.mint_b,
+        mintedAmount: 100,
+        decimals: 6,
+      });
+
+      await expectRevert(
+        program.methods
+          .createStablePool(new anchor.BN(invalidAmp))
+          .accounts({
+            amm: other.ammPda,
+            pool: other.poolPda,
+            poolAuthority: other.poolAuthority,
+            mintLiquidity: other.mintLiquidity,
+            mintA: other.mint_a.publicKey,
+            mintB: other.mint_b.publicKey,
+            poolAccountA: other.poolAccountA,
+            poolAccountB: other.poolAccountB,
+            payer: provider.wallet.publicKey,
+            tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+            tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+            tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+            associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          } as any)
+          .rpc()
+      );
+    }
+  });
+});
@@ -0,0 +1,235 @@
+import * as anchor from "@coral-xyz/anchor";
+import type { Program } from "@coral-xyz/anchor";
+import { Keypair } from "@solana/web3.js";
+import {
+  getAccount,
+  getAssociatedTokenAddressSync,
+} from "@solana/spl-token";
+import { expect } from "chai";
+import { MyAmmProject } from "../target/types/my_amm_project";
+import {
+  type TestValues,
+  createTestValues,
+  expectRevert,
+  mintingTokens,
+} from "./utils";
+
+describe("Swap Tokens For Exact Tokens", () => {
+  const provider = anchor.AnchorProvider.env();
+  const connection = provider.connection;
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.MyAmmProject as Program<MyAmmProject>;
+
+  let values: TestValues;
+
+  beforeEach(async () => {
+    // Setup: 生成测试数据
+    values = createTestValues();
+
+    // Setup: 创建AMM
+    await program.methods
+      .createAmm(values.id, values.fee, new anchor.BN(values.feeDelay))
+      .accounts({
+        amm: values.ammPda,
+        admin: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+
+    // Setup: 创建代币并给admin铸造
+    await mintingTokens({
+      connection,
+      creator: values.admin,
+      holder: values.admin,
+      mint_a: values.mint_a,
+      mint_b: values.mint_b,
+      mintedAmount: 10000, // 10000个代币用于测试
+      decimals: 6,
+    });
+
+    // Setup: 创建Pool
+    await program.methods
+      .createPool()
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        mintLiquidity: values.mintLiquidity,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        payer: provider.wallet.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+
+    // Setup: 添加初始流动性 (1000个A, 2000个B)
+    const initialAmountA = 1000 * 10 ** 6;
+    const initialAmountB = 2000 * 10 ** 6;
+    
+    await program.methods
+      .depositLiquidity(
+        new anchor.BN(initialAmountA),
+        new anchor.BN(initialAmountB),
+        new anchor.BN(0),
+        null,
+        null,
+        null
+      )
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        mintLiquidity: values.mintLiquidity,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        depositorAccountLiquidity: getAssociatedTokenAddressSync(
+          values.mintLiquidity,
+          values.admin.publicKey,
+          true
+        ),
+        depositorAccountA: getAssociatedTokenAddressSync(
+          values.mint_a.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        depositorAccountB: getAssociatedTokenAddressSync(
+          values.mint_b.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        depositor: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+  });
+
+  const swapForExact = (
+    swapA: boolean,
+    outputAmount: number,
+    maxInputAmount: number
+  ) =>
+    program.methods
+      .swapTokensForExactTokens(
+        swapA,
+        new anchor.BN(outputAmount),
+        new anchor.BN(maxInputAmount),
+        null
+      )
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        trader: values.admin.publicKey,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        traderAccountA: getAssociatedTokenAddressSync(
+          values.mint_a.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        traderAccountB: getAssociatedTokenAddressSync(
+          values.mint_b.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        payer: provider.wallet.publicKey,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+  it("Success: Receive exactly the requested amount of B", async () => {
+    const outputAmount = 100 * 10 ** 6; // 要求正好得到100个B
+    const maxInputAmount = 60 * 10 ** 6;
+
+    const traderTokenA = getAssociatedTokenAddressSync(
+      values.mint_a.publicKey,
+      values.admin.publicKey,
+      true
+    );
+    const traderTokenB = getAssociatedTokenAddressSync(
+      values.mint_b.publicKey,
+      values.admin.publicKey,
+      true
+    );
+    const beforeTraderA = (await getAccount(connection, traderTokenA)).amount;
+    const beforeTraderB = (await getAccount(connection, traderTokenB)).amount;
+    const beforePoolA = (await getAccount(connection, values.poolAccountA)).amount;
+    const beforePoolB = (await getAccount(connection, values.poolAccountB)).amount;
+
+    // taxed_input = 100 * 1000 / (2000 - 100) ≈ 52.63
+    // input = taxed_input / 0.95 ≈ 55.4
+    const expectedTaxedInput = Math.ceil(
+      (outputAmount * Number(beforePoolA)) / (Number(beforePoolB) - outputAmount)
+    );
+    const expectedInput = Math.ceil(
+      (expectedTaxedInput * 10000) / (10000 - values.fee)
+    );
+
+    await swapForExact(true, outputAmount, maxInputAmount);
+
+    const afterTraderA = (await getAccount(connection, traderTokenA)).amount;
+    const afterTraderB = (await getAccount(connection, traderTokenB)).amount;
+    const afterPoolA = (await getAccount(connection, values.poolAccountA)).amount;
+    const afterPoolB = (await getAccount(connection, values.poolAccountB)).amount;
+
+    // 用户正好得到100个B，花费的A不超过max_input_amount
+    expect(Number(afterTraderB - beforeTraderB)).to.equal(outputAmount);
+    const spent = Number(beforeTraderA - afterTraderA);
+    expect(spent).to.be.lessThanOrEqual(maxInputAmount);
+    expect(spent).to.be.approximately(expectedInput, 1);
+
+    // 验证不变量增加或保持
+    const beforeK = Number(beforePoolA) * Number(beforePoolB);
+    const afterK = Number(afterPoolA) * Number(afterPoolB);
+    expect(afterK).to.be.greaterThanOrEqual(beforeK);
+  });
+
+  it("Success: Receive exactly the requested amount of A", async () => {
+    const outputAmount = 50 * 10 ** 6;
+
+    const traderTokenA = getAssociatedTokenAddressSync(
+      values.mint_a.publicKey,
+      values.admin.publicKey,
+      true
+    );
+    const beforeTraderA = (await getAccount(connection, traderTokenA)).amount;
+
+    await swapForExact(false, outputAmount, 200 * 10 ** 6);
+
+    const afterTraderA = (await getAccount(connection, traderTokenA)).amount;
+    expect(Number(afterTraderA - beforeTraderA)).to.equal(outputAmount);
+  });
+
+  it("Failure: Required input above max_input_amount", async () => {
+    // 得到100个B至少需要约55个A
+    await expectRevert(swapForExact(true, 100 * 10 ** 6, 50 * 10 ** 6));
+  });
+
+  it("Failure: Output larger than the pool reserve", async () => {
+    await expectRevert(
+      swapForExact(true, 2000 * 10 ** 6, 100000 * 10 ** 6)
+    );
+  });
+});
@@ -0,0 +1,392 @@
+import * as anchor from "@coral-xyz/anchor";
+import type { Program } from "@coral-xyz/anchor";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import {
+  createMint,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { expect } from "chai";
+import { MyAmmProject } from "../target/types/my_amm_project";
+import {
+  type TestValues,
+  createTestValues,
+  expectRevert,
+  getPoolAddresses,
+  mintingTokens,
+  sortMints,
+} from "./utils";
+
+describe("Swap Route", () => {
+  const provider = anchor.AnchorProvider.env();
+  const connection = provider.connection;
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.MyAmmProject as Program<MyAmmProject>;
+
+  let values: TestValues;
+  let mint_c: Keypair;
+
+  const createPoolWithLiquidity = async (
+    mintX: PublicKey,
+    mintY: PublicKey,
+    amountX: number,
+    amountY: number
+  ) => {
+    const [mintA, mintB] = sortMints(mintX, mintY);
+    const [amountA, amountB] = mintA.equals(mintX)
+      ? [amountX, amountY]
+      : [amountY, amountX];
+    const pool = getPoolAddresses(values.ammPda, mintA, mintB);
+
+    await program.methods
+      .createPool()
+      .accounts({
+        amm: values.ammPda,
+        pool: pool.poolPda,
+        poolAuthority: pool.poolAuthority,
+        mintLiquidity: pool.mintLiquidity,
+        mintA,
+        mintB,
+        poolAccountA: pool.poolAccountA,
+        poolAccountB: pool.poolAccountB,
+        payer: provider.wallet.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+
+    await program.methods
+      .depositLiquidity(
+        new anchor.BN(amountA),
+        new anchor.BN(amountB),
+        new anchor.BN(0),
+        null,
+        null,
+        null
+      )
+      .accounts({
+        amm: values.ammPda,
+        pool: pool.poolPda,
+        poolAuthority: pool.poolAuthority,
+        mintLiquidity: pool.mintLiquidity,
+        mintA,
+        mintB,
+        poolAccountA: pool.poolAccountA,
+        poolAccountB: pool.poolAccountB,
+        depositorAccountLiquidity: getAssociatedTokenAddressSync(
+          pool.mintLiquidity,
+          values.admin.publicKey,
+          true
+        ),
+        depositorAccountA: getAssociatedTokenAddressSync(
+          mintA,
+          values.admin.publicKey,
+          true
+        ),
+        depositorAccountB: getAssociatedTokenAddressSync(
+          mintB,
+          values.admin.publicKey,
+          true
+        ),
+        depositor: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+    return pool;
+  };
+
+  // 一跳在remaining_accounts里的账户：
+  // [pool, pool_authority, pool_account_in, pool_account_out, mint_out, token_program_out, oracle]
+  // 不记录观测点时oracle传本程序的ID
+  const leg = (
+    pool: ReturnType<typeof getPoolAddresses>,
+    mintIn: PublicKey,
+    mintOut: PublicKey,
+    oracle: PublicKey = program.programId
+  ) => [
+    { pubkey: pool.poolPda, isWritable: true, isSigner: false },
+    { pubkey: pool.poolAuthority, isWritable: false, isSigner: false },
+    {
+      pubkey: getAssociatedTokenAddressSync(mintIn, pool.poolAuthority, true),
+      isWritable: true,
+      isSigner: false,
+    },
+    {
+      pubkey: getAssociatedTokenAddressSync(mintOut, pool.poolAuthority, true),
+      isWritable: true,
+      isSigner: false,
+    },
+    { pubkey: mintOut, isWritable: false, isSigner: false },
+    {
+      pubkey: anchor.utils.token.TOKEN_PROGRAM_ID,
+      isWritable: false,
+      isSigner: false,
+    },
+    { pubkey: oracle, isWritable: true, isSigner: false },
+  ];
+
+  const swapRoute = (
+    mintIn: PublicKey,
+    mintOut: PublicKey,
+    inputAmount: number,
+    minOutputAmount: number,
+    remainingAccounts: any[]
+  ) =>
+    program.methods
+      .swapRoute(new anchor.BN(inputAmount), new anchor.BN(minOutputAmount), null)
+      .accounts({
+        amm: values.ammPda,
+        trader: values.admin.publicKey,
+        mintIn,
+        mintOut,
+        traderAccountIn: getAssociatedTokenAddressSync(
+          mintIn,
+          values.admin.publicKey,
+          true
+        ),
+        traderAccountOut: getAssociatedTokenAddressSync(
+          mintOut,
+          values.admin.publicKey,
+          true
+        ),
+        payer: provider.wallet.publicKey,
+        tokenProgramIn: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramOut: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .remainingAccounts(remainingAccounts)
+      .signers([values.admin])
+      .rpc();
+
+  let poolAB: ReturnType<typeof getPoolAddresses>;
+  let poolBC: ReturnType<typeof getPoolAddresses>;
+
+  beforeEach(async () => {
+    // Setup: 生成测试数据
+    values = createTestValues();
+    mint_c = Keypair.generate();
+
+    // Setup: 创建AMM
+    await program.methods
+      .createAmm(values.id, values.fee, new anchor.BN(values.feeDelay))
+      .accounts({
+        amm: values.ammPda,
+        admin: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+
+    // Setup: 创建代币A、B并给admin铸造
+    await mintingTokens({
+      connection,
+      creator: values.admin,
+      holder: values.admin,
+      mint_a: values.mint_a,
+      mint_b: values.mint_b,
+      mintedAmount: 10000,
+      decimals: 6,
+    });
+
+    // Setup: 创建代币C并给admin铸造
+    await createMint(
+      connection,
+      values.admin,
+      values.admin.publicKey,
+      values.admin.publicKey,
+      6,
+      mint_c
+    );
+    await getOrCreateAssociatedTokenAccount(
+      connection,
+      values.admin,
+      mint_c.publicKey,
+      values.admin.publicKey,
+      true
+    );
+    await mintTo(
+      connection,
+      values.admin,
+      mint_c.publicKey,
+      getAssociatedTokenAddressSync(mint_c.publicKey, values.admin.publicKey, true),
+      values.admin.publicKey,
+      10000 * 10 ** 6
+    );
+
+    // Setup: A/B池子 (1000 A : 2000 B)，B/C池子 (2000 B : 4000 C)
+    poolAB = await createPoolWithLiquidity(
+      values.mint_a.publicKey,
+      values.mint_b.publicKey,
+      1000 * 10 ** 6,
+      2000 * 10 ** 6
+    );
+    poolBC = await createPoolWithLiquidity(
+      values.mint_b.publicKey,
+      mint_c.publicKey,
+      2000 * 10 ** 6,
+      4000 * 10 ** 6
+    );
+  });
+
+  it("Success: Swap A → B → C in one instruction", async () => {
+    const inputAmount = 100 * 10 ** 6;
+
+    const traderTokenA = getAssociatedTokenAddressSync(
+      values.mint_a.publicKey,
+      values.admin.publicKey,
+      true
+    );
+    const traderTokenB = getAssociatedTokenAddressSync(
+      values.mint_b.publicKey,
+      values.admin.publicKey,
+      true
+    );
+    const traderTokenC = getAssociatedTokenAddressSync(
+      mint_c.publicKey,
+      values.admin.publicKey,
+      true
+    );
+    const beforeTraderA = (await getAccount(connection, traderTokenA)).amount;
+    const beforeTraderB = (await getAccount(connection, traderTokenB)).amount;
+    const beforeTraderC = (await getAccount(connection, traderTokenC)).amount;
+
+    // 第一跳：A → B，output = taxed * 2000 / (1000 + taxed)
+    const taxed1 = inputAmount - Math.floor((inputAmount * values.fee) / 10000);
+    const output1 = Math.floor((taxed1 * 2000 * 10 ** 6) / (1000 * 10 ** 6 + taxed1));
+    // 第二跳：B → C，output = taxed * 4000 / (2000 + taxed)
+    const taxed2 = output1 - Math.floor((output1 * values.fee) / 10000);
+    const output2 = Math.floor((taxed2 * 4000 * 10 ** 6) / (2000 * 10 ** 6 + taxed2));
+
+    await swapRoute(values.mint_a.publicKey, mint_c.publicKey, inputAmount, 1, [
+      ...leg(poolAB, values.mint_a.publicKey, values.mint_b.publicKey),
+      ...leg(poolBC, values.mint_b.publicKey, mint_c.publicKey),
+    ]);
+
+    const afterTraderA = (await getAccount(connection, traderTokenA)).amount;
+    const afterTraderB = (await getAccount(connection, traderTokenB)).amount;
+    const afterTraderC = (await getAccount(connection, traderTokenC)).amount;
+
+    expect(Number(beforeTraderA - afterTraderA)).to.equal(inputAmount);
+    // 中间代币B不经过用户的账户
+    expect(afterTraderB).to.equal(beforeTraderB);
+    expect(Number(afterTraderC - beforeTraderC)).to.be.approximately(output2, 2);
+  });
+
+  it("Failure: Final output below min_output_amount", async () => {
+    // 100个A大约只能换到约340个C
+    await expectRevert(
+      swapRoute(values.mint_a.publicKey, mint_c.publicKey, 100 * 10 ** 6, 400 * 10 ** 6, [
+        ...leg(poolAB, values.mint_a.publicKey, values.mint_b.publicKey),
+        ...leg(poolBC, values.mint_b.publicKey, mint_c.publicKey),
+      ])
+    );
+  });
+
+  it("Failure: Route does not end in mint_out", async () => {
+    await expectRevert(
+      swapRoute(values.mint_a.publicKey, mint_c.publicKey, 100 * 10 ** 6, 1, [
+        ...leg(poolAB, values.mint_a.publicKey, values.mint_b.publicKey),
+      ])
+    );
+  });
+
+  it("Failure: Same pool used twice", async () => {
+    await expectRevert(
+      swapRoute(values.mint_a.publicKey, values.mint_a.publicKey, 100 * 10 ** 6, 1, [
+        ...leg(poolAB, values.mint_a.publicKey, values.mint_b.publicKey),
+        ...leg(poolAB, values.mint_b.publicKey, values.mint_a.publicKey),
+      ])
+    );
+  });
+
+  it("Success: A leg records an observation in the oracle of its pool", async () => {
+    const oraclePda = PublicKey.findProgramAddressSync(
+      [poolAB.poolPda.toBuffer(), Buffer.from("oracle")],
+      program.programId
+    )[0];
+    await program.methods
+      .initializeOracle(10)
+      .accounts({
+        pool: poolAB.poolPda,
+        oracle: oraclePda,
+        payer: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+    await new Promise((resolve) => setTimeout(resolve, 2000));
+
+    await swapRoute(values.mint_a.publicKey, mint_c.publicKey, 100 * 10 ** 6, 1, [
+      ...leg(poolAB, values.mint_a.publicKey, values.mint_b.publicKey, oraclePda),
+      ...leg(poolBC, values.mint_b.publicKey, mint_c.publicKey),
+    ]);
+
+    const pool = await program.account.pool.fetch(poolAB.poolPda);
+    const oracle = await program.account.oracle.fetch(oraclePda);
+    expect(oracle.observations.length).to.equal(2);
+    expect(oracle.observations[oracle.index].timestamp.toNumber()).to.equal(
+      pool.lastPriceUpdate.toNumber()
+    );
+    expect(pool.priceBPerACumulative.gtn(0)).to.be.true;
+  });
+
+  it("Failure: Oracle of another pool", async () => {
+    const oraclePda = PublicKey.findProgramAddressSync(
+      [poolBC.poolPda.toBuffer(), Buffer.from("oracle")],
+      program.programId
+    )[0];
+    await program.methods
+      .initializeOracle(10)
+      .accounts({
+        pool: poolBC.poolPda,
+        oracle: oraclePda,
+        payer: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+
+    await expectRevert(
+      swapRoute(values.mint_a.publicKey, mint_c.publicKey, 100 * 10 ** 6, 1, [
+        ...leg(poolAB, values.mint_a.publicKey, values.mint_b.publicKey, oraclePda),
+        ...leg(poolBC, values.mint_b.publicKey, mint_c.publicKey, oraclePda),
+      ])
+    );
+  });
+
+  it("Failure: Leg without the oracle of its pool", async () => {
+    const oraclePda = PublicKey.findProgramAddressSync(
+      [poolBC.poolPda.toBuffer(), Buffer.from("oracle")],
+      program.programId
+    )[0];
+    await program.methods
+      .initializeOracle(10)
+      .accounts({
+        pool: poolBC.poolPda,
+        oracle: oraclePda,
+        payer: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+
+    // poolBC有oracle之后，经过它的一跳必须传入oracle
+    await expectRevert(
+      swapRoute(values.mint_a.publicKey, mint_c.publicKey, 100 * 10 ** 6, 1, [
+        ...leg(poolAB, values.mint_a.publicKey, values.mint_b.publicKey),
+        ...leg(poolBC, values.mint_b.publicKey, mint_c.publicKey),
+      ])
+    );
+  });
+});
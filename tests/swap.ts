@@ -33,7 +33,7 @@ describe("Swap Exact Tokens For Tokens", () => {
 
     // Setup: 创建AMM
     await program.methods
-      .createAmm(values.id, values.fee)
+      .createAmm(values.id, values.fee, new anchor.BN(values.feeDelay))
       .accounts({
         amm: values.ammPda,
         admin: values.admin.publicKey,
@@ -67,6 +67,8 @@ describe("Swap Exact Tokens For Tokens", () => {
         poolAccountB: values.poolAccountB,
         payer: provider.wallet.publicKey,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -77,8 +79,16 @@ describe("Swap Exact Tokens For Tokens", () => {
     const initialAmountB = 2000 * 10 ** 6;
     
     await program.methods
-      .depositLiquidity(new anchor.BN(initialAmountA), new anchor.BN(initialAmountB))
+      .depositLiquidity(
+        new anchor.BN(initialAmountA),
+        new anchor.BN(initialAmountB),
+        new anchor.BN(0),
+        null,
+        null,
+        null
+      )
       .accounts({
+        amm: values.ammPda,
         pool: values.poolPda,
         poolAuthority: values.poolAuthority,
         mintLiquidity: values.mintLiquidity,
@@ -104,6 +114,8 @@ describe("Swap Exact Tokens For Tokens", () => {
         depositor: values.admin.publicKey,
         payer: provider.wallet.publicKey,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -145,7 +157,8 @@ describe("Swap Exact Tokens For Tokens", () => {
       .swapExactTokensForTokens(
         true, // swap_a = true (A -> B)
         new anchor.BN(inputAmount),
-        new anchor.BN(minOutputAmount)
+        new anchor.BN(minOutputAmount),
+        null
       )
       .accounts({
         amm: values.ammPda,
@@ -159,7 +172,8 @@ describe("Swap Exact Tokens For Tokens", () => {
         traderAccountA: traderTokenA,
         traderAccountB: traderTokenB,
         payer: provider.wallet.publicKey,
-        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -215,7 +229,8 @@ describe("Swap Exact Tokens For Tokens", () => {
       .swapExactTokensForTokens(
         false, // swap_a = false (B -> A)
         new anchor.BN(inputAmount),
-        new anchor.BN(minOutputAmount)
+        new anchor.BN(minOutputAmount),
+        null
       )
       .accounts({
         amm: values.ammPda,
@@ -229,7 +244,8 @@ describe("Swap Exact Tokens For Tokens", () => {
         traderAccountA: traderTokenA,
         traderAccountB: traderTokenB,
         payer: provider.wallet.publicKey,
-        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -267,7 +283,8 @@ describe("Swap Exact Tokens For Tokens", () => {
         .swapExactTokensForTokens(
           true,
           new anchor.BN(inputAmount),
-          new anchor.BN(unrealisticMinOutput)
+          new anchor.BN(unrealisticMinOutput),
+          null
         )
         .accounts({
           amm: values.ammPda,
@@ -289,7 +306,8 @@ describe("Swap Exact Tokens For Tokens", () => {
             true
           ),
           payer: provider.wallet.publicKey,
-          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: anchor.web3.SystemProgram.programId,
         } as any)
@@ -298,6 +316,127 @@ describe("Swap Exact Tokens For Tokens", () => {
     );
   });
 
+  it("Failure: Deadline exceeded", async () => {
+    const deadline = 1; // 早已过期的unix时间戳
+
+    await expectRevert(
+      program.methods
+        .swapExactTokensForTokens(
+          true,
+          new anchor.BN(50 * 10 ** 6),
+          new anchor.BN(1),
+          { timestamp: { unixTimestamp: new anchor.BN(deadline) } }
+        )
+        .accounts({
+          amm: values.ammPda,
+          pool: values.poolPda,
+          poolAuthority: values.poolAuthority,
+          trader: values.admin.publicKey,
+          mintA: values.mint_a.publicKey,
+          mintB: values.mint_b.publicKey,
+          poolAccountA: values.poolAccountA,
+          poolAccountB: values.poolAccountB,
+          traderAccountA: getAssociatedTokenAddressSync(
+            values.mint_a.publicKey,
+            values.admin.publicKey,
+            true
+          ),
+          traderAccountB: getAssociatedTokenAddressSync(
+            values.mint_b.publicKey,
+            values.admin.publicKey,
+            true
+          ),
+          payer: provider.wallet.publicKey,
+          tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+          associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        } as any)
+        .signers([values.admin])
+        .rpc()
+    );
+  });
+
+  it("Failure: Slot deadline exceeded", async () => {
+    const deadline = 1; // 早已过去的slot
+
+    await expectRevert(
+      program.methods
+        .swapExactTokensForTokens(
+          true,
+          new anchor.BN(50 * 10 ** 6),
+          new anchor.BN(1),
+          { slot: { slot: new anchor.BN(deadline) } }
+        )
+        .accounts({
+          amm: values.ammPda,
+          pool: values.poolPda,
+          poolAuthority: values.poolAuthority,
+          trader: values.admin.publicKey,
+          mintA: values.mint_a.publicKey,
+          mintB: values.mint_b.publicKey,
+          poolAccountA: values.poolAccountA,
+          poolAccountB: values.poolAccountB,
+          traderAccountA: getAssociatedTokenAddressSync(
+            values.mint_a.publicKey,
+            values.admin.publicKey,
+            true
+          ),
+          traderAccountB: getAssociatedTokenAddressSync(
+            values.mint_b.publicKey,
+            values.admin.publicKey,
+            true
+          ),
+          payer: provider.wallet.publicKey,
+          tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+          associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        } as any)
+        .signers([values.admin])
+        .rpc()
+    );
+  });
+
+  it("Success: Swap before the deadline", async () => {
+    const deadline = Math.floor(Date.now() / 1000) + 60 * 60; // 1小时后
+
+    await program.methods
+      .swapExactTokensForTokens(
+        true,
+        new anchor.BN(50 * 10 ** 6),
+        new anchor.BN(1),
+        { timestamp: { unixTimestamp: new anchor.BN(deadline) } }
+      )
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        trader: values.admin.publicKey,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        traderAccountA: getAssociatedTokenAddressSync(
+          values.mint_a.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        traderAccountB: getAssociatedTokenAddressSync(
+          values.mint_b.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        payer: provider.wallet.publicKey,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+  });
+
   it("Success: Swap with insufficient balance (auto-adjustment)", async () => {
     // 创建新用户，只给少量代币
     const newTrader = Keypair.generate();
@@ -338,7 +477,8 @@ describe("Swap Exact Tokens For Tokens", () => {
       .swapExactTokensForTokens(
         true,
         new anchor.BN(100 * 10 ** 6), // 想换100个，但只有50个
-        new anchor.BN(1) // 最小输出设为1
+        new anchor.BN(1), // 最小输出设为1
+        null
       )
       .accounts({
         amm: values.ammPda,
@@ -352,7 +492,8 @@ describe("Swap Exact Tokens For Tokens", () => {
         traderAccountA: traderTokenA,
         traderAccountB: traderTokenB,
         payer: provider.wallet.publicKey,
-        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -378,7 +519,7 @@ describe("Swap Exact Tokens For Tokens", () => {
 
     // 创建0手续费AMM
     await program.methods
-      .createAmm(zeroFeeId, 0) // 0% fee
+      .createAmm(zeroFeeId, 0, new anchor.BN(0)) // 0% fee
       .accounts({
         amm: zeroFeeAmmPda,
         admin: values.admin.publicKey,
@@ -442,6 +583,8 @@ describe("Swap Exact Tokens For Tokens", () => {
         poolAccountB: zeroFeePoolAccountB,
         payer: provider.wallet.publicKey,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -449,8 +592,16 @@ describe("Swap Exact Tokens For Tokens", () => {
 
     // 给零手续费池子添加相同的流动性
     await program.methods
-      .depositLiquidity(new anchor.BN(1000 * 10 ** 6), new anchor.BN(2000 * 10 ** 6))
+      .depositLiquidity(
+        new anchor.BN(1000 * 10 ** 6),
+        new anchor.BN(2000 * 10 ** 6),
+        new anchor.BN(0),
+        null,
+        null,
+        null
+      )
       .accounts({
+        amm: zeroFeeAmmPda,
         pool: zeroFeePoolPda,
         poolAuthority: zeroFeePoolAuthority,
         mintLiquidity: zeroFeeMintLiquidity,
@@ -476,6 +627,8 @@ describe("Swap Exact Tokens For Tokens", () => {
         depositor: values.admin.publicKey,
         payer: provider.wallet.publicKey,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -532,7 +685,7 @@ describe("Swap Exact Tokens For Tokens", () => {
     const beforeBalanceTrader1 = (await getAccount(connection, trader1TokenB)).amount;
 
     await program.methods
-      .swapExactTokensForTokens(true, new anchor.BN(inputAmount), new anchor.BN(1))
+      .swapExactTokensForTokens(true, new anchor.BN(inputAmount), new anchor.BN(1), null)
       .accounts({
         amm: values.ammPda,
         pool: values.poolPda,
@@ -549,7 +702,8 @@ describe("Swap Exact Tokens For Tokens", () => {
         ),
         traderAccountB: trader1TokenB,
         payer: provider.wallet.publicKey,
-        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -565,7 +719,7 @@ describe("Swap Exact Tokens For Tokens", () => {
     const beforeBalanceTrader2 = (await getAccount(connection, trader2TokenB)).amount;
 
     await program.methods
-      .swapExactTokensForTokens(true, new anchor.BN(inputAmount), new anchor.BN(1))
+      .swapExactTokensForTokens(true, new anchor.BN(inputAmount), new anchor.BN(1), null)
       .accounts({
         amm: zeroFeeAmmPda,
         pool: zeroFeePoolPda,
@@ -582,7 +736,8 @@ describe("Swap Exact Tokens For Tokens", () => {
         ),
         traderAccountB: trader2TokenB,
         payer: provider.wallet.publicKey,
-        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -625,7 +780,7 @@ describe("Swap Exact Tokens For Tokens", () => {
       const inputAmount = 10 * 10 ** 6; // 每次交换10个代币
 
       await program.methods
-        .swapExactTokensForTokens(swapA, new anchor.BN(inputAmount), new anchor.BN(1))
+        .swapExactTokensForTokens(swapA, new anchor.BN(inputAmount), new anchor.BN(1), null)
         .accounts({
           amm: values.ammPda,
           pool: values.poolPda,
@@ -646,7 +801,8 @@ describe("Swap Exact Tokens For Tokens", () => {
             true
           ),
           payer: provider.wallet.publicKey,
-          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: anchor.web3.SystemProgram.programId,
         } as any)
@@ -674,7 +830,8 @@ describe("Swap Exact Tokens For Tokens", () => {
       .swapExactTokensForTokens(
         true,
         new anchor.BN(largeInputAmount),
-        new anchor.BN(1) // 接受任何输出
+        new anchor.BN(1), // 接受任何输出
+        null
       )
       .accounts({
         amm: values.ammPda,
@@ -692,7 +849,8 @@ describe("Swap Exact Tokens For Tokens", () => {
         ),
         traderAccountB: traderTokenB,
         payer: provider.wallet.publicKey,
-        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -713,4 +871,59 @@ describe("Swap Exact Tokens For Tokens", () => {
     expect(priceImpact).to.be.greaterThan(0.1); // 大量交换应该有显著价格影响 (>10%)
     expect(outputReceived).to.be.lessThan(largeInputAmount * 2); // 由于价格影响，不能按初始比例兑换
   });
-});
\ No newline at end of file
+
+  it("Success: Emits a Swapped event with post-trade reserves", async () => {
+    const inputAmount = 100 * 10 ** 6;
+
+    // 索引器通过事件而不是msg!日志来解析交易
+    let event: any;
+    const listener = program.addEventListener("swapped", (e) => {
+      event = e;
+    });
+
+    await program.methods
+      .swapExactTokensForTokens(true, new anchor.BN(inputAmount), new anchor.BN(1), null)
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        trader: values.admin.publicKey,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        traderAccountA: getAssociatedTokenAddressSync(
+          values.mint_a.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        traderAccountB: getAssociatedTokenAddressSync(
+          values.mint_b.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        payer: provider.wallet.publicKey,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+    // 等待事件推送
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    await program.removeEventListener(listener);
+
+    const afterPoolA = (await getAccount(connection, values.poolAccountA)).amount;
+    const afterPoolB = (await getAccount(connection, values.poolAccountB)).amount;
+
+    expect(event.pool.toString()).to.equal(values.poolPda.toString());
+    expect(event.trader.toString()).to.equal(values.admin.publicKey.toString());
+    expect(event.swapA).to.equal(true);
+    expect(event.inputAmount.toNumber()).to.equal(inputAmount);
+    expect(event.feeAmount.toNumber()).to.equal((inputAmount * values.fee) / 10000);
+    expect(event.reserveA.toNumber()).to.equal(Number(afterPoolA));
+    expect(event.reserveB.toNumber()).to.equal(Number(afterPoolB));
+  });
+});
@@ -0,0 +1,402 @@
+import * as anchor from "@coral-xyz/anchor";
+import type { Program } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram, Transaction } from "@solana/web3.js";
+import {
+  ExtensionType,
+  TOKEN_2022_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  createMint,
+  getMintLen,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { expect } from "chai";
+import { MyAmmProject } from "../target/types/my_amm_project";
+import { type TestValues, createTestValues, expectRevert } from "./utils";
+
+describe("Token-2022", () => {
+  const provider = anchor.AnchorProvider.env();
+  const connection = provider.connection;
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.MyAmmProject as Program<MyAmmProject>;
+
+  let values: TestValues;
+  // 代币A的Token-2022转账手续费（基点），0表示不开启transfer-fee扩展
+  let transferFeeBps: number;
+  // 代币A属于Token-2022程序，代币B属于经典的Token程序
+  let poolAccountA: PublicKey;
+  let depositorAccountA: PublicKey;
+
+  const createPoolWithLiquidity = (tokenProgramA: PublicKey) =>
+    program.methods
+      .createPoolWithLiquidity(
+        new anchor.BN(1000 * 10 ** 6),
+        new anchor.BN(2000 * 10 ** 6)
+      )
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        mintLiquidity: values.mintLiquidity,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA,
+        poolAccountB: values.poolAccountB,
+        depositorAccountLiquidity: getAssociatedTokenAddressSync(
+          values.mintLiquidity,
+          values.admin.publicKey,
+          true
+        ),
+        depositorAccountA,
+        depositorAccountB: getAssociatedTokenAddressSync(
+          values.mint_b.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        depositor: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenProgramA,
+        tokenProgramB: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+  // 创建带transfer-fee扩展的Token-2022 mint
+  const createTransferFeeMint = async (mint: Keypair, feeBps: number) => {
+    const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+    const lamports =
+      await connection.getMinimumBalanceForRentExemption(mintLen);
+    const transaction = new Transaction().add(
+      SystemProgram.createAccount({
+        fromPubkey: values.admin.publicKey,
+        newAccountPubkey: mint.publicKey,
+        space: mintLen,
+        lamports,
+        programId: TOKEN_2022_PROGRAM_ID,
+      }),
+      createInitializeTransferFeeConfigInstruction(
+        mint.publicKey,
+        values.admin.publicKey,
+        values.admin.publicKey,
+        feeBps,
+        BigInt(10 ** 18), // 不设手续费上限
+        TOKEN_2022_PROGRAM_ID
+      ),
+      createInitializeMintInstruction(
+        mint.publicKey,
+        6,
+        values.admin.publicKey,
+        values.admin.publicKey,
+        TOKEN_2022_PROGRAM_ID
+      )
+    );
+    await provider.sendAndConfirm(transaction, [values.admin, mint]);
+  };
+
+  const setup = async () => {
+    values = createTestValues();
+
+    // Token-2022的ATA地址和经典Token的不同（ATA的种子里包含token程序）
+    poolAccountA = getAssociatedTokenAddressSync(
+      values.mint_a.publicKey,
+      values.poolAuthority,
+      true,
+      TOKEN_2022_PROGRAM_ID
+    );
+    depositorAccountA = getAssociatedTokenAddressSync(
+      values.mint_a.publicKey,
+      values.admin.publicKey,
+      true,
+      TOKEN_2022_PROGRAM_ID
+    );
+
+    await program.methods
+      .createAmm(values.id, values.fee, new anchor.BN(values.feeDelay))
+      .accounts({
+        amm: values.ammPda,
+        admin: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+
+    const signature = await connection.requestAirdrop(
+      values.admin.publicKey,
+      10 ** 10
+    );
+    const latestBlockhash = await connection.getLatestBlockhash();
+    await connection.confirmTransaction({
+      signature,
+      blockhash: latestBlockhash.blockhash,
+      lastValidBlockHeight: latestBlockhash.lastValidBlockHeight,
+    });
+
+    // 代币A：Token-2022
+    if (transferFeeBps > 0) {
+      await createTransferFeeMint(values.mint_a, transferFeeBps);
+    } else {
+      await createMint(
+        connection,
+        values.admin,
+        values.admin.publicKey,
+        values.admin.publicKey,
+        6,
+        values.mint_a,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+    }
+    await getOrCreateAssociatedTokenAccount(
+      connection,
+      values.admin,
+      values.mint_a.publicKey,
+      values.admin.publicKey,
+      true,
+      undefined,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    await mintTo(
+      connection,
+      values.admin,
+      values.mint_a.publicKey,
+      depositorAccountA,
+      values.admin.publicKey,
+      10000 * 10 ** 6,
+      [],
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+
+    // 代币B：经典Token
+    await createMint(
+      connection,
+      values.admin,
+      values.admin.publicKey,
+      values.admin.publicKey,
+      6,
+      values.mint_b
+    );
+    await getOrCreateAssociatedTokenAccount(
+      connection,
+      values.admin,
+      values.mint_b.publicKey,
+      values.admin.publicKey,
+      true
+    );
+    await mintTo(
+      connection,
+      values.admin,
+      values.mint_b.publicKey,
+      getAssociatedTokenAddressSync(
+        values.mint_b.publicKey,
+        values.admin.publicKey,
+        true
+      ),
+      values.admin.publicKey,
+      10000 * 10 ** 6
+    );
+  };
+
+  beforeEach(() => {
+    transferFeeBps = 0;
+  });
+
+  it("Success: Create a Token-2022 / Token pool and swap", async () => {
+    await setup();
+    await createPoolWithLiquidity(TOKEN_2022_PROGRAM_ID);
+
+    const vaultA = await getAccount(
+      connection,
+      poolAccountA,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    expect(Number(vaultA.amount)).to.equal(1000 * 10 ** 6);
+
+    const traderTokenB = getAssociatedTokenAddressSync(
+      values.mint_b.publicKey,
+      values.admin.publicKey,
+      true
+    );
+    const beforeB = (await getAccount(connection, traderTokenB)).amount;
+
+    const inputAmount = 100 * 10 ** 6;
+    await program.methods
+      .swapExactTokensForTokens(
+        true,
+        new anchor.BN(inputAmount),
+        new anchor.BN(1),
+        null
+      )
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        trader: values.admin.publicKey,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA,
+        poolAccountB: values.poolAccountB,
+        traderAccountA: depositorAccountA,
+        traderAccountB: traderTokenB,
+        payer: provider.wallet.publicKey,
+        tokenProgramA: TOKEN_2022_PROGRAM_ID,
+        tokenProgramB: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+    const afterVaultA = await getAccount(
+      connection,
+      poolAccountA,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    const afterB = (await getAccount(connection, traderTokenB)).amount;
+    expect(Number(afterVaultA.amount)).to.equal(1000 * 10 ** 6 + inputAmount);
+    expect(Number(afterB - beforeB)).to.be.greaterThan(0);
+  });
+
+  it("Failure: Token program does not own the mint", async () => {
+    await setup();
+    // mint_a属于Token-2022，传经典Token程序应该失败
+    await expectRevert(createPoolWithLiquidity(TOKEN_PROGRAM_ID));
+  });
+
+  it("Success: Transfer-fee mint is accounted on net amounts", async () => {
+    transferFeeBps = 100; // 1%
+    await setup();
+    await createPoolWithLiquidity(TOKEN_2022_PROGRAM_ID);
+
+    // 创建者转入1000个A，池子只收到990个
+    const reserveA = 1000 * 10 ** 6 - (1000 * 10 ** 6 * transferFeeBps) / 10000;
+    const reserveB = 2000 * 10 ** 6;
+    let vaultA = await getAccount(
+      connection,
+      poolAccountA,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    expect(Number(vaultA.amount)).to.equal(reserveA);
+
+    // LP按池子实际收到的数量铸造
+    const lpAccount = await getAccount(
+      connection,
+      getAssociatedTokenAddressSync(
+        values.mintLiquidity,
+        values.admin.publicKey,
+        true
+      )
+    );
+    expect(Number(lpAccount.amount)).to.equal(
+      Math.floor(Math.sqrt(reserveA * reserveB)) - 100
+    );
+
+    // 用100个A换B：池子收到99个A，输出按99个A计算
+    const inputAmount = 100 * 10 ** 6;
+    const netInput = inputAmount - (inputAmount * transferFeeBps) / 10000;
+    const taxed = netInput - Math.floor((netInput * values.fee) / 10000);
+    const expectedOutput = Math.floor((taxed * reserveB) / (reserveA + taxed));
+
+    const traderTokenB = getAssociatedTokenAddressSync(
+      values.mint_b.publicKey,
+      values.admin.publicKey,
+      true
+    );
+    const beforeB = (await getAccount(connection, traderTokenB)).amount;
+
+    await program.methods
+      .swapExactTokensForTokens(
+        true,
+        new anchor.BN(inputAmount),
+        new anchor.BN(1),
+        null
+      )
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        trader: values.admin.publicKey,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA,
+        poolAccountB: values.poolAccountB,
+        traderAccountA: depositorAccountA,
+        traderAccountB: traderTokenB,
+        payer: provider.wallet.publicKey,
+        tokenProgramA: TOKEN_2022_PROGRAM_ID,
+        tokenProgramB: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+
+    vaultA = await getAccount(
+      connection,
+      poolAccountA,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    const afterB = (await getAccount(connection, traderTokenB)).amount;
+    expect(Number(vaultA.amount)).to.equal(reserveA + netInput);
+    expect(Number(afterB - beforeB)).to.be.approximately(expectedOutput, 2);
+  });
+
+  it("Failure: min_output_amount applies to the amount actually received", async () => {
+    transferFeeBps = 100; // 1%
+    await setup();
+    await createPoolWithLiquidity(TOKEN_2022_PROGRAM_ID);
+
+    // 用B换A：池子转出的A会再被扣1%，用户收到的少于池子按公式算出的输出
+    const reserveA = 1000 * 10 ** 6 - (1000 * 10 ** 6 * transferFeeBps) / 10000;
+    const reserveB = 2000 * 10 ** 6;
+    const inputAmount = 100 * 10 ** 6;
+    const taxed = inputAmount - Math.floor((inputAmount * values.fee) / 10000);
+    const poolOutput = Math.floor((taxed * reserveA) / (reserveB + taxed));
+
+    await expectRevert(
+      program.methods
+        .swapExactTokensForTokens(
+          false,
+          new anchor.BN(inputAmount),
+          new anchor.BN(poolOutput),
+          null
+        )
+        .accounts({
+          amm: values.ammPda,
+          pool: values.poolPda,
+          poolAuthority: values.poolAuthority,
+          trader: values.admin.publicKey,
+          mintA: values.mint_a.publicKey,
+          mintB: values.mint_b.publicKey,
+          poolAccountA,
+          poolAccountB: values.poolAccountB,
+          traderAccountA: depositorAccountA,
+          traderAccountB: getAssociatedTokenAddressSync(
+            values.mint_b.publicKey,
+            values.admin.publicKey,
+            true
+          ),
+          payer: provider.wallet.publicKey,
+          tokenProgramA: TOKEN_2022_PROGRAM_ID,
+          tokenProgramB: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        } as any)
+        .signers([values.admin])
+        .rpc()
+    );
+  });
+});
@@ -25,6 +25,7 @@ export interface TestValues {
   // CreateAmm
   id: PublicKey;
   fee: number;
+  feeDelay: number;
   admin: Keypair;
   ammPda: PublicKey;
 
@@ -42,6 +43,7 @@ export function createTestValues(): TestValues {
   const id = Keypair.generate().publicKey;
   const admin = Keypair.generate();
   const fee = 500;
+  const feeDelay = 0;
   const [ammPda, bump_amm] = PublicKey.findProgramAddressSync(
     [id.toBuffer()],
     anchor.workspace.MyAmmProject.programId
@@ -95,6 +97,7 @@ export function createTestValues(): TestValues {
   return {
     id,
     fee,
+    feeDelay,
     admin,
     ammPda,
     mint_a,
@@ -107,6 +110,57 @@ export function createTestValues(): TestValues {
   };
 }
 
+// 池子要求 mint_a < mint_b（按pubkey字节比较），同一对代币只有一个池子
+export function sortMints(
+  mintX: PublicKey,
+  mintY: PublicKey
+): [PublicKey, PublicKey] {
+  return new BN(mintX.toBytes()).lt(new BN(mintY.toBytes()))
+    ? [mintX, mintY]
+    : [mintY, mintX];
+}
+
+// 计算某个AMM下(mintA, mintB)池子相关的所有PDA和ATA
+export function getPoolAddresses(
+  ammPda: PublicKey,
+  mintA: PublicKey,
+  mintB: PublicKey
+) {
+  const programId = anchor.workspace.MyAmmProject.programId;
+  const poolPda = PublicKey.findProgramAddressSync(
+    [ammPda.toBuffer(), mintA.toBuffer(), mintB.toBuffer()],
+    programId
+  )[0];
+  const poolAuthority = PublicKey.findProgramAddressSync(
+    [
+      ammPda.toBuffer(),
+      mintA.toBuffer(),
+      mintB.toBuffer(),
+      Buffer.from("authority"),
+    ],
+    programId
+  )[0];
+  const mintLiquidity = PublicKey.findProgramAddressSync(
+    [
+      ammPda.toBuffer(),
+      mintA.toBuffer(),
+      mintB.toBuffer(),
+      Buffer.from("liquidity"),
+    ],
+    programId
+  )[0];
+  const poolAccountA = getAssociatedTokenAddressSync(mintA, poolAuthority, true);
+  const poolAccountB = getAssociatedTokenAddressSync(mintB, poolAuthority, true);
+
+  return {
+    poolPda,
+    poolAuthority,
+    mintLiquidity,
+    poolAccountA,
+    poolAccountB,
+  };
+}
+
 export const expectRevert = async (promise: Promise<any>) => {
   try {
     await promise;
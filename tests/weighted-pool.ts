@@ -0,0 +1,259 @@
+import * as anchor from "@coral-xyz/anchor";
+import type { Program } from "@coral-xyz/anchor";
+import {
+  getAccount,
+  getAssociatedTokenAddressSync,
+} from "@solana/spl-token";
+import { expect } from "chai";
+import { MyAmmProject } from "../target/types/my_amm_project";
+import {
+  type TestValues,
+  createTestValues,
+  expectRevert,
+  mintingTokens,
+} from "./utils";
+
+describe("Weighted Pool", () => {
+  const provider = anchor.AnchorProvider.env();
+  const connection = provider.connection;
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.MyAmmProject as Program<MyAmmProject>;
+
+  let values: TestValues;
+  const weightA = 8000; // 80/20
+
+  beforeEach(async () => {
+    // Setup: 生成测试数据
+    values = createTestValues();
+
+    // Setup: 创建AMM
+    await program.methods
+      .createAmm(values.id, values.fee, new anchor.BN(values.feeDelay))
+      .accounts({
+        amm: values.ammPda,
+        admin: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+
+    // Setup: 创建代币并给admin铸造
+    await mintingTokens({
+      connection,
+      creator: values.admin,
+      holder: values.admin,
+      mint_a: values.mint_a,
+      mint_b: values.mint_b,
+      mintedAmount: 10000, // 10000个代币用于测试
+      decimals: 6,
+    });
+
+    // Setup: 创建加权Pool
+    await program.methods
+      .createWeightedPool(weightA)
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        mintLiquidity: values.mintLiquidity,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        payer: provider.wallet.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+
+    // Setup: 添加初始流动性 (800个A, 200个B)，按80/20的权重，A和B的价格都是1
+    const initialAmountA = 800 * 10 ** 6;
+    const initialAmountB = 200 * 10 ** 6;
+    
+    await program.methods
+      .depositLiquidity(
+        new anchor.BN(initialAmountA),
+        new anchor.BN(initialAmountB),
+        new anchor.BN(0),
+        null,
+        null,
+        null
+      )
+      .accounts({
+        amm: values.ammPda,
+        pool: values.poolPda,
+        poolAuthority: values.poolAuthority,
+        mintLiquidity: values.mintLiquidity,
+        mintA: values.mint_a.publicKey,
+        mintB: values.mint_b.publicKey,
+        poolAccountA: values.poolAccountA,
+        poolAccountB: values.poolAccountB,
+        depositorAccountLiquidity: getAssociatedTokenAddressSync(
+          values.mintLiquidity,
+          values.admin.publicKey,
+          true
+        ),
+        depositorAccountA: getAssociatedTokenAddressSync(
+          values.mint_a.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        depositorAccountB: getAssociatedTokenAddressSync(
+          values.mint_b.publicKey,
+          values.admin.publicKey,
+          true
+        ),
+        depositor: values.admin.publicKey,
+        payer: provider.wallet.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([values.admin])
+      .rpc();
+  });
+
+  const traderAccounts = () => ({
+    amm: values.ammPda,
+    pool: values.poolPda,
+    poolAuthority: values.poolAuthority,
+    trader: values.admin.publicKey,
+    mintA: values.mint_a.publicKey,
+    mintB: values.mint_b.publicKey,
+    poolAccountA: values.poolAccountA,
+    poolAccountB: values.poolAccountB,
+    traderAccountA: getAssociatedTokenAddressSync(
+      values.mint_a.publicKey,
+      values.admin.publicKey,
+      true
+    ),
+    traderAccountB: getAssociatedTokenAddressSync(
+      values.mint_b.publicKey,
+      values.admin.publicKey,
+      true
+    ),
+    payer: provider.wallet.publicKey,
+    tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+    tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+    associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+    systemProgram: anchor.web3.SystemProgram.programId,
+  });
+
+  it("Success: Pool stores the weights", async () => {
+    const pool = await program.account.pool.fetch(values.poolPda);
+    expect(pool.curveType).to.deep.equal({ weighted: {} });
+    expect(pool.weightA).to.equal(8000);
+    expect(pool.weightB).to.equal(2000);
+
+    // 第一次存款的LP数量是加权几何平均 800^0.8 * 200^0.2 ≈ 606.29，减去锁定的最小流动性
+    const lpBalance = (
+      await getAccount(
+        connection,
+        getAssociatedTokenAddressSync(values.mintLiquidity, values.admin.publicKey, true)
+      )
+    ).amount;
+    const expected = Math.pow(800, 0.8) * Math.pow(200, 0.2) * 10 ** 6 - 100;
+    expect(Number(lpBalance)).to.be.closeTo(expected, 10);
+  });
+
+  it("Success: Swap follows the weighted product formula", async () => {
+    const inputAmount = 10 * 10 ** 6;
+    const accounts = traderAccounts();
+    const beforeTraderB = (await getAccount(connection, accounts.traderAccountB)).amount;
+
+    await program.methods
+      .swapExactTokensForTokens(true, new anchor.BN(inputAmount), new anchor.BN(0), null)
+      .accounts(accounts as any)
+      .signers([values.admin])
+      .rpc();
+
+    const afterTraderB = (await getAccount(connection, accounts.traderAccountB)).amount;
+    const output = Number(afterTraderB - beforeTraderB);
+
+    // output = 200 * (1 - (800 / (800 + 9.5))^(0.8 / 0.2)) ≈ 9.27
+    const taxedInput = inputAmount * (10000 - values.fee) / 10000;
+    const expected =
+      200 * 10 ** 6 * (1 - Math.pow((800 * 10 ** 6) / (800 * 10 ** 6 + taxedInput), 4));
+    expect(output).to.be.closeTo(expected, 10);
+    expect(output).to.be.lessThanOrEqual(Math.floor(expected));
+  });
+
+  it("Success: Swap for an exact output", async () => {
+    const outputAmount = 5 * 10 ** 6;
+    const accounts = traderAccounts();
+    const beforeTraderA = (await getAccount(connection, accounts.traderAccountA)).amount;
+    const beforeTraderB = (await getAccount(connection, accounts.traderAccountB)).amount;
+
+    await program.methods
+      .swapTokensForExactTokens(
+        false,
+        new anchor.BN(outputAmount),
+        new anchor.BN(10 * 10 ** 6),
+        null
+      )
+      .accounts(accounts as any)
+      .signers([values.admin])
+      .rpc();
+
+    const afterTraderA = (await getAccount(connection, accounts.traderAccountA)).amount;
+    const afterTraderB = (await getAccount(connection, accounts.traderAccountB)).amount;
+    expect(Number(afterTraderA - beforeTraderA)).to.equal(outputAmount);
+
+    // taxed_input = 200 * ((800 / 795)^(0.8 / 0.2) - 1) ≈ 5.05，再除以(1 - 5%)
+    const taxedInput = 200 * 10 ** 6 * (Math.pow(800 / 795, 4) - 1);
+    const input = Number(beforeTraderB - afterTraderB);
+    expect(input).to.be.closeTo(taxedInput / 0.95, 10);
+  });
+
+  it("Fail: Weight outside the allowed range", async () => {
+    for (const invalidWeight of [0, 50, 9950, 10000]) {
+      const other = createTestValues();
+      await program.methods
+        .createAmm(other.id, other.fee, new anchor.BN(other.feeDelay))
+        .accounts({
+          amm: other.ammPda,
+          admin: other.admin.publicKey,
+          payer: provider.wallet.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        } as any)
+        .rpc();
+      await mintingTokens({
+        connection,
+        creator: other.admin,
+        holder: other.admin,
+        mint_a: other.mint_a,
+        mint_b: other.mint_b,
+        mintedAmount: 100,
+        decimals: 6,
+      });
+
+      await expectRevert(
+        program.methods
+          .createWeightedPool(invalidWeight)
+          .accounts({
+            amm: other.ammPda,
+            pool: other.poolPda,
+            poolAuthority: other.poolAuthority,
+            mintLiquidity: other.mintLiquidity,
+            mintA: other.mint_a.publicKey,
+            mintB: other.mint_b.publicKey,
+            poolAccountA: other.poolAccountA,
+            poolAccountB: other.poolAccountB,
+            payer: provider.wallet.publicKey,
+            tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+            tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+            tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+            associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          } as any)
+          .rpc()
+      );
+    }
+  });
+});
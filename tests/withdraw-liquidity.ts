@@ -32,7 +32,7 @@ describe("Withdraw Liquidity", () => {
 
     // Setup: 创建AMM
     await program.methods
-      .createAmm(values.id, values.fee)
+      .createAmm(values.id, values.fee, new anchor.BN(values.feeDelay))
       .accounts({
         amm: values.ammPda,
         admin: values.admin.publicKey,
@@ -66,6 +66,8 @@ describe("Withdraw Liquidity", () => {
         poolAccountB: values.poolAccountB,
         payer: provider.wallet.publicKey,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -78,9 +80,14 @@ describe("Withdraw Liquidity", () => {
     await program.methods
       .depositLiquidity(
         new anchor.BN(initialAmountA),
-        new anchor.BN(initialAmountB)
+        new anchor.BN(initialAmountB),
+        new anchor.BN(0),
+        null,
+        null,
+        null
       )
       .accounts({
+        amm: values.ammPda,
         pool: values.poolPda,
         poolAuthority: values.poolAuthority,
         mintLiquidity: values.mintLiquidity,
@@ -106,6 +113,8 @@ describe("Withdraw Liquidity", () => {
         depositor: values.admin.publicKey,
         payer: provider.wallet.publicKey,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -164,7 +173,12 @@ describe("Withdraw Liquidity", () => {
     const withdrawAmount = beforeLPBalance;
 
     await program.methods
-      .withdrawLiquidity(new anchor.BN(withdrawAmount.toString()))
+      .withdrawLiquidity(
+        new anchor.BN(withdrawAmount.toString()),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        null
+      )
       .accounts({
         pool: values.poolPda,
         poolAuthority: values.poolAuthority,
@@ -179,6 +193,8 @@ describe("Withdraw Liquidity", () => {
         depositor: values.admin.publicKey,
         payer: provider.wallet.publicKey,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -256,7 +272,12 @@ describe("Withdraw Liquidity", () => {
     const withdrawAmount = beforeLPBalance / BigInt(2);
 
     await program.methods
-      .withdrawLiquidity(new anchor.BN(withdrawAmount.toString()))
+      .withdrawLiquidity(
+        new anchor.BN(withdrawAmount.toString()),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        null
+      )
       .accounts({
         pool: values.poolPda,
         poolAuthority: values.poolAuthority,
@@ -279,6 +300,8 @@ describe("Withdraw Liquidity", () => {
         depositor: values.admin.publicKey,
         payer: provider.wallet.publicKey,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -349,9 +372,14 @@ describe("Withdraw Liquidity", () => {
     await program.methods
       .depositLiquidity(
         new anchor.BN(500 * 10 ** 6),
-        new anchor.BN(1000 * 10 ** 6)
+        new anchor.BN(1000 * 10 ** 6),
+        new anchor.BN(0),
+        null,
+        null,
+        null
       )
       .accounts({
+        amm: values.ammPda,
         pool: values.poolPda,
         poolAuthority: values.poolAuthority,
         mintLiquidity: values.mintLiquidity,
@@ -377,6 +405,8 @@ describe("Withdraw Liquidity", () => {
         depositor: user2.publicKey,
         payer: provider.wallet.publicKey,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -430,7 +460,12 @@ describe("Withdraw Liquidity", () => {
     console.log(`beforeUser1B: ${beforeUser1B}`);
 
     await program.methods
-      .withdrawLiquidity(new anchor.BN(user1Withdraw.toString()))
+      .withdrawLiquidity(
+        new anchor.BN(user1Withdraw.toString()),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        null
+      )
       .accounts({
         pool: values.poolPda,
         poolAuthority: values.poolAuthority,
@@ -449,6 +484,8 @@ describe("Withdraw Liquidity", () => {
         depositor: values.admin.publicKey,
         payer: provider.wallet.publicKey,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -474,7 +511,12 @@ describe("Withdraw Liquidity", () => {
     console.log(`beforeUser2B: ${beforeUser2B}`);
 
     await program.methods
-      .withdrawLiquidity(new anchor.BN(user2LP.toString()))
+      .withdrawLiquidity(
+        new anchor.BN(user2LP.toString()),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        null
+      )
       .accounts({
         pool: values.poolPda,
         poolAuthority: values.poolAuthority,
@@ -493,6 +535,8 @@ describe("Withdraw Liquidity", () => {
         depositor: user2.publicKey,
         payer: provider.wallet.publicKey,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -544,7 +588,61 @@ describe("Withdraw Liquidity", () => {
 
     await expectRevert(
       program.methods
-        .withdrawLiquidity(new anchor.BN(excessiveAmount.toString()))
+        .withdrawLiquidity(
+          new anchor.BN(excessiveAmount.toString()),
+          new anchor.BN(0),
+          new anchor.BN(0),
+          null
+        )
+        .accounts({
+          pool: values.poolPda,
+          poolAuthority: values.poolAuthority,
+          mintLiquidity: values.mintLiquidity,
+          mintA: values.mint_a.publicKey,
+          mintB: values.mint_b.publicKey,
+          poolAccountA: values.poolAccountA,
+          poolAccountB: values.poolAccountB,
+          depositorAccountA: getAssociatedTokenAddressSync(
+            values.mint_a.publicKey,
+            values.admin.publicKey,
+            true
+          ),
+          depositorAccountB: getAssociatedTokenAddressSync(
+            values.mint_b.publicKey,
+            values.admin.publicKey,
+            true
+          ),
+          depositorAccountLiquidity: lpTokenAddress,
+          depositor: values.admin.publicKey,
+          payer: provider.wallet.publicKey,
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+          associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        } as any)
+        .signers([values.admin])
+        .rpc()
+    );
+  });
+
+  it("Failure: Deadline exceeded", async () => {
+    const lpTokenAddress = getAssociatedTokenAddressSync(
+      values.mintLiquidity,
+      values.admin.publicKey,
+      true
+    );
+    const currentBalance = (await getAccount(connection, lpTokenAddress))
+      .amount;
+
+    await expectRevert(
+      program.methods
+        .withdrawLiquidity(
+          new anchor.BN(currentBalance.toString()),
+          new anchor.BN(0),
+          new anchor.BN(0),
+          new anchor.BN(1) // 早已过期的unix时间戳
+        )
         .accounts({
           pool: values.poolPda,
           poolAuthority: values.poolAuthority,
@@ -567,6 +665,59 @@ describe("Withdraw Liquidity", () => {
           depositor: values.admin.publicKey,
           payer: provider.wallet.publicKey,
           tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
+          associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        } as any)
+        .signers([values.admin])
+        .rpc()
+    );
+  });
+
+  it("Failure: Withdrawn amount below min_amount_a", async () => {
+    const lpTokenAddress = getAssociatedTokenAddressSync(
+      values.mintLiquidity,
+      values.admin.publicKey,
+      true
+    );
+    const currentBalance = (await getAccount(connection, lpTokenAddress))
+      .amount;
+    const poolA = (await getAccount(connection, values.poolAccountA)).amount;
+
+    // 要求得到的A比池子里全部的A还多
+    await expectRevert(
+      program.methods
+        .withdrawLiquidity(
+          new anchor.BN(currentBalance.toString()),
+          new anchor.BN((poolA + BigInt(1)).toString()),
+          new anchor.BN(0),
+          null
+        )
+        .accounts({
+          pool: values.poolPda,
+          poolAuthority: values.poolAuthority,
+          mintLiquidity: values.mintLiquidity,
+          mintA: values.mint_a.publicKey,
+          mintB: values.mint_b.publicKey,
+          poolAccountA: values.poolAccountA,
+          poolAccountB: values.poolAccountB,
+          depositorAccountA: getAssociatedTokenAddressSync(
+            values.mint_a.publicKey,
+            values.admin.publicKey,
+            true
+          ),
+          depositorAccountB: getAssociatedTokenAddressSync(
+            values.mint_b.publicKey,
+            values.admin.publicKey,
+            true
+          ),
+          depositorAccountLiquidity: lpTokenAddress,
+          depositor: values.admin.publicKey,
+          payer: provider.wallet.publicKey,
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: anchor.web3.SystemProgram.programId,
         } as any)
@@ -583,7 +734,8 @@ describe("Withdraw Liquidity", () => {
       .swapExactTokensForTokens(
         true,
         new anchor.BN(swapAmount),
-        new anchor.BN(1)
+        new anchor.BN(1),
+        null
       )
       .accounts({
         amm: values.ammPda,
@@ -605,7 +757,8 @@ describe("Withdraw Liquidity", () => {
           true
         ),
         payer: provider.wallet.publicKey,
-        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -646,7 +799,12 @@ describe("Withdraw Liquidity", () => {
     const withdrawAmount = lpBalance / BigInt(2);
 
     await program.methods
-      .withdrawLiquidity(new anchor.BN(withdrawAmount.toString()))
+      .withdrawLiquidity(
+        new anchor.BN(withdrawAmount.toString()),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        null
+      )
       .accounts({
         pool: values.poolPda,
         poolAuthority: values.poolAuthority,
@@ -661,6 +819,8 @@ describe("Withdraw Liquidity", () => {
         depositor: values.admin.publicKey,
         payer: provider.wallet.publicKey,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -724,7 +884,12 @@ describe("Withdraw Liquidity", () => {
     const beforeUserB = (await getAccount(connection, userTokenB)).amount;
 
     await program.methods
-      .withdrawLiquidity(new anchor.BN(withdrawAmount.toString()))
+      .withdrawLiquidity(
+        new anchor.BN(withdrawAmount.toString()),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        null
+      )
       .accounts({
         pool: values.poolPda,
         poolAuthority: values.poolAuthority,
@@ -739,6 +904,8 @@ describe("Withdraw Liquidity", () => {
         depositor: values.admin.publicKey,
         payer: provider.wallet.publicKey,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)
@@ -783,7 +950,12 @@ describe("Withdraw Liquidity", () => {
 
     // 提取几乎全部LP代币
     await program.methods
-      .withdrawLiquidity(new anchor.BN(lpBalance.toString()))
+      .withdrawLiquidity(
+        new anchor.BN(lpBalance.toString()),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        null
+      )
       .accounts({
         pool: values.poolPda,
         poolAuthority: values.poolAuthority,
@@ -806,6 +978,8 @@ describe("Withdraw Liquidity", () => {
         depositor: values.admin.publicKey,
         payer: provider.wallet.publicKey,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: anchor.utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: anchor.utils.token.TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
       } as any)